@@ -24,7 +24,26 @@ pub mod order {
 		fn order(&self) -> usize;
 	}
 
+	/// Order of the models with only linear terms.
 	#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+	#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+	pub struct Linear;
+
+	impl Order for Linear {
+		type NodeSetType = [usize; 1];
+		fn order(&self) -> usize {
+			1
+		}
+	}
+
+	impl Debug for Linear {
+		fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+			f.write_fmt(format_args!("{}", self.order()))
+		}
+	}
+
+	#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+	#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 	pub struct Quadric;
 
 	impl Order for Quadric {
@@ -41,6 +60,7 @@ pub mod order {
 	}
 
 	#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+	#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 	pub struct HighOrder(usize);
 
 	impl HighOrder {
@@ -1,10 +1,10 @@
-use crate::node::{Node, SingleNode};
-use crate::order::{Order, Quadric};
+use crate::node::{Binary, Node, SingleNode, Spin};
+use crate::order::{HighOrder, Linear, Order, Quadric};
 use crate::set::NodeSet;
 use crate::solution::SingleSolution;
 use crate::variable::Real;
-use std::cmp::Ordering;
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{BufRead, BufReader, Read, Write};
 use std::iter::IntoIterator;
 use std::marker::PhantomData;
 
@@ -63,6 +63,32 @@ pub trait SingleModelView: Clone {
 		let v = p.iter().map(|n| solution[n]).collect::<Vec<_>>();
 		self.node().calculate_prod(&v)
 	}
+
+	/// Extract the model of the given nodes, which are renumbered to
+	/// `0..nodes.len()` in the given order. The weights involving nodes out of
+	/// `nodes` are dropped.
+	fn subgraph(&self, nodes: &[usize]) -> FixedSingleQuadricModel<Self::Node> {
+		let index = nodes
+			.iter()
+			.enumerate()
+			.map(|(i, n)| (*n, i))
+			.collect::<HashMap<_, _>>();
+		assert!(index.len() == nodes.len(), "nodes should be distinct");
+		let mut model = FixedSingleQuadricModel::new(self.node().clone(), nodes.len());
+		for p in self.prods() {
+			let v = p
+				.iter()
+				.map(|n| index.get(&n).copied())
+				.collect::<Option<Vec<_>>>();
+			match v.as_deref() {
+				Some([i]) => model.add_weight(*i, *i, self.get_weight(&p)),
+				Some([i, j]) => model.add_weight(*i, *j, self.get_weight(&p)),
+				Some(_) => panic!("subgraph() supports only quadric models"),
+				None => (),
+			}
+		}
+		model
+	}
 }
 
 impl<T: SingleModelView> ModelView for T {
@@ -103,6 +129,104 @@ pub trait FixedSingleModelView: Clone {
 		&self,
 		p: &<Self::Order as Order>::NodeSetType,
 	) -> <Self::Node as SingleNode>::RealType;
+
+	/// See [`SparseIterableModel::nonzero_prods()`]. The default implementation
+	/// visits all the products, which takes O(n^2) time for a quadric model of
+	/// n nodes however sparse it is, so models which know their non-zero
+	/// weights should override it.
+	fn nonzero_prods(&self) -> Vec<<Self::Order as Order>::NodeSetType> {
+		Prods::new(self.order().order(), self.size())
+			.filter(|p| unsafe { self.get_weight_unchecked(p) } != Real::zero())
+			.collect()
+	}
+}
+
+/// Model which can iterate over the products of the non-zero weights without
+/// visiting all the products, which makes the setup of the solvers O(edges)
+/// for sparse models.
+pub trait SparseIterableModel: SingleModelView {
+	type NonzeroProdsIter: IntoIterator<Item = <Self::Order as Order>::NodeSetType>;
+
+	/// The products whose weights are non-zero. It may also yield products of
+	/// zero weights, which are ignored by the users.
+	fn nonzero_prods(&self) -> Self::NonzeroProdsIter;
+
+	/// The minimum and the maximum of the absolute values of the non-zero
+	/// weights, or `(0, 0)` if there is none.
+	fn weight_range(
+		&self,
+	) -> (
+		<Self::Node as SingleNode>::RealType,
+		<Self::Node as SingleNode>::RealType,
+	) {
+		abs_range(
+			self.nonzero_prods()
+				.into_iter()
+				.map(|p| self.get_weight(&p)),
+		)
+	}
+
+	/// The same as [`SparseIterableModel::weight_range()`] over the products of
+	/// `order` nodes, e.g. `1` for the linear weights and `2` for the
+	/// quadratic ones.
+	fn weight_range_of_order(
+		&self,
+		order: usize,
+	) -> (
+		<Self::Node as SingleNode>::RealType,
+		<Self::Node as SingleNode>::RealType,
+	) {
+		abs_range(
+			self.nonzero_prods()
+				.into_iter()
+				.filter(|p| p.len() == order)
+				.map(|p| self.get_weight(&p)),
+		)
+	}
+}
+
+fn abs_range<R: Real, I: Iterator<Item = R>>(weights: I) -> (R, R) {
+	weights
+		.filter(|w| *w != R::zero())
+		.map(|w| w.abs())
+		.fold(None, |range: Option<(R, R)>, w| match range {
+			Some((min, max)) => Some((min.min(w), max.max(w))),
+			None => Some((w, w)),
+		})
+		.unwrap_or((R::zero(), R::zero()))
+}
+
+/// The largest factor with which the absolute values of the linear and the
+/// quadratic weights are at most `h_range` and `j_range` respectively, or `1`
+/// if all the weights are zero. Panics if the factor is truncated to zero,
+/// which would remove all the weights.
+fn normalize_factor<P: SparseIterableModel>(
+	model: &P,
+	h_range: <P::Node as SingleNode>::RealType,
+	j_range: <P::Node as SingleNode>::RealType,
+) -> <P::Node as SingleNode>::RealType {
+	let zero = <P::Node as SingleNode>::RealType::zero();
+	let (_, h_max) = model.weight_range_of_order(1);
+	let (_, j_max) = model.weight_range_of_order(2);
+	let factor = [(h_range, h_max), (j_range, j_max)]
+		.iter()
+		.filter(|(_, max)| *max != zero)
+		.map(|(range, max)| *range / *max)
+		.reduce(|a, b| a.min(b))
+		.unwrap_or_else(<P::Node as SingleNode>::RealType::one);
+	assert!(
+		factor != zero,
+		"normalize: the range is smaller than the maximum weight"
+	);
+	factor
+}
+
+impl<P: FixedSingleModelView> SparseIterableModel for P {
+	type NonzeroProdsIter = Vec<<P::Order as Order>::NodeSetType>;
+
+	fn nonzero_prods(&self) -> Self::NonzeroProdsIter {
+		FixedSingleModelView::nonzero_prods(self)
+	}
 }
 
 /// Single Model with fixed size and no missing indexes of nodes, edges
@@ -146,13 +270,61 @@ impl<P: FixedSingleModelView> SingleModelView for P {
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "serde",
+	serde(bound(
+		serialize = "NodeType: serde::Serialize, NodeType::RealType: serde::Serialize, \
+		             O: serde::Serialize, O::NodeSetType: serde::Serialize",
+		deserialize = "NodeType: serde::Deserialize<'de>, \
+		               NodeType::RealType: serde::Deserialize<'de>, O: serde::Deserialize<'de>, \
+		               O::NodeSetType: serde::Deserialize<'de>"
+	))
+)]
 pub struct SingleModel<NodeType: SingleNode, O: Order> {
 	node: NodeType,
 	order: O,
 	nodes: BTreeSet<usize>,
+	#[cfg_attr(feature = "serde", serde(with = "serde_pairs"))]
 	inner: HashMap<O::NodeSetType, NodeType::RealType>,
 }
 
+/// Serialize the map as the sequence of the pairs, because some formats, e.g.
+/// JSON, accept only strings as the keys. The products are normalized on load,
+/// e.g. `[usize; 2]` must be sorted, and the weights of the same product are
+/// summed.
+#[cfg(feature = "serde")]
+mod serde_pairs {
+	use crate::set::NodeSet;
+	use crate::variable::Real;
+	use serde::{Deserialize, Deserializer, Serialize, Serializer};
+	use std::collections::HashMap;
+
+	pub fn serialize<K, V, S>(map: &HashMap<K, V>, s: S) -> Result<S::Ok, S::Error>
+	where
+		K: Serialize,
+		V: Serialize,
+		S: Serializer,
+	{
+		s.collect_seq(map.iter())
+	}
+
+	pub fn deserialize<'de, K, V, D>(d: D) -> Result<HashMap<K, V>, D::Error>
+	where
+		K: Deserialize<'de> + NodeSet,
+		V: Deserialize<'de> + Real,
+		D: Deserializer<'de>,
+	{
+		let mut map = HashMap::new();
+		for (k, v) in Vec::<(K, V)>::deserialize(d)? {
+			let k = K::from_vec(k.into_vec())
+				.ok_or_else(|| serde::de::Error::custom("invalid product"))?;
+			*map.entry(k).or_insert_with(V::zero) += v;
+		}
+		Ok(map)
+	}
+}
+
 impl<M: SingleNode, O: Order> SingleModel<M, O> {
 	pub fn new(node: M, order: O) -> Self {
 		Self {
@@ -170,6 +342,26 @@ impl<M: SingleNode, O: Order> SingleModel<M, O> {
 		}
 		*self.inner.entry(prod).or_insert(M::RealType::zero()) += w;
 	}
+
+	/// Set the weight of `prod` to `w`, replacing the current one.
+	pub fn set_weight(&mut self, prod: O::NodeSetType, w: M::RealType) {
+		for node in prod.iter() {
+			self.nodes.insert(node);
+		}
+		self.inner.insert(prod, w);
+	}
+
+	/// Get the mutable reference to the weight of `prod`, or `None` if it is
+	/// not stored.
+	pub fn get_weight_mut(&mut self, prod: &O::NodeSetType) -> Option<&mut M::RealType> {
+		self.inner.get_mut(prod)
+	}
+
+	/// Remove the weight of `prod` and return it. The nodes of `prod` remain in
+	/// the model.
+	pub fn remove_weight(&mut self, prod: &O::NodeSetType) -> Option<M::RealType> {
+		self.inner.remove(prod)
+	}
 }
 
 impl<M: SingleNode, O: Order> SingleModelView for SingleModel<M, O> {
@@ -212,14 +404,31 @@ impl<M: SingleNode, O: Order> SingleModelView for SingleModel<M, O> {
 	}
 }
 
+impl<M: SingleNode, O: Order> SparseIterableModel for SingleModel<M, O> {
+	type NonzeroProdsIter = Vec<O::NodeSetType>;
+
+	fn nonzero_prods(&self) -> Self::NonzeroProdsIter {
+		self.inner
+			.iter()
+			.filter(|(_, w)| **w != M::RealType::zero())
+			.map(|(p, _)| p.clone())
+			.collect()
+	}
+}
+
 // pub type SingleQuadricModel<NodeType: SingleNode> = SingleModelView<NodeType,
 // order::Quadric>;
 
+/// Model with interactions of arbitrary order, e.g. a PUBO.
+pub type HighOrderModel<NodeType> = SingleModel<NodeType, HighOrder>;
+
 #[derive(Clone)]
 pub struct FixedSingleQuadricModel<NodeType: SingleNode> {
 	size: usize,
 	node: NodeType,
 	matrix: Vec<NodeType::RealType>,
+	/// The products `[i, j]` (`i <= j`) of the non-zero weights.
+	nonzero: BTreeSet<[usize; 2]>,
 }
 
 impl<M: SingleNode> FixedSingleQuadricModel<M> {
@@ -230,6 +439,7 @@ impl<M: SingleNode> FixedSingleQuadricModel<M> {
 			matrix: std::iter::repeat(<M::RealType as Default>::default())
 				.take(size * (size + 1) / 2)
 				.collect(),
+			nonzero: BTreeSet::new(),
 		}
 	}
 
@@ -251,10 +461,373 @@ impl<M: SingleNode> FixedSingleQuadricModel<M> {
 	pub fn add_weight(&mut self, i: usize, j: usize, w: M::RealType) {
 		let idx = self.get_index(i, j);
 		self.matrix[idx] += w;
+		let prod = if i < j { [i, j] } else { [j, i] };
+		if self.matrix[idx] == M::RealType::zero() {
+			self.nonzero.remove(&prod);
+		} else {
+			self.nonzero.insert(prod);
+		}
+	}
+
+	/// Drop the products whose weights became zero from `nonzero`.
+	fn retain_nonzero(&mut self) {
+		let (matrix, nonzero) = (&self.matrix, &mut self.nonzero);
+		nonzero.retain(|p| matrix[p[1] * (p[1] + 1) / 2 + p[0]] != M::RealType::zero());
+	}
+
+	/// Zero out all weights whose absolute value is less than `threshold`.
+	pub fn prune(mut self, threshold: M::RealType) -> Self {
+		self.prune_inplace(threshold);
+		self
+	}
+
+	/// In-place version of [`FixedSingleQuadricModel::prune()`].
+	pub fn prune_inplace(&mut self, threshold: M::RealType) {
+		for w in self.matrix.iter_mut() {
+			if w.abs() < threshold {
+				*w = M::RealType::zero();
+			}
+		}
+		self.retain_nonzero();
+	}
+
+	/// Scale the weights so that the maximum of their absolute values is
+	/// `target_max`, i.e. [`Self::normalize_to()`] with the same range for the
+	/// linear and the quadratic weights. Returns the scaled model and the scale
+	/// factor, by which the energies of the scaled model are divided to get the
+	/// original ones. If all the weights are zero, the model is returned
+	/// unchanged with the factor `1`. For integer types, the factor is
+	/// truncated, so the maximum may fall short of `target_max`.
+	///
+	/// # Panics
+	///
+	/// Panics if the factor is truncated to zero, i.e. `target_max` is smaller
+	/// than the maximum of the absolute values of the weights for integer
+	/// types.
+	pub fn normalize(mut self, target_max: M::RealType) -> (Self, M::RealType) {
+		let factor = self.normalize_to(target_max, target_max);
+		(self, factor)
+	}
+
+	/// Set the weight of `[i, j]` to `w`, replacing the current one.
+	pub fn set_weight(&mut self, i: usize, j: usize, w: M::RealType) {
+		let idx = self.get_index(i, j);
+		let current = self.matrix[idx];
+		self.add_weight(i, j, w - current);
+	}
+
+	/// Set the weight of `[i, j]` to zero and return the removed one.
+	pub fn remove_weight(&mut self, i: usize, j: usize) -> M::RealType {
+		let idx = self.get_index(i, j);
+		let current = self.matrix[idx];
+		self.set_weight(i, j, M::RealType::zero());
+		current
+	}
+
+	/// Substitute `value` for the node `i`. The couplers of `i` are folded into
+	/// the linear weights of the other nodes, and all the weights of `i` are
+	/// removed, so `i` no longer affects the energy. Returns the constant
+	/// offset, which is added to the energy of the model to get the one of the
+	/// original model with `i` fixed.
+	pub fn fix_variable(&mut self, i: usize, value: bool) -> M::RealType {
+		let v = self.node.get_value(value);
+		let mut offset = M::RealType::zero();
+		for j in 0..self.size {
+			let w = self.remove_weight(i, j);
+			if w == M::RealType::zero() {
+				continue;
+			}
+			if i == j {
+				offset += w * v;
+			} else {
+				self.add_weight(j, j, w * v);
+			}
+		}
+		offset
+	}
+
+	/// Multiply all the weights by `factor`.
+	pub fn scale(&mut self, factor: M::RealType) {
+		for w in self.matrix.iter_mut() {
+			*w *= factor;
+		}
+		// Integer weights may be truncated to zero
+		self.retain_nonzero();
+	}
+
+	/// Scale the model so that the absolute values of the linear and the
+	/// quadratic weights are at most `h_range` and `j_range` respectively, and
+	/// at least one of them reaches its range. Returns the factor, by which the
+	/// energies of the scaled model are divided to get the original ones.
+	///
+	/// # Panics
+	///
+	/// Panics if the factor is truncated to zero, i.e. a range is smaller than
+	/// the maximum of the absolute values of its weights for integer types.
+	pub fn normalize_to(&mut self, h_range: M::RealType, j_range: M::RealType) -> M::RealType {
+		let factor = normalize_factor(self, h_range, j_range);
+		self.scale(factor);
+		factor
+	}
+
+	/// Write the model in MatrixMarket symmetric coordinate format.
+	///
+	/// Diagonal entries are the linear biases and off-diagonal entries are the
+	/// couplers. Only non-zero weights are written, in the lower triangle as
+	/// the format requires.
+	pub fn to_matrix_market<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+		let entries = (0..self.size)
+			.flat_map(|j| (0..=j).map(move |i| (i, j)))
+			.map(|(i, j)| (i, j, self.matrix[self.get_index(i, j)]))
+			.filter(|(_, _, v)| *v != M::RealType::zero())
+			.collect::<Vec<_>>();
+		writeln!(w, "%%MatrixMarket matrix coordinate real symmetric")?;
+		writeln!(w, "{} {} {}", self.size, self.size, entries.len())?;
+		for (i, j, v) in entries.into_iter() {
+			writeln!(w, "{} {} {}", j + 1, i + 1, v)?;
+		}
+		Ok(())
+	}
+
+	/// Read the model from MatrixMarket coordinate format.
+	///
+	/// Both `symmetric` and `general` matrices are accepted. A `general` matrix
+	/// `A` is symmetrized as `(A + A^T) / 2`, i.e. the entries `(i, j)` and
+	/// `(j, i)` are averaged into one coupler, which is the convention of
+	/// `Expr::quadratic_form` with `symmetric`. A `symmetric` matrix stores
+	/// only one of them, which is the coupler. The number of the entries must
+	/// match the one in the size line.
+	pub fn from_matrix_market<Rd: Read>(node: M, r: Rd) -> std::io::Result<Self> {
+		fn invalid(msg: &str) -> std::io::Error {
+			std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+		}
+		let mut lines = BufReader::new(r).lines();
+		let header = lines.next().ok_or_else(|| invalid("empty input"))??;
+		let header = header.to_lowercase();
+		let header = header.split_whitespace().collect::<Vec<_>>();
+		let general = match header.as_slice() {
+			["%%matrixmarket", "matrix", "coordinate", "real" | "integer", "symmetric"] => false,
+			["%%matrixmarket", "matrix", "coordinate", "real" | "integer", "general"] => true,
+			_ => return Err(invalid("unsupported MatrixMarket header")),
+		};
+		let mut size = None;
+		// Summed in `f64`, so that halving does not truncate integer weights
+		let mut weights = BTreeMap::<(usize, usize), f64>::new();
+		let mut entries = 0;
+		for line in lines {
+			let line = line?;
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('%') {
+				continue;
+			}
+			let v = line.split_whitespace().collect::<Vec<_>>();
+			if v.len() != 3 {
+				return Err(invalid("each line must have 3 columns"));
+			}
+			if let Some((size, _)) = size {
+				let parse_index = |s: &str| match s.parse::<usize>() {
+					Ok(i) if i >= 1 && i <= size => Ok(i - 1),
+					_ => Err(invalid("invalid index")),
+				};
+				let (i, j) = (parse_index(v[0])?, parse_index(v[1])?);
+				let w = v[2].parse::<f64>().map_err(|_| invalid("invalid weight"))?;
+				let w = if general && i != j { w / 2.0 } else { w };
+				*weights.entry((i.min(j), i.max(j))).or_insert(0.0) += w;
+				entries += 1;
+			} else {
+				let n = v[0].parse::<usize>().map_err(|_| invalid("invalid size"))?;
+				if v[1].parse::<usize>().ok() != Some(n) {
+					return Err(invalid("matrix must be square"));
+				}
+				let nnz = v[2]
+					.parse::<usize>()
+					.map_err(|_| invalid("invalid number of entries"))?;
+				size = Some((n, nnz));
+			}
+		}
+		let (size, nnz) = size.ok_or_else(|| invalid("missing size line"))?;
+		if entries != nnz {
+			return Err(invalid("number of entries does not match the size line"));
+		}
+		let mut model = Self::new(node, size);
+		for ((i, j), w) in weights.into_iter() {
+			model.add_weight(i, j, M::RealType::from_f64(w));
+		}
+		Ok(model)
+	}
+}
+
+/// Serialized without `nonzero`, which is rebuilt from `matrix` on load.
+#[cfg(feature = "serde")]
+impl<M> serde::Serialize for FixedSingleQuadricModel<M>
+where
+	M: SingleNode + serde::Serialize,
+	M::RealType: serde::Serialize,
+{
+	fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+		use serde::ser::SerializeStruct;
+		let mut st = s.serialize_struct("FixedSingleQuadricModel", 3)?;
+		st.serialize_field("size", &self.size)?;
+		st.serialize_field("node", &self.node)?;
+		st.serialize_field("matrix", &self.matrix)?;
+		st.end()
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de, M> serde::Deserialize<'de> for FixedSingleQuadricModel<M>
+where
+	M: SingleNode + serde::Deserialize<'de>,
+	M::RealType: serde::Deserialize<'de>,
+{
+	fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+		#[derive(serde::Deserialize)]
+		#[serde(bound = "M: serde::Deserialize<'de>, M::RealType: serde::Deserialize<'de>")]
+		struct Raw<M: SingleNode> {
+			size: usize,
+			node: M,
+			matrix: Vec<M::RealType>,
+		}
+		let Raw { size, node, matrix } = Raw::<M>::deserialize(d)?;
+		// `get_weight_unchecked()` relies on the length of the matrix
+		let expected = size.checked_add(1).and_then(|n| n.checked_mul(size));
+		if expected.map(|n| n / 2) != Some(matrix.len()) {
+			return Err(serde::de::Error::custom(format!(
+				"{} weights do not match the size {}",
+				matrix.len(),
+				size
+			)));
+		}
+		let nonzero = (0..size)
+			.flat_map(|j| (0..=j).map(move |i| [i, j]))
+			.zip(matrix.iter())
+			.filter(|(_, w)| **w != M::RealType::zero())
+			.map(|(p, _)| p)
+			.collect();
+		Ok(Self {
+			size,
+			node,
+			matrix,
+			nonzero,
+		})
 	}
 }
 
 const QUADRIC: Quadric = Quadric;
+impl<M: SingleNode> FixedSingleQuadricModel<M> {
+	/// The non-zero weights as `(i, j, w)` with `i <= j`.
+	fn nonzero_weights(&self) -> impl Iterator<Item = (usize, usize, M::RealType)> + '_ {
+		self.nonzero
+			.iter()
+			.map(move |p| (p[0], p[1], self.matrix[p[1] * (p[1] + 1) / 2 + p[0]]))
+	}
+
+	/// Put `other` side by side with the model, without coupling them. The
+	/// node `i` of `other` becomes the node `self.size() + i`, and the node
+	/// type of `self` is kept.
+	pub fn disjoint_union(self, other: Self) -> Self {
+		let size = self.size;
+		let mut ret = Self::new(self.node.clone(), size + other.size);
+		for (i, j, w) in self.nonzero_weights() {
+			ret.add_weight(i, j, w);
+		}
+		for (i, j, w) in other.nonzero_weights() {
+			ret.add_weight(size + i, size + j, w);
+		}
+		ret
+	}
+
+	/// Split the nodes into the groups which are not coupled with each other
+	/// by non-zero weights. Each group is sorted, and the groups are sorted by
+	/// their first nodes.
+	pub fn connected_components(&self) -> Vec<Vec<usize>> {
+		crate::set::connected_components(self.size, self.nonzero.iter().map(|p| (p[0], p[1])))
+	}
+
+	/// The model of the weights among `nodes`, dropping the couplers to the
+	/// other nodes. Returns the model and the mapping, whose `k`-th element is
+	/// the original index of the node `k` of the model. The mapping is sorted
+	/// and has no duplicates.
+	pub fn induced_submodel(&self, nodes: &[usize]) -> (Self, Vec<usize>) {
+		let mut mapping = nodes.to_vec();
+		mapping.sort_unstable();
+		mapping.dedup();
+		(self.subgraph(&mapping), mapping)
+	}
+}
+
+impl<R: Real> FixedSingleQuadricModel<Binary<R>> {
+	/// Convert the Ising model into the QUBO with `s = 2x - 1`. Returns the
+	/// constant offset, which is added to the energy of the QUBO to get the one
+	/// of the Ising model, and the QUBO.
+	pub fn from_ising(model: &FixedSingleQuadricModel<Spin<R>>) -> (R, Self) {
+		to_qubo(model)
+	}
+
+	/// Convert the QUBO into the Ising model with `x = (s + 1) / 2`. Returns
+	/// the constant offset, which is added to the energy of the Ising model to
+	/// get the one of the QUBO, and the Ising model. The weights are halved
+	/// and quartered, so integer weights may be truncated.
+	pub fn to_ising(&self) -> (R, FixedSingleQuadricModel<Spin<R>>) {
+		to_ising(self)
+	}
+}
+
+/// Convert the model into the one of the node `to` with the same energies, in
+/// which each value `u` of the node of `model` is replaced with the value `v`
+/// of `to` of the same assignment, i.e. `u = (du * v + c) / dv` where `du`
+/// and `dv` are the differences of the true and the false values. Returns the
+/// constant offset, which is added to the energy of the converted model to get
+/// the one of `model`, and the converted model. Integer weights may be
+/// truncated by the division with `dv`.
+///
+/// # Panics
+///
+/// Panics if the true and the false values of `to` are the same.
+pub fn convert_node<F: SingleNode, T: SingleNode<RealType = F::RealType>>(
+	model: &FixedSingleQuadricModel<F>,
+	to: T,
+) -> (F::RealType, FixedSingleQuadricModel<T>) {
+	let (u0, u1) = (model.node.get_value(false), model.node.get_value(true));
+	let (v0, v1) = (to.get_value(false), to.get_value(true));
+	assert!(v0 != v1, "the values of the node should differ");
+	let (du, dv) = (u1 - u0, v1 - v0);
+	let c = u0 * dv - du * v0;
+	let mut ret = FixedSingleQuadricModel::new(to, model.size);
+	let mut offset = F::RealType::zero();
+	for (i, j, w) in model.nonzero_weights() {
+		if i == j {
+			// w u = w du / dv v + w c / dv
+			ret.add_weight(i, i, w * du / dv);
+			offset += w * c / dv;
+		} else {
+			// w u_i u_j = w / dv^2 (du^2 v_i v_j + du c v_i + du c v_j + c^2)
+			let dv2 = dv * dv;
+			ret.add_weight(i, j, w * du * du / dv2);
+			ret.add_weight(i, i, w * du * c / dv2);
+			ret.add_weight(j, j, w * du * c / dv2);
+			offset += w * c * c / dv2;
+		}
+	}
+	(offset, ret)
+}
+
+/// Convert the QUBO into the Ising model, where `true` is the spin `+1`. See
+/// [`convert_node()`] for the offset.
+pub fn to_ising<R: Real>(
+	model: &FixedSingleQuadricModel<Binary<R>>,
+) -> (R, FixedSingleQuadricModel<Spin<R>>) {
+	convert_node(model, Spin::new())
+}
+
+/// Convert the Ising model into the QUBO, where `true` is the spin `+1`. See
+/// [`convert_node()`] for the offset.
+pub fn to_qubo<R: Real>(
+	model: &FixedSingleQuadricModel<Spin<R>>,
+) -> (R, FixedSingleQuadricModel<Binary<R>>) {
+	convert_node(model, Binary::new())
+}
+
 impl<M: SingleNode> FixedSingleModelView for FixedSingleQuadricModel<M> {
 	type Node = M;
 	type Order = Quadric;
@@ -280,23 +853,507 @@ impl<M: SingleNode> FixedSingleModelView for FixedSingleQuadricModel<M> {
 			.matrix
 			.get_unchecked(self.get_index_unchecked(p[0], p[1]))
 	}
+
+	fn nonzero_prods(&self) -> Vec<[usize; 2]> {
+		self.nonzero.iter().copied().collect()
+	}
 }
 
-#[allow(unused)]
-pub struct Prods<S: NodeSet> {
-	order: usize,
+/// Model with fixed size which has only the linear terms, whose weights are
+/// stored in a `Vec`.
+#[derive(Clone)]
+pub struct FixedSingleLinearModel<NodeType: SingleNode> {
+	node: NodeType,
+	weights: Vec<NodeType::RealType>,
+}
+
+impl<M: SingleNode> FixedSingleLinearModel<M> {
+	pub fn new(node: M, size: usize) -> Self {
+		Self {
+			node,
+			weights: vec![M::RealType::zero(); size],
+		}
+	}
+
+	#[inline]
+	pub fn add_weight(&mut self, i: usize, w: M::RealType) {
+		assert!(
+			i < self.weights.len(),
+			"i should be less than {}",
+			self.weights.len()
+		);
+		self.weights[i] += w;
+	}
+}
+
+impl<M: SingleNode> FixedSingleModelView for FixedSingleLinearModel<M> {
+	type Node = M;
+	type Order = Linear;
+
+	#[inline]
+	fn node(&self) -> &Self::Node {
+		&self.node
+	}
+
+	#[inline]
+	fn order(&self) -> &Self::Order {
+		&Linear
+	}
+
+	#[inline]
+	fn size(&self) -> usize {
+		self.weights.len()
+	}
+
+	#[inline]
+	unsafe fn get_weight_unchecked(&self, p: &[usize; 1]) -> M::RealType {
+		*self.weights.get_unchecked(p[0])
+	}
+
+	fn nonzero_prods(&self) -> Vec<[usize; 1]> {
+		(0..self.weights.len())
+			.filter(|i| self.weights[*i] != M::RealType::zero())
+			.map(|i| [i])
+			.collect()
+	}
+}
+
+/// Quadric model with fixed size whose weights are stored in an adjacency
+/// list, which uses O(edges) memory in place of the dense triangular matrix of
+/// [`FixedSingleQuadricModel`]. `prods()` and `neighbors()` visit only the
+/// stored weights, so they take O(edges) and O(degree) time respectively.
+#[derive(Clone)]
+pub struct SparseQuadricModel<NodeType: SingleNode> {
 	size: usize,
-	indices: Vec<usize>,
+	node: NodeType,
+	/// Weights keyed by `(min, max)` of the indices.
+	weights: HashMap<(usize, usize), NodeType::RealType>,
+	/// The nodes coupled with each node by the stored weights.
+	adjacency: Vec<BTreeSet<usize>>,
+}
+
+impl<M: SingleNode> SparseQuadricModel<M> {
+	pub fn new(node: M, size: usize) -> Self {
+		Self {
+			size,
+			node,
+			weights: HashMap::new(),
+			adjacency: vec![BTreeSet::new(); size],
+		}
+	}
+
+	#[inline]
+	pub fn add_weight(&mut self, i: usize, j: usize, w: M::RealType) {
+		assert!(i < self.size, "i should be less than {}", self.size);
+		assert!(j < self.size, "j should be less than {}", self.size);
+		let key = if i < j { (i, j) } else { (j, i) };
+		*self.weights.entry(key).or_insert(M::RealType::zero()) += w;
+		if i != j {
+			self.adjacency[i].insert(j);
+			self.adjacency[j].insert(i);
+		}
+	}
+
+	/// The number of the stored weights.
+	pub fn len(&self) -> usize {
+		self.weights.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.weights.is_empty()
+	}
+
+	/// Multiply all the weights by `factor`.
+	pub fn scale(&mut self, factor: M::RealType) {
+		for w in self.weights.values_mut() {
+			*w *= factor;
+		}
+	}
+
+	/// See [`FixedSingleQuadricModel::normalize_to()`], which panics in the
+	/// same way.
+	pub fn normalize_to(&mut self, h_range: M::RealType, j_range: M::RealType) -> M::RealType {
+		let factor = normalize_factor(self, h_range, j_range);
+		self.scale(factor);
+		factor
+	}
+
+	/// Iterate over the stored weights as `((i, j), weight)` with `i <= j`.
+	pub fn weights(&self) -> impl Iterator<Item = ((usize, usize), M::RealType)> + '_ {
+		self.weights.iter().map(|(k, w)| (*k, *w))
+	}
+}
+
+impl<M: SingleNode> SingleModelView for SparseQuadricModel<M> {
+	type Node = M;
+	type NodesIter = std::ops::Range<usize>;
+	type ProdsIter = std::vec::IntoIter<[usize; 2]>;
+	type NeighborsIter = std::vec::IntoIter<[usize; 2]>;
+	type Order = Quadric;
+
+	fn order(&self) -> &Self::Order {
+		&QUADRIC
+	}
+
+	fn node(&self) -> &Self::Node {
+		&self.node
+	}
+
+	fn nodes(&self) -> Self::NodesIter {
+		0..self.size
+	}
+
+	#[inline]
+	fn size(&self) -> usize {
+		self.size
+	}
+
+	#[inline]
+	fn get_weight(&self, p: &[usize; 2]) -> M::RealType {
+		assert!(p[0] < self.size && p[1] < self.size);
+		unsafe { self.get_weight_unchecked(p) }
+	}
+
+	#[inline]
+	unsafe fn get_weight_unchecked(&self, p: &[usize; 2]) -> M::RealType {
+		let key = if p[0] < p[1] {
+			(p[0], p[1])
+		} else {
+			(p[1], p[0])
+		};
+		self.weights
+			.get(&key)
+			.copied()
+			.unwrap_or_else(M::RealType::zero)
+	}
+
+	/// The products of the stored weights, sorted.
+	fn prods(&self) -> Self::ProdsIter {
+		let mut ret = self
+			.weights
+			.keys()
+			.map(|(i, j)| [*i, *j])
+			.collect::<Vec<_>>();
+		ret.sort_unstable();
+		ret.into_iter()
+	}
+
+	fn neighbors(&self, u: usize) -> Self::NeighborsIter {
+		let mut ret = vec![[u, u]];
+		ret.extend(
+			self.adjacency[u]
+				.iter()
+				.map(|v| if u < *v { [u, *v] } else { [*v, u] }),
+		);
+		ret.into_iter()
+	}
+}
+
+impl<M: SingleNode> SparseIterableModel for SparseQuadricModel<M> {
+	type NonzeroProdsIter = Vec<[usize; 2]>;
+
+	fn nonzero_prods(&self) -> Self::NonzeroProdsIter {
+		self.prods()
+			.filter(|p| unsafe { self.get_weight_unchecked(p) } != M::RealType::zero())
+			.collect()
+	}
+}
+
+/// Quadric model whose couplers are stored in the compressed sparse row (CSR)
+/// format, in which `neighbors()` takes O(degree) time. The model is immutable
+/// once built with [`CsrQuadricModel::from_entries()`].
+#[derive(Clone)]
+pub struct CsrQuadricModel<NodeType: SingleNode> {
+	node: NodeType,
+	linear: Vec<NodeType::RealType>,
+	/// The couplers of the node `i` are in `row_ptr[i]..row_ptr[i + 1]` of
+	/// `col_idx` and `weights`, sorted by the column. Each coupler is stored in
+	/// both of its rows.
+	row_ptr: Vec<usize>,
+	col_idx: Vec<usize>,
+	weights: Vec<NodeType::RealType>,
+}
+
+impl<M: SingleNode> CsrQuadricModel<M> {
+	/// Build the model from the weights `((i, j), w)`. The entries with
+	/// `i == j` are the linear terms, and the weights of the same product are
+	/// summed.
+	pub fn from_entries<I>(node: M, size: usize, entries: I) -> Self
+	where
+		I: IntoIterator<Item = ((usize, usize), M::RealType)>,
+	{
+		let mut linear = vec![M::RealType::zero(); size];
+		let mut rows = vec![Vec::new(); size];
+		for ((i, j), w) in entries {
+			assert!(i < size, "i should be less than {}", size);
+			assert!(j < size, "j should be less than {}", size);
+			if i == j {
+				linear[i] += w;
+			} else {
+				rows[i].push((j, w));
+				rows[j].push((i, w));
+			}
+		}
+		let mut row_ptr = Vec::with_capacity(size + 1);
+		let mut col_idx = Vec::new();
+		let mut weights: Vec<M::RealType> = Vec::new();
+		row_ptr.push(0);
+		for mut row in rows {
+			row.sort_by_key(|(j, _)| *j);
+			let start = col_idx.len();
+			for (j, w) in row {
+				if col_idx.len() > start && col_idx.last() == Some(&j) {
+					*weights.last_mut().unwrap() += w;
+				} else {
+					col_idx.push(j);
+					weights.push(w);
+				}
+			}
+			row_ptr.push(col_idx.len());
+		}
+		Self {
+			node,
+			linear,
+			row_ptr,
+			col_idx,
+			weights,
+		}
+	}
+
+	/// The neighbors of the node `i` with the weights of the couplers.
+	pub fn row(&self, i: usize) -> impl Iterator<Item = (usize, M::RealType)> + '_ {
+		let range = self.row_ptr[i]..self.row_ptr[i + 1];
+		self.col_idx[range.clone()]
+			.iter()
+			.copied()
+			.zip(self.weights[range].iter().copied())
+	}
+}
+
+impl<M: SingleNode> SingleModelView for CsrQuadricModel<M> {
+	type Node = M;
+	type NodesIter = std::ops::Range<usize>;
+	type ProdsIter = std::vec::IntoIter<[usize; 2]>;
+	type NeighborsIter = std::vec::IntoIter<[usize; 2]>;
+	type Order = Quadric;
+
+	fn order(&self) -> &Self::Order {
+		&QUADRIC
+	}
+
+	fn node(&self) -> &Self::Node {
+		&self.node
+	}
+
+	fn nodes(&self) -> Self::NodesIter {
+		0..self.linear.len()
+	}
+
+	#[inline]
+	fn size(&self) -> usize {
+		self.linear.len()
+	}
+
+	#[inline]
+	fn get_weight(&self, p: &[usize; 2]) -> M::RealType {
+		assert!(p[0] < self.linear.len() && p[1] < self.linear.len());
+		unsafe { self.get_weight_unchecked(p) }
+	}
+
+	#[inline]
+	unsafe fn get_weight_unchecked(&self, p: &[usize; 2]) -> M::RealType {
+		if p[0] == p[1] {
+			return *self.linear.get_unchecked(p[0]);
+		}
+		let range = *self.row_ptr.get_unchecked(p[0])..*self.row_ptr.get_unchecked(p[0] + 1);
+		match self.col_idx[range.clone()].binary_search(&p[1]) {
+			Ok(k) => self.weights[range.start + k],
+			Err(_) => M::RealType::zero(),
+		}
+	}
+
+	/// The linear terms of all the nodes and the stored couplers.
+	fn prods(&self) -> Self::ProdsIter {
+		let mut ret = (0..self.linear.len()).map(|i| [i, i]).collect::<Vec<_>>();
+		for i in 0..self.linear.len() {
+			ret.extend(self.row(i).filter(|(j, _)| i < *j).map(|(j, _)| [i, j]));
+		}
+		ret.into_iter()
+	}
+
+	fn neighbors(&self, u: usize) -> Self::NeighborsIter {
+		let mut ret = vec![[u, u]];
+		ret.extend(
+			self.row(u)
+				.map(|(v, _)| if u < v { [u, v] } else { [v, u] }),
+		);
+		ret.into_iter()
+	}
+}
+
+impl<M: SingleNode> SparseIterableModel for CsrQuadricModel<M> {
+	type NonzeroProdsIter = Vec<[usize; 2]>;
+
+	fn nonzero_prods(&self) -> Self::NonzeroProdsIter {
+		self.prods()
+			.filter(|p| unsafe { self.get_weight_unchecked(p) } != M::RealType::zero())
+			.collect()
+	}
+}
+
+/// View of a model whose weights are multiplied by `factor`, with a constant
+/// energy offset. The weights of the inner model are not copied.
+#[derive(Clone)]
+pub struct ScaledModel<'a, P: SingleModelView> {
+	inner: &'a P,
+	factor: <P::Node as SingleNode>::RealType,
+	offset: <P::Node as SingleNode>::RealType,
+}
+
+impl<'a, P: SingleModelView> ScaledModel<'a, P> {
+	pub fn new(inner: &'a P, factor: <P::Node as SingleNode>::RealType) -> Self {
+		Self {
+			inner,
+			factor,
+			offset: Default::default(),
+		}
+	}
+
+	/// Set the offset added to the energy by [`ScaledModel::calculate_energy()`].
+	pub fn with_offset(mut self, offset: <P::Node as SingleNode>::RealType) -> Self {
+		self.offset = offset;
+		self
+	}
+
+	pub fn factor(&self) -> <P::Node as SingleNode>::RealType {
+		self.factor
+	}
+
+	pub fn offset(&self) -> <P::Node as SingleNode>::RealType {
+		self.offset
+	}
+
+	/// The energy of `solution` in the scaled model, including the offset.
+	pub fn calculate_energy(
+		&self,
+		solution: &SingleSolution<P::Node>,
+	) -> <P::Node as SingleNode>::RealType {
+		solution.calculate_energy(self) + self.offset
+	}
+}
+
+impl<'a, P: SingleModelView> SingleModelView for ScaledModel<'a, P> {
+	type Node = P::Node;
+	type NodesIter = P::NodesIter;
+	type ProdsIter = P::ProdsIter;
+	type NeighborsIter = P::NeighborsIter;
+	type Order = P::Order;
+
+	fn order(&self) -> &Self::Order {
+		self.inner.order()
+	}
+
+	fn node(&self) -> &Self::Node {
+		self.inner.node()
+	}
+
+	fn nodes(&self) -> Self::NodesIter {
+		self.inner.nodes()
+	}
+
+	#[inline]
+	fn size(&self) -> usize {
+		self.inner.size()
+	}
+
+	#[inline]
+	fn get_weight(
+		&self,
+		p: &<Self::Order as Order>::NodeSetType,
+	) -> <Self::Node as SingleNode>::RealType {
+		self.inner.get_weight(p) * self.factor
+	}
+
+	#[inline]
+	unsafe fn get_weight_unchecked(
+		&self,
+		p: &<Self::Order as Order>::NodeSetType,
+	) -> <Self::Node as SingleNode>::RealType {
+		self.inner.get_weight_unchecked(p) * self.factor
+	}
+
+	fn prods(&self) -> Self::ProdsIter {
+		self.inner.prods()
+	}
+
+	fn neighbors(&self, u: usize) -> Self::NeighborsIter {
+		self.inner.neighbors(u)
+	}
+}
+
+impl<'a, P: SparseIterableModel> SparseIterableModel for ScaledModel<'a, P> {
+	type NonzeroProdsIter = P::NonzeroProdsIter;
+
+	fn nonzero_prods(&self) -> Self::NonzeroProdsIter {
+		self.inner.nonzero_prods()
+	}
+}
+
+/// Iterator of the subsets of `{0..n}` whose sizes are in `min..=max`, in
+/// the ascending order of the sizes and then in the lexicographic order.
+pub(crate) struct Subsets {
+	n: usize,
+	max: usize,
+	indices: Option<Vec<usize>>,
+}
+
+impl Subsets {
+	pub(crate) fn new(n: usize, min: usize, max: usize) -> Self {
+		Self {
+			n,
+			max,
+			indices: if min <= max && min <= n {
+				Some((0..min).collect())
+			} else {
+				None
+			},
+		}
+	}
+}
+
+impl std::iter::Iterator for Subsets {
+	type Item = Vec<usize>;
+	fn next(&mut self) -> Option<Vec<usize>> {
+		let (n, max) = (self.n, self.max);
+		let indices = self.indices.as_mut()?;
+		let ret = indices.clone();
+		let k = indices.len();
+		// The rightmost index which can be incremented
+		if let Some(i) = (0..k).rev().find(|i| indices[*i] < n - k + i) {
+			indices[i] += 1;
+			for j in i + 1..k {
+				indices[j] = indices[j - 1] + 1;
+			}
+		} else if k < max && k < n {
+			*indices = (0..=k).collect();
+		} else {
+			self.indices = None;
+		}
+		Some(ret)
+	}
+}
+
+/// Iterator of all the products of a model whose order is `order`, i.e. the
+/// subsets of the nodes of size `1..=order`.
+pub struct Prods<S: NodeSet> {
+	subsets: Subsets,
 	_phantom: PhantomData<S>,
 }
 
 impl<S: NodeSet> Prods<S> {
 	pub fn new(order: usize, size: usize) -> Self {
-		assert!(order == 2); // TODO:
 		Self {
-			order,
-			size,
-			indices: vec![0],
+			subsets: Subsets::new(size, 1, order),
 			_phantom: PhantomData,
 		}
 	}
@@ -305,45 +1362,24 @@ impl<S: NodeSet> Prods<S> {
 impl<S: NodeSet> std::iter::Iterator for Prods<S> {
 	type Item = S;
 	fn next(&mut self) -> Option<S> {
-		// TODO: high-order
-		let finger = self.indices.len() - 1;
-		if self.indices[finger] < self.size {
-			let ret = S::from_vec(self.indices.clone()).unwrap();
-			self.indices[finger] += 1;
-			Some(ret)
-		} else if self.indices.len() == 1 {
-			self.indices = vec![0, 1];
-			let ret = S::from_vec(self.indices.clone()).unwrap();
-			self.indices[1] += 1;
-			Some(ret)
-		} else {
-			self.indices[0] += 1;
-			self.indices[1] = self.indices[0] + 1;
-			if self.indices[1] < self.size {
-				let ret = S::from_vec(self.indices.clone()).unwrap();
-				self.indices[1] += 1;
-				Some(ret)
-			} else {
-				None
-			}
-		}
+		self.subsets.next().map(|v| S::from_vec(v).unwrap())
 	}
 }
 
+/// Iterator of the products of a model whose order is `order` which contain
+/// the node `u`.
 pub struct Neighbors<S: NodeSet> {
 	u: usize,
-	item: usize,
-	size: usize,
+	subsets: Subsets,
 	_phantom: PhantomData<S>,
 }
 
 impl<S: NodeSet> Neighbors<S> {
 	pub fn new(u: usize, order: usize, size: usize) -> Self {
-		assert!(order == 2); // TODO:
+		// The subsets of the other nodes, to which `u` is added
 		Self {
-			size,
-			item: 0,
 			u,
+			subsets: Subsets::new(size.saturating_sub(1), 0, order.saturating_sub(1)),
 			_phantom: PhantomData,
 		}
 	}
@@ -352,17 +1388,16 @@ impl<S: NodeSet> Neighbors<S> {
 impl<S: NodeSet> std::iter::Iterator for Neighbors<S> {
 	type Item = S;
 	fn next(&mut self) -> Option<S> {
-		if self.item < self.size {
-			let v = self.item;
-			self.item += 1;
-			match self.u.cmp(&v) {
-				Ordering::Greater => Some(S::from_vec(vec![v, self.u]).unwrap()),
-				Ordering::Equal => Some(S::from_vec(vec![v]).unwrap()),
-				Ordering::Less => Some(S::from_vec(vec![self.u, v]).unwrap()),
+		let u = self.u;
+		self.subsets.next().map(|mut v| {
+			for i in v.iter_mut() {
+				if *i >= u {
+					*i += 1;
+				}
 			}
-		} else {
-			None
-		}
+			v.push(u);
+			S::from_vec(v).unwrap()
+		})
 	}
 }
 
@@ -380,4 +1415,585 @@ fn proditer_test() {
 	assert_eq!(it.next(), Some([1, 3]));
 	assert_eq!(it.next(), Some([2, 3]));
 	assert_eq!(it.next(), None);
+	let it: Prods<[usize; 2]> = Prods::new(2, 1);
+	assert_eq!(it.collect::<Vec<_>>(), vec![[0, 0]]);
+}
+
+#[test]
+fn high_order_proditer_test() {
+	use std::collections::HashSet;
+	let prods = Prods::<Vec<usize>>::new(3, 4).collect::<Vec<_>>();
+	assert_eq!(prods.len(), 4 + 6 + 4);
+	assert_eq!(prods[..5], [vec![0], vec![1], vec![2], vec![3], vec![0, 1]]);
+	assert_eq!(
+		prods[10..],
+		[vec![0, 1, 2], vec![0, 1, 3], vec![0, 2, 3], vec![1, 2, 3]]
+	);
+	assert_eq!(Prods::<Vec<usize>>::new(4, 5).count(), 5 + 10 + 10 + 5);
+	assert_eq!(Prods::<Vec<usize>>::new(5, 3).count(), 7);
+	let neighbors = Neighbors::<BTreeSet<usize>>::new(1, 3, 4).collect::<Vec<_>>();
+	assert_eq!(neighbors.len(), 1 + 3 + 3);
+	assert!(neighbors.iter().all(|p| p.contains(&1) && p.len() <= 3));
+	assert_eq!(
+		neighbors.iter().collect::<HashSet<_>>().len(),
+		neighbors.len()
+	);
+	let mut it: Neighbors<[usize; 2]> = Neighbors::new(2, 2, 4);
+	assert_eq!(it.next(), Some([2, 2]));
+	assert_eq!(it.next(), Some([0, 2]));
+	assert_eq!(it.next(), Some([1, 2]));
+	assert_eq!(it.next(), Some([2, 3]));
+	assert_eq!(it.next(), None);
+}
+
+#[test]
+fn matrix_market_test() {
+	use crate::node::Binary;
+	let mut model = FixedSingleQuadricModel::new(Binary::new(), 4);
+	model.add_weight(0, 0, 1.5f64);
+	model.add_weight(3, 3, -2.0);
+	model.add_weight(0, 1, 3.0);
+	model.add_weight(2, 1, -0.25);
+	model.add_weight(1, 3, 4.0);
+	let mut buf = Vec::new();
+	model.to_matrix_market(&mut buf).unwrap();
+	let loaded =
+		FixedSingleQuadricModel::from_matrix_market(Binary::<f64>::new(), &buf[..]).unwrap();
+	assert_eq!(FixedSingleModelView::size(&loaded), 4);
+	for p in model.prods() {
+		assert_eq!(
+			FixedSingleModelView::get_weight(&loaded, &p),
+			FixedSingleModelView::get_weight(&model, &p)
+		);
+	}
+}
+
+#[test]
+fn matrix_market_general_test() {
+	use crate::node::Binary;
+	let read =
+		|s: &str| FixedSingleQuadricModel::from_matrix_market(Binary::<f64>::new(), s.as_bytes());
+	let general =
+		"%%MatrixMarket matrix coordinate real general\n3 3 4\n1 1 1.5\n1 2 3\n2 1 1\n3 2 -2\n";
+	let model = read(general).unwrap();
+	assert_eq!(FixedSingleModelView::get_weight(&model, &[0, 0]), 1.5);
+	assert_eq!(FixedSingleModelView::get_weight(&model, &[0, 1]), 2.0);
+	assert_eq!(FixedSingleModelView::get_weight(&model, &[1, 2]), -1.0);
+	// A symmetric matrix stores each coupler once
+	let symmetric = "%%MatrixMarket matrix coordinate real symmetric\n3 3 2\n2 1 2\n3 2 -1\n";
+	let model = read(symmetric).unwrap();
+	assert_eq!(FixedSingleModelView::get_weight(&model, &[0, 1]), 2.0);
+	assert_eq!(FixedSingleModelView::get_weight(&model, &[1, 2]), -1.0);
+	// The number of the entries must match the size line
+	assert!(read("%%MatrixMarket matrix coordinate real general\n2 2 2\n1 1 1\n").is_err());
+	assert!(read("%%MatrixMarket matrix coordinate real general\n2 2 0\n1 1 1\n").is_err());
+}
+
+#[test]
+fn prune_test() {
+	use crate::node::Binary;
+	let mut model = FixedSingleQuadricModel::new(Binary::new(), 3);
+	model.add_weight(0, 0, 1.0e-9f64);
+	model.add_weight(0, 1, -1.0e-7);
+	model.add_weight(1, 2, 2.0);
+	model.add_weight(2, 2, -0.5);
+	let model = model.prune(1.0e-6);
+	assert_eq!(FixedSingleModelView::get_weight(&model, &[0, 0]), 0.0);
+	assert_eq!(FixedSingleModelView::get_weight(&model, &[0, 1]), 0.0);
+	assert_eq!(FixedSingleModelView::get_weight(&model, &[1, 2]), 2.0);
+	assert_eq!(FixedSingleModelView::get_weight(&model, &[2, 2]), -0.5);
+}
+
+#[test]
+fn normalize_test() {
+	use crate::node::Binary;
+	let mut model = FixedSingleQuadricModel::new(Binary::new(), 3);
+	model.add_weight(0, 0, 2.0f64);
+	model.add_weight(0, 1, -8.0);
+	model.add_weight(2, 2, 4.0);
+	let sol = SingleSolution::from_vec(&[true, true, true]);
+	let energy = sol.calculate_energy(&model);
+	let (normalized, factor) = model.normalize(2.0);
+	assert_eq!(factor, 0.25);
+	assert_eq!(FixedSingleModelView::get_weight(&normalized, &[0, 0]), 0.5);
+	assert_eq!(FixedSingleModelView::get_weight(&normalized, &[0, 1]), -2.0);
+	assert_eq!(FixedSingleModelView::get_weight(&normalized, &[2, 2]), 1.0);
+	assert_eq!(sol.calculate_energy(&normalized) / factor, energy);
+
+	let zero = FixedSingleQuadricModel::new(Binary::<f64>::new(), 2);
+	let (zero, factor) = zero.normalize(2.0);
+	assert_eq!(factor, 1.0);
+	assert_eq!(FixedSingleModelView::get_weight(&zero, &[0, 1]), 0.0);
+
+	// The weights are scaled by the truncated factor
+	let mut model = FixedSingleQuadricModel::new(Binary::new(), 2);
+	model.add_weight(0, 1, 3i32);
+	model.add_weight(1, 1, -1);
+	let (normalized, factor) = model.normalize(7);
+	assert_eq!(factor, 2);
+	assert_eq!(FixedSingleModelView::get_weight(&normalized, &[0, 1]), 6);
+	assert_eq!(FixedSingleModelView::get_weight(&normalized, &[1, 1]), -2);
+}
+
+#[test]
+#[should_panic]
+fn normalize_truncated_test() {
+	use crate::node::Binary;
+	// The factor 2 / 5 is truncated to zero
+	let mut model = FixedSingleQuadricModel::new(Binary::new(), 2);
+	model.add_weight(0, 0, 5i32);
+	model.add_weight(0, 1, -3);
+	let _ = model.normalize(2);
+}
+
+#[test]
+#[should_panic(expected = "smaller than the maximum weight")]
+fn normalize_to_truncated_test() {
+	use crate::node::Binary;
+	// The linear weights fit, but the factor 2 / 5 of the quadratic ones is
+	// truncated to zero
+	let mut model = FixedSingleQuadricModel::new(Binary::new(), 2);
+	model.add_weight(0, 0, 1i32);
+	model.add_weight(0, 1, 5);
+	model.normalize_to(3, 2);
+}
+
+#[test]
+#[should_panic(expected = "smaller than the maximum weight")]
+fn sparse_normalize_to_truncated_test() {
+	use crate::node::Binary;
+	let mut model = SparseQuadricModel::new(Binary::new(), 2);
+	model.add_weight(0, 0, 1i32);
+	model.add_weight(0, 1, 5);
+	model.normalize_to(3, 2);
+}
+
+#[test]
+fn sparse_model_test() {
+	use crate::node::Binary;
+	let mut sparse = SparseQuadricModel::new(Binary::new(), 4);
+	let mut dense = FixedSingleQuadricModel::new(Binary::new(), 4);
+	for (i, j, w) in [(0, 0, 1.0f64), (2, 1, -2.0), (1, 2, 0.5), (3, 0, 3.0)] {
+		sparse.add_weight(i, j, w);
+		dense.add_weight(i, j, w);
+	}
+	assert_eq!(sparse.len(), 3);
+	for p in dense.prods() {
+		assert_eq!(
+			SingleModelView::get_weight(&sparse, &p),
+			FixedSingleModelView::get_weight(&dense, &p)
+		);
+	}
+	// Only the stored weights are visited
+	assert_eq!(
+		sparse.prods().collect::<Vec<_>>(),
+		vec![[0, 0], [0, 3], [1, 2]]
+	);
+	assert_eq!(
+		sparse.neighbors(0).collect::<Vec<_>>(),
+		vec![[0, 0], [0, 3]]
+	);
+	assert_eq!(
+		sparse.neighbors(2).collect::<Vec<_>>(),
+		vec![[2, 2], [1, 2]]
+	);
+	let sol = SingleSolution::from_vec(&[true, true, true, false]);
+	assert_eq!(sol.calculate_energy(&sparse), sol.calculate_energy(&dense));
+}
+
+#[test]
+fn subgraph_test() {
+	use crate::node::Binary;
+	let mut model = FixedSingleQuadricModel::new(Binary::new(), 4);
+	model.add_weight(0, 0, 1.0f64);
+	model.add_weight(1, 1, 2.0);
+	model.add_weight(3, 3, 3.0);
+	model.add_weight(0, 3, 4.0);
+	model.add_weight(1, 3, 5.0);
+	model.add_weight(0, 2, 6.0);
+	let sub = model.subgraph(&[3, 0]);
+	assert_eq!(FixedSingleModelView::size(&sub), 2);
+	assert_eq!(FixedSingleModelView::get_weight(&sub, &[0, 0]), 3.0);
+	assert_eq!(FixedSingleModelView::get_weight(&sub, &[1, 1]), 1.0);
+	assert_eq!(FixedSingleModelView::get_weight(&sub, &[0, 1]), 4.0);
+}
+
+#[test]
+fn scaled_model_test() {
+	use crate::node::Binary;
+	let mut model = FixedSingleQuadricModel::new(Binary::new(), 3);
+	model.add_weight(0, 0, 1.0f64);
+	model.add_weight(0, 1, -3.0);
+	model.add_weight(2, 2, 2.0);
+	let scaled = ScaledModel::new(&model, 2.0);
+	assert_eq!(SingleModelView::size(&scaled), 3);
+	assert_eq!(SingleModelView::get_weight(&scaled, &[0, 1]), -6.0);
+	for v in [
+		[true, true, false],
+		[true, false, true],
+		[false, true, true],
+	] {
+		let sol = SingleSolution::from_vec(&v);
+		assert_eq!(
+			scaled.calculate_energy(&sol),
+			2.0 * sol.calculate_energy(&model)
+		);
+		assert_eq!(
+			scaled.clone().with_offset(0.5).calculate_energy(&sol),
+			2.0 * sol.calculate_energy(&model) + 0.5
+		);
+	}
+}
+
+#[test]
+fn nonzero_prods_test() {
+	use crate::node::Binary;
+	use rand::Rng;
+	let mut r = rand::thread_rng();
+	let mut dense = FixedSingleQuadricModel::new(Binary::new(), 30);
+	let mut sparse = SparseQuadricModel::new(Binary::new(), 30);
+	for _ in 0..40 {
+		let (i, j) = (r.gen_range(0, 30), r.gen_range(0, 30));
+		let w = r.gen_range(-5, 5) as f64;
+		dense.add_weight(i, j, w);
+		sparse.add_weight(i, j, w);
+	}
+	// A weight cancelled to zero is dropped
+	dense.add_weight(3, 7, 1.0);
+	dense.add_weight(7, 3, -FixedSingleModelView::get_weight(&dense, &[3, 7]));
+	let expected = Prods::<[usize; 2]>::new(2, 30)
+		.filter(|p| FixedSingleModelView::get_weight(&dense, p) != 0.0)
+		.collect::<BTreeSet<_>>();
+	let nonzero = SparseIterableModel::nonzero_prods(&dense);
+	assert_eq!(nonzero.len(), expected.len());
+	assert_eq!(nonzero.into_iter().collect::<BTreeSet<_>>(), expected);
+	let sol = SingleSolution::from_vec(&(0..30).map(|_| r.gen()).collect::<Vec<bool>>());
+	fn energy<P: SparseIterableModel<Node = Binary<f64>>>(
+		model: &P,
+		sol: &SingleSolution<Binary<f64>>,
+	) -> f64 {
+		model
+			.nonzero_prods()
+			.into_iter()
+			.map(|p| model.get_weight(&p) * model.calculate_prod(&p, sol))
+			.sum()
+	}
+	assert_eq!(energy(&dense, &sol), sol.calculate_energy(&dense));
+	assert_eq!(energy(&sparse, &sol), sol.calculate_energy(&sparse));
+	let pruned = dense.prune(3.0);
+	assert!(SparseIterableModel::nonzero_prods(&pruned)
+		.iter()
+		.all(|p| FixedSingleModelView::get_weight(&pruned, p).abs() >= 3.0));
+}
+
+#[test]
+fn csr_model_test() {
+	use crate::node::Binary;
+	use rand::Rng;
+	let mut r = rand::thread_rng();
+	let size = 12;
+	let entries = (0..40)
+		.map(|_| {
+			let (i, j) = (r.gen_range(0, size), r.gen_range(0, size));
+			((i, j), r.gen_range(-5, 5) as f64)
+		})
+		.collect::<Vec<_>>();
+	let csr = CsrQuadricModel::from_entries(Binary::new(), size, entries.clone());
+	let mut reference = SingleModel::new(Binary::new(), Quadric);
+	for i in 0..size {
+		reference.add_weight([i, i], 0.0);
+	}
+	for ((i, j), w) in entries {
+		reference.add_weight(if i < j { [i, j] } else { [j, i] }, w);
+	}
+	for p in reference.prods() {
+		assert_eq!(csr.get_weight(&p), reference.get_weight(&p));
+	}
+	for u in 0..size {
+		let neighbors = csr.neighbors(u).collect::<BTreeSet<_>>();
+		assert_eq!(neighbors, reference.neighbors(u).collect::<BTreeSet<_>>());
+	}
+	for _ in 0..10 {
+		let sol = SingleSolution::from_vec(&(0..size).map(|_| r.gen()).collect::<Vec<bool>>());
+		assert_eq!(sol.calculate_energy(&csr), sol.calculate_energy(&reference));
+		assert_eq!(
+			sol.clone().calculate_local_field(&csr),
+			sol.calculate_local_field(&reference)
+		);
+	}
+}
+
+#[test]
+fn ising_conversion_test() {
+	use rand::Rng;
+	let mut r = rand::thread_rng();
+	let mut ising = FixedSingleQuadricModel::new(Spin::new(), 5);
+	for _ in 0..12 {
+		let (i, j) = (r.gen_range(0, 5), r.gen_range(0, 5));
+		ising.add_weight(i, j, r.gen_range(-8, 8) as f64);
+	}
+	let (offset, qubo) = FixedSingleQuadricModel::from_ising(&ising);
+	let (back_offset, back) = qubo.to_ising();
+	for bits in 0..1usize << 5 {
+		let v = (0..5).map(|i| bits >> i & 1 == 1).collect::<Vec<_>>();
+		let e_ising = SingleSolution::from_vec(&v).calculate_energy(&ising);
+		let e_qubo = SingleSolution::from_vec(&v).calculate_energy(&qubo);
+		let e_back = SingleSolution::from_vec(&v).calculate_energy(&back);
+		assert_eq!(e_qubo + offset, e_ising);
+		assert_eq!(e_back + back_offset, e_qubo);
+	}
+}
+
+#[test]
+fn high_order_energy_test() {
+	use crate::node::Binary;
+	let set = |v: &[usize]| v.iter().copied().collect::<BTreeSet<_>>();
+	let mut model = HighOrderModel::new(Binary::new(), HighOrder::new(3));
+	model.add_weight(set(&[0, 1, 2]), 5.0f64);
+	model.add_weight(set(&[1, 3]), -2.0);
+	model.add_weight(set(&[2]), 1.5);
+	for bits in 0..1usize << 4 {
+		let v = (0..4).map(|i| bits >> i & 1 == 1).collect::<Vec<_>>();
+		let expected = 5.0 * (v[0] && v[1] && v[2]) as u8 as f64
+			- 2.0 * (v[1] && v[3]) as u8 as f64
+			+ 1.5 * v[2] as u8 as f64;
+		assert_eq!(
+			SingleSolution::from_vec(&v).calculate_energy(&model),
+			expected
+		);
+	}
+}
+
+#[test]
+fn linear_model_test() {
+	use crate::node::Binary;
+	let mut model = FixedSingleLinearModel::new(Binary::new(), 3);
+	model.add_weight(0, 1.0f64);
+	model.add_weight(2, -2.0);
+	model.add_weight(2, 0.5);
+	assert_eq!(SingleModelView::size(&model), 3);
+	assert_eq!(
+		model.prods().collect::<Vec<_>>(),
+		[[0usize], [1], [2]].to_vec()
+	);
+	assert_eq!(model.neighbors(1).collect::<Vec<_>>(), [[1usize]].to_vec());
+	assert_eq!(
+		FixedSingleModelView::nonzero_prods(&model),
+		[[0], [2]].to_vec()
+	);
+	let sol = SingleSolution::from_vec(&[true, true, true]);
+	assert_eq!(sol.calculate_energy(&model), -0.5);
+}
+
+#[test]
+fn convert_node_test() {
+	use crate::node::TwoVal;
+	use rand::Rng;
+	let mut r = rand::thread_rng();
+	for _ in 0..20 {
+		let mut qubo = FixedSingleQuadricModel::new(Binary::new(), 8);
+		for _ in 0..16 {
+			let (i, j) = (r.gen_range(0, 8), r.gen_range(0, 8));
+			qubo.add_weight(i, j, r.gen_range(-8, 8) as f64);
+		}
+		let (ising_offset, ising) = to_ising(&qubo);
+		let (qubo_offset, back) = to_qubo(&ising);
+		let (two_val_offset, two_val) = convert_node(&qubo, TwoVal::new(3.0, -1.0));
+		for bits in 0..1usize << 8 {
+			let v = (0..8).map(|i| bits >> i & 1 == 1).collect::<Vec<_>>();
+			let e_qubo = SingleSolution::from_vec(&v).calculate_energy(&qubo);
+			let e_ising = SingleSolution::from_vec(&v).calculate_energy(&ising);
+			assert_eq!(e_ising + ising_offset, e_qubo);
+			assert_eq!(
+				SingleSolution::from_vec(&v).calculate_energy(&back) + qubo_offset,
+				e_ising
+			);
+			assert_eq!(
+				SingleSolution::from_vec(&v).calculate_energy(&two_val) + two_val_offset,
+				e_qubo
+			);
+		}
+	}
+}
+
+#[test]
+fn weight_range_test() {
+	use crate::node::Binary;
+	let entries = [
+		(0, 0, 2.0f64),
+		(1, 1, -0.5),
+		(0, 1, -6.0),
+		(1, 2, 1.5),
+		(2, 3, 3.0),
+	];
+	let mut dense = FixedSingleQuadricModel::new(Binary::new(), 4);
+	let mut sparse = SparseQuadricModel::new(Binary::new(), 4);
+	for (i, j, w) in entries.iter() {
+		dense.add_weight(*i, *j, *w);
+		sparse.add_weight(*i, *j, *w);
+	}
+	assert_eq!(dense.weight_range(), (0.5, 6.0));
+	assert_eq!(dense.weight_range_of_order(1), (0.5, 2.0));
+	assert_eq!(dense.weight_range_of_order(2), (1.5, 6.0));
+	assert_eq!(sparse.weight_range(), (0.5, 6.0));
+	assert_eq!(
+		FixedSingleQuadricModel::new(Binary::<f64>::new(), 2).weight_range(),
+		(0.0, 0.0)
+	);
+
+	let factor = dense.normalize_to(1.0, 2.0);
+	assert_eq!(factor, 1.0 / 3.0);
+	assert_eq!(dense.weight_range_of_order(2).1, 2.0);
+	assert!(dense.weight_range_of_order(1).1 <= 1.0);
+	assert_eq!(sparse.normalize_to(1.0, 2.0), factor);
+	dense.scale(3.0);
+	sparse.scale(3.0);
+	for (i, j, w) in entries.iter() {
+		assert!((FixedSingleModelView::get_weight(&dense, &[*i, *j]) - w).abs() < 1e-12);
+		assert!((SingleModelView::get_weight(&sparse, &[*i, *j]) - w).abs() < 1e-12);
+	}
+}
+
+#[test]
+fn fix_variable_test() {
+	use rand::Rng;
+	let mut r = rand::thread_rng();
+	let mut model = FixedSingleQuadricModel::new(Binary::new(), 5);
+	for _ in 0..12 {
+		let (i, j) = (r.gen_range(0, 5), r.gen_range(0, 5));
+		model.add_weight(i, j, r.gen_range(-8, 8) as f64);
+	}
+	model.set_weight(1, 3, 2.5);
+	assert_eq!(FixedSingleModelView::get_weight(&model, &[1, 3]), 2.5);
+	for value in [false, true] {
+		let mut fixed = model.clone();
+		let offset = fixed.fix_variable(1, value);
+		assert!(FixedSingleModelView::nonzero_prods(&fixed)
+			.iter()
+			.all(|p| !p.contains(1)));
+		for bits in 0..1usize << 5 {
+			let mut v = (0..5).map(|i| bits >> i & 1 == 1).collect::<Vec<_>>();
+			let e_fixed = SingleSolution::from_vec(&v).calculate_energy(&fixed);
+			v[1] = value;
+			let e_orig = SingleSolution::from_vec(&v).calculate_energy(&model);
+			assert_eq!(e_fixed + offset, e_orig);
+		}
+	}
+	assert_eq!(model.remove_weight(3, 1), 2.5);
+	assert_eq!(FixedSingleModelView::get_weight(&model, &[1, 3]), 0.0);
+	assert!(!FixedSingleModelView::nonzero_prods(&model).contains(&[1, 3]));
+}
+
+#[test]
+fn single_model_mut_test() {
+	let set = |v: &[usize]| v.iter().copied().collect::<BTreeSet<_>>();
+	let mut model = HighOrderModel::new(Binary::new(), HighOrder::new(3));
+	model.add_weight(set(&[0, 1, 2]), 1.0f64);
+	model.set_weight(set(&[0, 1, 2]), 4.0);
+	model.set_weight(set(&[3]), -1.0);
+	assert_eq!(model.get_weight(&set(&[0, 1, 2])), 4.0);
+	*model.get_weight_mut(&set(&[3])).unwrap() *= 2.0;
+	assert_eq!(model.get_weight(&set(&[3])), -2.0);
+	assert!(model.get_weight_mut(&set(&[1])).is_none());
+	assert_eq!(model.remove_weight(&set(&[0, 1, 2])), Some(4.0));
+	assert_eq!(model.remove_weight(&set(&[0, 1, 2])), None);
+	assert_eq!(model.prods().collect::<Vec<_>>(), vec![set(&[3])]);
+	let sol = SingleSolution::from_vec(&[true, true, true, true]);
+	assert_eq!(sol.calculate_energy(&model), -2.0);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_test() {
+	use crate::node::TwoVal;
+	let mut model = FixedSingleQuadricModel::new(TwoVal::new(3.0, -1.0), 3);
+	model.add_weight(0, 0, 1.5f64);
+	model.add_weight(0, 2, -2.0);
+	model.add_weight(2, 1, 0.5);
+	let json = serde_json::to_string(&model).unwrap();
+	let bin = bincode::serialize(&model).unwrap();
+	for de in [
+		serde_json::from_str::<FixedSingleQuadricModel<TwoVal<f64>>>(&json).unwrap(),
+		bincode::deserialize(&bin).unwrap(),
+	] {
+		assert_eq!(SingleModelView::size(&de), 3);
+		assert_eq!(SingleModelView::node(&de).get_value(false), -1.0);
+		assert_eq!(
+			de.prods().collect::<Vec<_>>(),
+			model.prods().collect::<Vec<_>>()
+		);
+		for p in model.prods() {
+			assert_eq!(
+				FixedSingleModelView::get_weight(&de, &p),
+				FixedSingleModelView::get_weight(&model, &p)
+			);
+		}
+	}
+
+	let set = |v: &[usize]| v.iter().copied().collect::<BTreeSet<_>>();
+	let mut model = HighOrderModel::new(Spin::new(), HighOrder::new(3));
+	model.add_weight(set(&[0, 1, 2]), 1.0f64);
+	model.add_weight(set(&[3]), -1.0);
+	let json = serde_json::to_string(&model).unwrap();
+	let bin = bincode::serialize(&model).unwrap();
+	for de in [
+		serde_json::from_str::<HighOrderModel<Spin<f64>>>(&json).unwrap(),
+		bincode::deserialize(&bin).unwrap(),
+	] {
+		assert_eq!(de.get_weight(&set(&[0, 1, 2])), 1.0);
+		assert_eq!(de.get_weight(&set(&[3])), -1.0);
+		assert_eq!(de.prods().count(), 2);
+	}
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_validation_test() {
+	let mut model = FixedSingleQuadricModel::new(Binary::new(), 3);
+	model.add_weight(0, 2, 1.5f64);
+	model.add_weight(1, 1, -1.0);
+	let json = serde_json::to_string(&model).unwrap();
+	assert!(!json.contains("nonzero"));
+	let de: FixedSingleQuadricModel<Binary<f64>> = serde_json::from_str(&json).unwrap();
+	assert_eq!(
+		de.nonzero_weights().collect::<Vec<_>>(),
+		vec![(0, 2, 1.5), (1, 1, -1.0)]
+	);
+	let json = json.replace("\"size\":3", "\"size\":100000");
+	assert!(serde_json::from_str::<FixedSingleQuadricModel<Binary<f64>>>(&json).is_err());
+
+	// The products of `[usize; 2]` must be sorted
+	let mut model = SingleModel::new(Binary::new(), Quadric);
+	model.add_weight([0, 1], 2.0f64);
+	let json = serde_json::to_string(&model)
+		.unwrap()
+		.replace("[0,1]", "[1,0]");
+	let de: SingleModel<Binary<f64>, Quadric> = serde_json::from_str(&json).unwrap();
+	assert_eq!(de.get_weight(&[0, 1]), 2.0);
+}
+
+#[test]
+fn connected_components_test() {
+	let mut a = FixedSingleQuadricModel::new(Binary::new(), 3);
+	a.add_weight(0, 0, 1.0f64);
+	a.add_weight(0, 2, -2.0);
+	let mut b = FixedSingleQuadricModel::new(Binary::new(), 2);
+	b.add_weight(0, 1, 3.0);
+	b.add_weight(1, 1, -1.0);
+	let union = a.clone().disjoint_union(b.clone());
+	assert_eq!(SingleModelView::size(&union), 5);
+	assert_eq!(FixedSingleModelView::get_weight(&union, &[0, 2]), -2.0);
+	assert_eq!(FixedSingleModelView::get_weight(&union, &[3, 4]), 3.0);
+	assert_eq!(FixedSingleModelView::get_weight(&union, &[4, 4]), -1.0);
+	assert_eq!(
+		union.connected_components(),
+		vec![vec![0, 2], vec![1], vec![3, 4]]
+	);
+	let (sub, mapping) = union.induced_submodel(&[4, 0, 3, 4]);
+	assert_eq!(mapping, vec![0, 3, 4]);
+	assert_eq!(SingleModelView::size(&sub), 3);
+	assert_eq!(FixedSingleModelView::get_weight(&sub, &[0, 0]), 1.0);
+	assert_eq!(FixedSingleModelView::get_weight(&sub, &[1, 2]), 3.0);
+	assert_eq!(FixedSingleModelView::get_weight(&sub, &[2, 2]), -1.0);
+	assert_eq!(sub.nonzero_weights().count(), 3);
 }
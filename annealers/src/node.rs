@@ -33,6 +33,7 @@ impl<S: SingleNode, K: Hash + Debug + Clone + Send + Sync> Node for DiscreteNode
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Spin<R: Real> {
 	_phantom: PhantomData<R>,
 }
@@ -56,10 +57,19 @@ impl<R: Real> SingleNode for Spin<R> {
 	fn get_value(&self, b: bool) -> R {
 		R::from_i32(if b { 1 } else { -1 })
 	}
-	// TODO: add calculate_prod()
+
+	/// `(-1)^n` where `n` is the number of `false` in `v`.
+	fn calculate_prod(&self, v: &[bool]) -> R {
+		if v.iter().filter(|b| !**b).count() % 2 == 0 {
+			R::one()
+		} else {
+			-R::one()
+		}
+	}
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Binary<R: Real> {
 	_phantom: PhantomData<R>,
 }
@@ -70,7 +80,6 @@ impl<R: Real> Binary<R> {
 			_phantom: PhantomData,
 		}
 	}
-	// TODO: add calculate_prod()
 }
 
 impl<R: Real> Default for Binary<R> {
@@ -84,9 +93,19 @@ impl<R: Real> SingleNode for Binary<R> {
 	fn get_value(&self, b: bool) -> R {
 		R::from_i32(if b { 1 } else { 0 })
 	}
+
+	/// 1 if all the values in `v` are `true`, otherwise 0.
+	fn calculate_prod(&self, v: &[bool]) -> R {
+		if v.iter().all(|b| *b) {
+			R::one()
+		} else {
+			R::zero()
+		}
+	}
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TwoVal<R: Real> {
 	true_value: R,
 	false_value: R,
@@ -115,3 +134,21 @@ impl<R: Real> SingleNode for TwoVal<R> {
 // pub struct DiscreteNode<K: Hash + Eq, N: SingleNode>(HashSet<K>, N);
 //
 // impl<K: Hash + Eq, N: SingleNode> Node for DiscreteNode<K, N> {}
+
+#[test]
+fn calculate_prod_test() {
+	use rand::Rng;
+	fn fold<N: SingleNode>(node: &N, v: &[bool]) -> N::RealType {
+		v.iter()
+			.fold(N::RealType::one(), |m, b| m * node.get_value(*b))
+	}
+	let mut r = rand::thread_rng();
+	let (binary, spin) = (Binary::<i32>::new(), Spin::<i32>::new());
+	for len in 0..8 {
+		for _ in 0..20 {
+			let v = (0..len).map(|_| r.gen::<bool>()).collect::<Vec<_>>();
+			assert_eq!(binary.calculate_prod(&v), fold(&binary, &v));
+			assert_eq!(spin.calculate_prod(&v), fold(&spin, &v));
+		}
+	}
+}
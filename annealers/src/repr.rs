@@ -6,6 +6,29 @@ pub struct BinaryRepr {
 	len: usize,
 }
 
+/// Serialized as `(len, bytes)`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for BinaryRepr {
+	fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+		(self.len, &self.state).serialize(s)
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for BinaryRepr {
+	fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+		let (len, state) = <(usize, Vec<u8>)>::deserialize(d)?;
+		if state.len() != len.div_ceil(BYTESIZE) {
+			return Err(serde::de::Error::custom(format!(
+				"{} bytes cannot hold {} bits",
+				state.len(),
+				len
+			)));
+		}
+		Ok(Self { state, len })
+	}
+}
+
 static BITVALUES: [u8; 8] = [1, 2, 4, 8, 16, 32, 64, 128];
 const BYTESIZE: usize = 8;
 
@@ -99,6 +122,27 @@ impl BinaryRepr {
 	pub fn iter(&self) -> BinaryReprIter<'_> {
 		BinaryReprIter(self, 0)
 	}
+
+	/// The number of the bits differing from `other`, counted byte by byte.
+	///
+	/// # Panics
+	///
+	/// Panics if the lengths differ.
+	pub fn hamming_distance(&self, other: &Self) -> usize {
+		assert_eq!(self.len, other.len, "lengths of BinaryRepr differ");
+		let full = self.len / BYTESIZE;
+		let rem = self.len - full * BYTESIZE;
+		let mut ret = self.state[..full]
+			.iter()
+			.zip(other.state[..full].iter())
+			.map(|(a, b)| (a ^ b).count_ones() as usize)
+			.sum();
+		if rem > 0 {
+			let mask = BITVALUES[rem] - 1;
+			ret += ((self.state[full] ^ other.state[full]) & mask).count_ones() as usize;
+		}
+		ret
+	}
 }
 
 pub struct BinaryReprIter<'a>(&'a BinaryRepr, usize);
@@ -127,8 +171,85 @@ impl std::ops::Index<usize> for BinaryRepr {
 	}
 }
 
+/// Two `BinaryRepr`s are equal when they have the same length and the same
+/// bits. Unused bits in the last byte are ignored.
+impl PartialEq for BinaryRepr {
+	fn eq(&self, other: &Self) -> bool {
+		self.len == other.len && self.iter().eq(other.iter())
+	}
+}
+
+impl Eq for BinaryRepr {}
+
+impl std::hash::Hash for BinaryRepr {
+	fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+		self.len.hash(state);
+		let full = self.len / BYTESIZE;
+		let rem = self.len - full * BYTESIZE;
+		self.state[..full].hash(state);
+		if rem > 0 {
+			let mask = BITVALUES[rem] - 1;
+			(self.state[full] & mask).hash(state);
+		}
+	}
+}
+
 impl std::fmt::Debug for BinaryRepr {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		f.debug_list().entries(self.iter()).finish()
 	}
 }
+
+#[test]
+fn binary_repr_eq_test() {
+	let mut a = BinaryRepr::new_random(10, &mut rand::thread_rng());
+	let mut b = BinaryRepr::new_random(10, &mut rand::thread_rng());
+	for i in 0..10 {
+		a.set(i, i % 3 == 0);
+		b.set(i, i % 3 == 0);
+	}
+	a.state[1] |= 0xf0;
+	b.state[1] &= 0x0f;
+	assert_eq!(a, b);
+	let set = vec![a, b]
+		.into_iter()
+		.collect::<std::collections::HashSet<_>>();
+	assert_eq!(set.len(), 1);
+	let mut c = BinaryRepr::from_vec(&[true; 10]);
+	assert_ne!(set.iter().next().unwrap(), &c);
+	c.flip(0);
+	assert_ne!(
+		BinaryRepr::from_vec(&[true; 9]),
+		BinaryRepr::from_vec(&[true; 10])
+	);
+}
+
+#[test]
+fn binary_repr_hamming_distance_test() {
+	let mut a = BinaryRepr::from_vec(&[
+		true, false, true, true, false, false, true, false, true, true,
+	]);
+	let mut b = BinaryRepr::from_vec(&[
+		true, true, true, false, false, false, true, false, false, true,
+	]);
+	assert_eq!(a.hamming_distance(&b), 3);
+	assert_eq!(b.hamming_distance(&a), 3);
+	assert_eq!(a.hamming_distance(&a), 0);
+	// Unused bits in the last byte are ignored.
+	a.state[1] |= 0xf0;
+	b.state[1] &= 0x0f;
+	assert_eq!(a.hamming_distance(&b), 3);
+	let c = BinaryRepr::from_vec(&[false; 10]);
+	assert_eq!(b.hamming_distance(&c), 5);
+}
+
+#[test]
+fn binary_repr_flip_test() {
+	let mut state = BinaryRepr::new_random(20, &mut rand::thread_rng());
+	let before = state.to_vec();
+	state.flip(12);
+	let after = state.to_vec();
+	for i in 0..20 {
+		assert_eq!(before[i] != after[i], i == 12);
+	}
+}
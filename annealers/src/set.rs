@@ -113,6 +113,34 @@ impl NodeSet for Vec<usize> {
 	}
 }
 
+impl NodeSet for [usize; 1] {
+	type Iter = std::iter::Once<usize>;
+
+	#[inline]
+	fn from_it<T: IntoIterator<Item = usize>>(iter: T) -> Option<Self> {
+		let mut iter = iter.into_iter();
+		match (iter.next(), iter.next()) {
+			(Some(i), None) => Some([i]),
+			_ => None,
+		}
+	}
+
+	#[inline]
+	fn iter(&self) -> <Self as NodeSet>::Iter {
+		std::iter::once(self[0])
+	}
+
+	#[inline]
+	fn len(&self) -> usize {
+		1
+	}
+
+	#[inline]
+	fn contains(&self, node: usize) -> bool {
+		self[0] == node
+	}
+}
+
 // SAFETY: arr must be sorted
 // TODO: composite in struct for safety
 impl NodeSet for [usize; 2] {
@@ -151,3 +179,44 @@ impl NodeSet for [usize; 2] {
 		self[0] == node || self[1] == node
 	}
 }
+
+/// Split the nodes `0..size` into the groups connected by `edges`. Each group
+/// is sorted, and the groups are sorted by their first nodes.
+pub fn connected_components<I: IntoIterator<Item = (usize, usize)>>(
+	size: usize,
+	edges: I,
+) -> Vec<Vec<usize>> {
+	// Union-find with path halving, which needs no recursion
+	fn find(parent: &mut [usize], mut i: usize) -> usize {
+		while parent[i] != i {
+			parent[i] = parent[parent[i]];
+			i = parent[i];
+		}
+		i
+	}
+	let mut parent = (0..size).collect::<Vec<_>>();
+	for (i, j) in edges {
+		let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+		parent[a.max(b)] = a.min(b);
+	}
+	let mut components = std::collections::BTreeMap::<usize, Vec<usize>>::new();
+	for i in 0..size {
+		let root = find(&mut parent, i);
+		components.entry(root).or_default().push(i);
+	}
+	components.into_values().collect()
+}
+
+#[test]
+fn connected_components_test() {
+	let edges = vec![(0, 3), (4, 1), (3, 5), (5, 5)];
+	assert_eq!(
+		connected_components(6, edges),
+		vec![vec![0, 3, 5], vec![1, 4], vec![2]]
+	);
+	// A long path does not overflow the stack
+	let n = 1 << 20;
+	let components = connected_components(n, (1..n).map(|i| (i, i - 1)));
+	assert_eq!(components.len(), 1);
+	assert_eq!(components[0].len(), n);
+}
@@ -3,6 +3,7 @@ use crate::node::{Node, SingleNode};
 use crate::repr::BinaryRepr;
 use crate::set::NodeSet;
 use crate::variable::Real;
+use std::cmp::Ordering;
 use std::marker::PhantomData;
 
 pub trait Solution {
@@ -10,11 +11,20 @@ pub trait Solution {
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "serde",
+	serde(bound(
+		serialize = "NodeType::RealType: serde::Serialize",
+		deserialize = "NodeType::RealType: serde::Deserialize<'de>"
+	))
+)]
 pub struct SingleSolution<NodeType: SingleNode> {
 	pub state: BinaryRepr,
 	pub energy: Option<NodeType::RealType>,
 	pub occurrences: usize,
 	pub local_field: Option<Vec<NodeType::RealType>>,
+	#[cfg_attr(feature = "serde", serde(skip))]
 	_phantom: PhantomData<NodeType>,
 }
 
@@ -46,7 +56,7 @@ impl<M: SingleNode> SingleSolution<M> {
 	}
 
 	/// Compare two SingleSolution by energy.
-	pub fn compare_energy(&self, other: &Self) -> Option<std::cmp::Ordering> {
+	pub fn compare_energy(&self, other: &Self) -> Option<Ordering> {
 		if let (Some(e1), Some(e2)) = (self.energy, other.energy) {
 			e1.partial_cmp(&e2)
 		} else {
@@ -54,6 +64,34 @@ impl<M: SingleNode> SingleSolution<M> {
 		}
 	}
 
+	/// Set the number of occurrences of this solution.
+	pub fn with_occurrences(mut self, n: usize) -> Self {
+		self.occurrences = n;
+		self
+	}
+
+	/// Get the ratio of occurrences of this solution in the population which
+	/// has `total_occurrences` occurrences in total.
+	pub fn weight(&self, total_occurrences: usize) -> f64 {
+		self.occurrences as f64 / total_occurrences as f64
+	}
+
+	/// Get the solution with the minimum energy like [`best()`], which is the
+	/// first one if there are several. Solutions whose energy is NaN are
+	/// ignored.
+	///
+	/// # Panics
+	/// Panics if some of the solutions do not have energy.
+	pub fn best_of(mut solutions: Vec<Self>) -> Option<Self> {
+		assert!(
+			solutions.iter().all(|sol| sol.energy.is_some()),
+			"energy must be calculated"
+		);
+		best_index(&solutions)
+			.filter(|i| energy_key(&solutions[*i]).is_some())
+			.map(|i| solutions.swap_remove(i))
+	}
+
 	/// Ensure that SingleSolution has local field.
 	pub fn with_local_field<P: SingleModelView<Node = M>>(mut self, model: &P) -> Self {
 		self.local_field = Some(self.clone().calculate_local_field(model));
@@ -115,6 +153,25 @@ impl<M: SingleNode> SingleSolution<M> {
 	}
 }
 
+/// Solutions are equal if both of their states and energies are equal.
+/// Solutions without energy are not equal to any solution.
+impl<M: SingleNode> PartialEq for SingleSolution<M> {
+	fn eq(&self, other: &Self) -> bool {
+		self.state == other.state && self.compare_energy(other) == Some(Ordering::Equal)
+	}
+}
+
+/// Solutions are ordered by their energy. Solutions without energy, and
+/// different solutions of the same energy, are not comparable.
+impl<M: SingleNode> PartialOrd for SingleSolution<M> {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		match self.compare_energy(other) {
+			Some(Ordering::Equal) if self.state != other.state => None,
+			ord => ord,
+		}
+	}
+}
+
 impl<M: SingleNode> std::ops::Index<usize> for SingleSolution<M> {
 	type Output = bool;
 	#[inline]
@@ -126,3 +183,173 @@ impl<M: SingleNode> std::ops::Index<usize> for SingleSolution<M> {
 		}
 	}
 }
+
+/// The number of the nodes whose values differ between `a` and `b`.
+///
+/// # Panics
+///
+/// Panics if the lengths of the solutions differ.
+pub fn hamming_distance<M: SingleNode>(a: &SingleSolution<M>, b: &SingleSolution<M>) -> usize {
+	a.state.hamming_distance(&b.state)
+}
+
+/// The energy of the solution, or `None` if it has no energy or its energy is
+/// NaN.
+fn energy_key<M: SingleNode>(sol: &SingleSolution<M>) -> Option<M::RealType> {
+	sol.energy.filter(|e| e.partial_cmp(e).is_some())
+}
+
+/// Order of the solutions by energy, in which the solutions without energy or
+/// with NaN energy come last.
+fn energy_order<M: SingleNode>(a: &SingleSolution<M>, b: &SingleSolution<M>) -> Ordering {
+	match (energy_key(a), energy_key(b)) {
+		(Some(e1), Some(e2)) => e1.partial_cmp(&e2).unwrap(),
+		(Some(_), None) => Ordering::Less,
+		(None, Some(_)) => Ordering::Greater,
+		(None, None) => Ordering::Equal,
+	}
+}
+
+/// Sort the solutions in the ascending order of their energies. The energies
+/// of the solutions without energy are calculated with `model`, and the
+/// solutions whose energy is NaN sort last. The sort is stable.
+pub fn sort_by_energy<M: SingleNode, P: SingleModelView<Node = M>>(
+	solutions: &mut [SingleSolution<M>],
+	model: &P,
+) {
+	for sol in solutions.iter_mut() {
+		if sol.energy.is_none() {
+			sol.energy = Some(sol.calculate_energy(model));
+		}
+	}
+	solutions.sort_by(energy_order);
+}
+
+/// Get the solution with the minimum energy, which is the first one if there
+/// are several. The solutions without energy or with NaN energy sort last, so
+/// one of them is returned only if no solution has an energy. Returns `None` if
+/// `solutions` is empty.
+pub fn best<M: SingleNode>(solutions: &[SingleSolution<M>]) -> Option<&SingleSolution<M>> {
+	best_index(solutions).map(|i| &solutions[i])
+}
+
+/// The index of the solution returned by [`best()`].
+fn best_index<M: SingleNode>(solutions: &[SingleSolution<M>]) -> Option<usize> {
+	(0..solutions.len()).reduce(|b, i| {
+		if energy_order(&solutions[i], &solutions[b]) == Ordering::Less {
+			i
+		} else {
+			b
+		}
+	})
+}
+
+#[test]
+fn best_of_test() {
+	use crate::node::Binary;
+	let solutions = vec![(3.0, false), (-1.0, true), (f64::NAN, false), (2.0, true)]
+		.into_iter()
+		.map(|(e, b)| {
+			let mut sol = SingleSolution::<Binary<f64>>::from_vec(&[b]);
+			sol.energy = Some(e);
+			sol
+		})
+		.collect::<Vec<_>>();
+	assert!(solutions[1] < solutions[0]);
+	assert_eq!(solutions[2].partial_cmp(&solutions[0]), None);
+	let mut other = solutions[0].clone();
+	assert!(other == solutions[0]);
+	other.state.set(0, true);
+	assert!(other != solutions[0]);
+	assert_eq!(other.partial_cmp(&solutions[0]), None);
+	assert!(SingleSolution::best_of(vec![solutions[2].clone()]).is_none());
+	assert!(std::ptr::eq(best(&solutions).unwrap(), &solutions[1]));
+	let best = SingleSolution::best_of(solutions).unwrap();
+	assert_eq!(best.energy, Some(-1.0));
+	assert!(best.get(0));
+}
+
+#[test]
+fn hamming_distance_test() {
+	use crate::node::Binary;
+	let a = SingleSolution::<Binary<f64>>::from_vec(&[true, false, true]);
+	let b = SingleSolution::<Binary<f64>>::from_vec(&[false, false, false]);
+	assert_eq!(hamming_distance(&a, &b), 2);
+	assert_eq!(hamming_distance(&a, &a), 0);
+}
+
+#[test]
+#[should_panic]
+fn hamming_distance_len_test() {
+	use crate::node::Binary;
+	let a = SingleSolution::<Binary<f64>>::from_vec(&[true, false, true]);
+	let b = SingleSolution::<Binary<f64>>::from_vec(&[true, false]);
+	hamming_distance(&a, &b);
+}
+
+#[test]
+fn sort_by_energy_test() {
+	use crate::model::FixedSingleQuadricModel;
+	use crate::node::Binary;
+	use rand::seq::SliceRandom;
+	let mut model = FixedSingleQuadricModel::new(Binary::new(), 1);
+	model.add_weight(0, 0, 5.0f64);
+	let energies = [
+		Some(-2.0),
+		Some(0.5),
+		None,
+		Some(f64::NAN),
+		Some(3.0),
+		Some(-7.0),
+	];
+	let mut solutions = energies
+		.iter()
+		.map(|e| {
+			let mut sol = SingleSolution::<Binary<f64>>::from_vec(&[true]);
+			sol.energy = *e;
+			sol
+		})
+		.collect::<Vec<_>>();
+	solutions.shuffle(&mut rand::thread_rng());
+	assert_eq!(best(&solutions).unwrap().energy, Some(-7.0));
+	solutions.retain(|sol| sol.energy != Some(-7.0));
+	assert_eq!(best(&solutions).unwrap().energy, Some(-2.0));
+	sort_by_energy(&mut solutions, &model);
+	let sorted = solutions
+		.iter()
+		.map(|sol| sol.energy.unwrap())
+		.collect::<Vec<_>>();
+	assert_eq!(sorted[..4], [-2.0, 0.5, 3.0, 5.0]);
+	assert!(sorted[4].is_nan());
+
+	let mut sol = SingleSolution::<Binary<f64>>::from_vec(&[false]);
+	assert_eq!(best(std::slice::from_ref(&sol)).unwrap().energy, None);
+	sol.energy = Some(1.0);
+	let unknown = SingleSolution::<Binary<f64>>::from_vec(&[false]);
+	assert_eq!(best(&[unknown, sol]).unwrap().energy, Some(1.0));
+	assert!(best::<Binary<f64>>(&[]).is_none());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_test() {
+	use crate::node::Binary;
+	let mut sol = SingleSolution::<Binary<f64>>::from_vec(&[
+		true, false, true, true, false, false, true, false, true,
+	]);
+	sol.energy = Some(-3.5);
+	sol.occurrences = 4;
+	sol.local_field = Some(vec![1.0, -2.0]);
+	let json = serde_json::to_string(&sol).unwrap();
+	let bin = bincode::serialize(&sol).unwrap();
+	for de in [
+		serde_json::from_str::<SingleSolution<Binary<f64>>>(&json).unwrap(),
+		bincode::deserialize(&bin).unwrap(),
+	] {
+		assert_eq!(de.state.to_vec(), sol.state.to_vec());
+		assert_eq!(de.energy, Some(-3.5));
+		assert_eq!(de.occurrences, 4);
+		assert_eq!(de.local_field, Some(vec![1.0, -2.0]));
+	}
+	assert!(serde_json::from_str::<crate::repr::BinaryRepr>("[9,[1]]").is_err());
+}
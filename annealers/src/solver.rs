@@ -35,7 +35,7 @@
 //! - `SyncSolver`
 //! - `RngSolver`
 extern crate async_trait;
-use crate::model::ModelView;
+use crate::model::{ModelView, Subsets};
 use crate::node::Node;
 use crate::order::Order;
 use crate::solution::Solution;
@@ -169,37 +169,65 @@ fn unstructured_edge_iter_test() {
 	assert_eq!(iter.next(), None);
 }
 
-pub struct UnstructuredEdgeIter(usize, usize, Vec<usize>, Box<dyn Iterator<Item = usize>>);
+/// Iterator of the products of `2..=max_order` nodes taken from the possibly
+/// infinite iterator of nodes. The products are in the ascending order of
+/// their last nodes, so that the nodes are taken lazily.
+pub struct UnstructuredEdgeIter {
+	max_order: usize,
+	nodes: Vec<usize>,
+	iter: Box<dyn Iterator<Item = usize>>,
+	/// The subsets of `nodes` which are combined with the last node
+	subsets: Subsets,
+}
 
 impl UnstructuredEdgeIter {
 	fn from_iter(iter: Box<dyn Iterator<Item = usize>>, max_order: usize) -> Self {
-		if max_order != 2 {
-			unimplemented!();
+		Self {
+			max_order,
+			nodes: Vec::new(),
+			iter,
+			subsets: Subsets::new(0, 1, 0),
 		}
-		Self(0, 0, Vec::new(), iter)
 	}
 }
 
 impl Iterator for UnstructuredEdgeIter {
 	type Item = BTreeSet<usize>;
 	fn next(&mut self) -> Option<BTreeSet<usize>> {
-		if self.0 == self.1 {
-			self.1 += 1;
-			self.0 = 0;
-			while self.2.len() <= self.1 {
-				self.2.push(self.3.next()?);
+		if self.max_order < 2 {
+			return None;
+		}
+		loop {
+			if let Some(v) = self.subsets.next() {
+				let last = *self.nodes.last().unwrap();
+				let mut s = v
+					.into_iter()
+					.map(|i| self.nodes[i])
+					.collect::<BTreeSet<_>>();
+				s.insert(last);
+				return Some(s);
 			}
-			self.next()
-		} else {
-			let i = self.2[self.0];
-			let j = self.2[self.1];
-			self.0 += 1;
-			Some({
-				let mut s = BTreeSet::new();
-				s.insert(i);
-				s.insert(j);
-				s
-			})
+			let n = self.nodes.len();
+			self.nodes.push(self.iter.next()?);
+			self.subsets = Subsets::new(n, 1, self.max_order - 1);
 		}
 	}
 }
+
+#[test]
+fn high_order_unstructured_edge_iter_test() {
+	let iter = Box::new(0usize..4) as Box<dyn Iterator<Item = usize>>;
+	let prods = UnstructuredEdgeIter::from_iter(iter, 3).collect::<Vec<_>>();
+	assert_eq!(prods.len(), 6 + 4);
+	assert_eq!(
+		prods[..4],
+		[vec![0, 1], vec![0, 2], vec![1, 2], vec![0, 1, 2]]
+			.iter()
+			.map(|v| v.iter().copied().collect::<BTreeSet<_>>())
+			.collect::<Vec<_>>()[..]
+	);
+	assert!(prods.iter().all(|p| (2..=3).contains(&p.len())));
+	let iter = Box::new(0usize..) as Box<dyn Iterator<Item = usize>>;
+	let mut iter = UnstructuredEdgeIter::from_iter(iter, 4);
+	assert_eq!(iter.nth(100).map(|p| p.len() >= 2), Some(true));
+}
@@ -46,6 +46,17 @@ pub trait Real:
 		Self::from_i32(1)
 	}
 
+	/// Same as [`Self::from_f64()`], but the fraction truncated by integer
+	/// types is rounded up.
+	#[inline]
+	fn from_f64_ceil(f: f64) -> Self {
+		if Self::from_f64(0.5) == Self::zero() {
+			Self::from_f64(f.ceil())
+		} else {
+			Self::from_f64(f)
+		}
+	}
+
 	// TODO: add compare_with_f64() and remove as_f64(), from_i32()
 }
 
@@ -125,6 +136,55 @@ impl_real_as_f64!(false, i32, std::cmp);
 impl_real_as_f64!(false, i64, std::cmp);
 impl_real_as_f64!(false, i128, std::cmp);
 
+/// `OrderedFloat<f64>` is `Ord` and `Hash` with the total order of
+/// `OrderedFloat`, in which NaN is the largest. The arithmetic is delegated to
+/// `f64`.
+#[cfg(feature = "ordered-float")]
+impl Real for ordered_float::OrderedFloat<f64> {
+	const MAX: Self = ordered_float::OrderedFloat(f64::MAX);
+	const MIN: Self = ordered_float::OrderedFloat(f64::MIN);
+
+	#[inline]
+	fn as_f64(&self) -> f64 {
+		self.0
+	}
+
+	#[inline]
+	fn from_i32(i: i32) -> Self {
+		ordered_float::OrderedFloat(i as f64)
+	}
+
+	#[inline]
+	fn from_f64(f: f64) -> Self {
+		ordered_float::OrderedFloat(f)
+	}
+
+	#[inline]
+	fn abs(self) -> Self {
+		ordered_float::OrderedFloat(self.0.abs())
+	}
+
+	#[inline]
+	fn min(self, other: Self) -> Self {
+		std::cmp::min(self, other)
+	}
+
+	#[inline]
+	fn max(self, other: Self) -> Self {
+		std::cmp::max(self, other)
+	}
+
+	#[inline]
+	fn nan_or(_other: Self) -> Self {
+		ordered_float::OrderedFloat(f64::NAN)
+	}
+
+	#[inline]
+	fn is_finite(self) -> bool {
+		self.0.is_finite()
+	}
+}
+
 /// This trait is implemented between all Real types.
 pub trait ConvertForce<R: Real>: Real {
 	fn convert_force(self) -> R;
@@ -198,6 +258,29 @@ impl_convert_force!(f64, i64);
 impl_convert_force!(f64, i128);
 impl_convert_force!(f64, f32);
 
+#[cfg(feature = "ordered-float")]
+macro_rules! impl_convert_force_ordered {
+	($($r:ty),*) => {
+		$(
+			impl ConvertForce<$r> for ordered_float::OrderedFloat<f64> {
+				#[inline]
+				fn convert_force(self) -> $r {
+					self.0 as $r
+				}
+			}
+
+			impl ConvertForce<ordered_float::OrderedFloat<f64>> for $r {
+				#[inline]
+				fn convert_force(self) -> ordered_float::OrderedFloat<f64> {
+					ordered_float::OrderedFloat(self as f64)
+				}
+			}
+		)*
+	};
+}
+#[cfg(feature = "ordered-float")]
+impl_convert_force_ordered!(i8, i16, i32, i64, i128, f32, f64);
+
 pub trait ConvertFrom<R: Real>: ConvertForce<R> {
 	fn convert_from(f: R) -> Self;
 }
@@ -291,3 +374,25 @@ impl_all!(i64, f64);
 impl_all!(i128, f32);
 impl_all!(i128, f64);
 impl_all!(f32, f64);
+
+#[cfg(feature = "ordered-float")]
+#[test]
+fn ordered_float_test() {
+	use ordered_float::OrderedFloat;
+	let mut v = [
+		OrderedFloat(2.5),
+		OrderedFloat(f64::NAN),
+		OrderedFloat(-1.0),
+	];
+	v.sort();
+	assert_eq!(v[0], OrderedFloat(-1.0));
+	assert!(v[2].is_nan());
+	assert_eq!(
+		Real::max(OrderedFloat(1.0), OrderedFloat(3.0)),
+		OrderedFloat(3.0)
+	);
+	assert!(!OrderedFloat::<f64>::nan_or(OrderedFloat(0.0)).is_finite());
+	let sum: OrderedFloat<f64> = v[..2].iter().copied().sum();
+	assert_eq!(sum.as_f64(), 1.5);
+	assert_eq!(ConvertForce::<i32>::convert_force(OrderedFloat(2.0)), 2);
+}
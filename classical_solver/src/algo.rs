@@ -1,7 +1,8 @@
-use annealers::model::SingleModelView;
-use annealers::node::{Binary, Node, SingleNode};
+use annealers::model::SparseIterableModel;
+use annealers::node::{Node, SingleNode};
 use annealers::repr::BinaryRepr;
 use annealers::set::NodeSet;
+use annealers::solution::SingleSolution;
 use annealers::variable::Real;
 use rand::prelude::*;
 
@@ -27,13 +28,78 @@ unsafe fn calculate_flip_cost<S: NodeSet, M: SingleNode>(
 	}
 }
 
-pub fn simulated_annealing<T: Rng, P: SingleModelView<Node = Binary<R>>, R: Real>(
+/// If the ratio of the non-zero couplers to all the pairs of nodes is less than
+/// this value, [`simulated_annealing()`] updates the energy differences along
+/// an adjacency list of the couplers.
+pub const SPARSE_DENSITY: f64 = 0.1;
+
+/// Callback called after each beta step of [`simulated_annealing()`] with the
+/// index of the step, the beta, the energy of the state and the ratio of the
+/// accepted flips in the step.
+pub type DiagnosticCallback = dyn Fn(usize, f64, f64, f64) + Send + Sync;
+
+/// Rule of [`simulated_annealing()`] to accept a flip which changes the energy
+/// by `ed` at the inverse temperature `beta`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AcceptanceRule {
+	/// Accept with the probability `min(1, exp(-ed * beta))`.
+	#[default]
+	Metropolis,
+	/// Accept with the probability `1 / (1 + exp(ed * beta))`, also known as
+	/// the Glauber dynamics.
+	HeatBath,
+}
+
+impl AcceptanceRule {
+	#[inline]
+	fn accepts<T: Rng>(&self, ed: f64, beta: f64, random: &mut T) -> bool {
+		match self {
+			Self::Metropolis => ed <= 0.0 || f64::exp(-ed * beta) > random.gen_range(0.0, 1.0),
+			Self::HeatBath => 1.0 / (1.0 + f64::exp(ed * beta)) > random.gen_range(0.0, 1.0),
+		}
+	}
+}
+
+/// Anneals `state` with the Metropolis rule. See
+/// [`simulated_annealing_with_rule()`] to select another [`AcceptanceRule`].
+pub fn simulated_annealing<T, P, N, R>(
 	random: &mut T,
 	state: &mut BinaryRepr,
-	beta_schedule: &[<P::Node as Node>::RealType],
+	beta_schedule: &[f64],
 	sweeps_per_round: usize,
 	model: &P,
-) {
+	diagnostic: Option<&DiagnosticCallback>,
+) where
+	T: Rng,
+	P: SparseIterableModel<Node = N>,
+	N: SingleNode<RealType = R>,
+	R: Real,
+{
+	simulated_annealing_with_rule(
+		random,
+		state,
+		beta_schedule,
+		sweeps_per_round,
+		model,
+		AcceptanceRule::Metropolis,
+		diagnostic,
+	)
+}
+
+pub fn simulated_annealing_with_rule<T, P, N, R>(
+	random: &mut T,
+	state: &mut BinaryRepr,
+	beta_schedule: &[f64],
+	sweeps_per_round: usize,
+	model: &P,
+	rule: AcceptanceRule,
+	diagnostic: Option<&DiagnosticCallback>,
+) where
+	T: Rng,
+	P: SparseIterableModel<Node = N>,
+	N: SingleNode<RealType = R>,
+	R: Real,
+{
 	assert!(state.len() == model.size());
 	let size = model.size();
 	let node = model.node();
@@ -42,37 +108,80 @@ pub fn simulated_annealing<T: Rng, P: SingleModelView<Node = Binary<R>>, R: Real
 		.collect::<Vec<_>>();
 	let d = node.get_value(true) - node.get_value(false);
 	let dd = d * d;
-	for prod in model.prods() {
+	let mut couplers = Vec::new();
+	for prod in model.nonzero_prods() {
 		let weight = model.get_weight(&prod);
 		for i in prod.iter() {
 			energy_diffs[i] += unsafe { calculate_flip_cost(node, &prod, &state, i) } * weight;
 		}
+		if prod.len() == 2 && weight != R::zero() {
+			let mut it = prod.iter();
+			couplers.push((it.next().unwrap(), it.next().unwrap(), weight));
+		}
 	}
-	for beta in beta_schedule.iter() {
+	// For sparse models, visit only the non-zero couplers when updating
+	// `energy_diffs` instead of all the neighbors.
+	let adjacency =
+		if (couplers.len() as f64) < SPARSE_DENSITY * (size * size.saturating_sub(1) / 2) as f64 {
+			let mut adjacency = vec![Vec::new(); size];
+			for (i, j, weight) in couplers.into_iter() {
+				adjacency[i].push((j, weight));
+				adjacency[j].push((i, weight));
+			}
+			Some(adjacency)
+		} else {
+			None
+		};
+	// The energy and the acceptances are tracked only for the diagnostics.
+	let mut energy = diagnostic.map(|_| {
+		let sol = SingleSolution::<N>::from_state(state.clone());
+		model
+			.nonzero_prods()
+			.into_iter()
+			.map(|p| (model.get_weight(&p) * model.calculate_prod(&p, &sol)).as_f64())
+			.sum::<f64>()
+	});
+	for (step, beta) in beta_schedule.iter().enumerate() {
+		let mut accepted = 0;
 		for _ in 0..sweeps_per_round {
-			let threshold = 44.36142 / beta.as_f64();
+			let threshold = 44.36142 / beta;
 			for i in 0..state.len() {
 				let ed = energy_diffs[i];
 				if ed.as_f64() > threshold {
 					continue;
 				}
-				if ed.as_f64() <= 0.0
-					|| f64::exp(-(ed * *beta).as_f64()) > random.gen_range(0.0, 1.0)
-				{
+				if rule.accepts(ed.as_f64(), *beta, random) {
 					unsafe {
 						state.flip_unchecked(i);
 					}
+					if let Some(energy) = &mut energy {
+						*energy += ed.as_f64();
+						accepted += 1;
+					}
 					let stat = unsafe { state.get_unchecked(i) };
 					energy_diffs[i] *= -<P::Node as Node>::RealType::one();
-					for neigh in model.neighbors(i) {
-						if neigh.len() != 1 {
-							let weight = model.get_weight(&neigh);
-							for j in neigh.iter() {
-								if i != j {
-									if stat != unsafe { state.get_unchecked(j) } {
-										energy_diffs[j] += dd * weight;
-									} else {
-										energy_diffs[j] -= dd * weight;
+					if let Some(adjacency) = &adjacency {
+						for (j, weight) in adjacency[i].iter() {
+							if stat != unsafe { state.get_unchecked(*j) } {
+								energy_diffs[*j] += dd * *weight;
+							} else {
+								energy_diffs[*j] -= dd * *weight;
+							}
+						}
+					} else {
+						for neigh in model.neighbors(i) {
+							if neigh.len() != 1 {
+								let weight = model.get_weight(&neigh);
+								if weight == <P::Node as Node>::RealType::zero() {
+									continue;
+								}
+								for j in neigh.iter() {
+									if i != j {
+										if stat != unsafe { state.get_unchecked(j) } {
+											energy_diffs[j] += dd * weight;
+										} else {
+											energy_diffs[j] -= dd * weight;
+										}
 									}
 								}
 							}
@@ -81,6 +190,10 @@ pub fn simulated_annealing<T: Rng, P: SingleModelView<Node = Binary<R>>, R: Real
 				}
 			}
 		}
+		if let (Some(cb), Some(energy)) = (diagnostic, energy) {
+			let trials = (sweeps_per_round * state.len()).max(1);
+			cb(step, *beta, energy, accepted as f64 / trials as f64);
+		}
 	}
 }
 
@@ -2,19 +2,43 @@
 /// Generally, beta schedule is array of `f64`, but effecient beta schedule is
 /// generated from *beta range* or *beta count*. So you can specify them in
 /// place of beta schedule.
-use annealers::model::SingleModelView;
+use annealers::model::SparseIterableModel;
 use annealers::node::{Node, SingleNode};
+use annealers::set::NodeSet;
 use annealers::variable::Real;
 
 #[derive(Clone, Debug)]
 pub enum BetaType<R: Real> {
 	/// Specify beta schedule by *beta count*.
 	Count(usize),
-	/// Specify beta schedule by *beta count* and *beta range*.
+	/// Specify beta schedule by *beta count* and *beta range*. Same as
+	/// [`BetaType::Geometric`].
 	CountRange(usize, R, R),
+	/// Specify beta schedule by *beta count* and *beta range*, whose betas are
+	/// spaced geometrically.
+	Geometric(usize, R, R),
+	/// Specify beta schedule by *beta count* and *beta range*, whose betas are
+	/// spaced linearly.
+	Linear(usize, R, R),
 	/// Specify *beta schedule* manually. This values should take larger
 	/// as the index incleases.
 	Schedule(Vec<R>),
+	/// Specify beta schedule by *beta count* determined from the size of the
+	/// model with [`auto_beta_count()`].
+	Auto,
+}
+
+/// Heuristic *beta count* for a model with `size` nodes, which is
+/// proportional to `size` and clamped to `10..=1000`. It is 100 for 1000
+/// nodes.
+pub fn auto_beta_count(size: usize) -> usize {
+	(size / 10).clamp(10, 1000)
+}
+
+/// Heuristic number of sweeps per beta step for a model with `size` nodes,
+/// which is proportional to `log(size)`. It is 35 for 1000 nodes.
+pub fn auto_sweeps_per_round(size: usize) -> usize {
+	((5.0 * (size as f64 + 1.0).ln()).ceil() as usize).max(1)
 }
 
 macro_rules! real_typ {
@@ -23,63 +47,126 @@ macro_rules! real_typ {
 	};
 }
 
-fn generate_beta_range<P: SingleModelView>(model: &P) -> (real_typ!(P), real_typ!(P)) {
-	macro_rules! nan_or_min {
-		() => {
-			<real_typ!(P)>::nan_or(<real_typ!(P)>::MIN)
-		};
-	}
+fn generate_beta_range<P: SparseIterableModel>(model: &P) -> (f64, f64) {
 	let node = model.node();
-	let ndiff = node.get_value(true) - node.get_value(false);
-	let eg_min = model
-		.prods()
-		.into_iter()
-		.map(|p| model.get_weight(&p).abs())
-		.fold(nan_or_min!(), |p, n: real_typ!(P)| n.max(p));
-	let eg_max = model
-		.nodes()
+	// The order of the values does not matter, e.g. for `TwoVal`.
+	let ndiff = (node.get_value(true) - node.get_value(false)).abs();
+	let (_, eg_min) = model.weight_range();
+	// The sum of the absolute weights of the products containing each node
+	let mut sums = vec![<real_typ!(P)>::zero(); model.size()];
+	for p in model.nonzero_prods() {
+		let w = model.get_weight(&p).abs();
+		for n in p.iter() {
+			sums[n] += w;
+		}
+	}
+	let eg_max = sums
 		.into_iter()
-		.map(|n| {
-			model
-				.neighbors(n)
-				.into_iter()
-				.map(|p| model.get_weight(&p).abs())
-				.sum()
-		})
-		.fold(nan_or_min!(), |p, n: real_typ!(P)| n.max(p));
-	if eg_max.is_finite() && eg_min.is_finite() {
+		.fold(<real_typ!(P)>::zero(), <real_typ!(P)>::max);
+	// `eg_min` is zero if all the weights are zero, when any schedule works.
+	if eg_min != <real_typ!(P)>::zero() && eg_min.is_finite() && eg_max.is_finite() {
 		(
-			<real_typ!(P)>::from_f64(f64::ln(2.0) / (ndiff * eg_max).as_f64()),
-			<real_typ!(P)>::from_f64(f64::ln(100.0) / (ndiff * eg_min).as_f64()),
+			f64::ln(2.0) / (ndiff * eg_max).as_f64(),
+			f64::ln(100.0) / (ndiff * eg_min).as_f64(),
 		)
 	} else {
-		(<real_typ!(P)>::one(), <real_typ!(P)>::from_i32(10))
+		(0.1, 1.0)
 	}
 }
 
 /// Generate *beta schedule* from given parameters.
 /// the meanings of the parameters is the same of
 /// [`AnnealerInfo::build_with_ising()`].
-pub(crate) fn generate_schedule<P: SingleModelView>(
+///
+/// The schedule is always `f64`, because beta is usually fractional even if
+/// the weights of the model are integers.
+pub(crate) fn generate_schedule<P: SparseIterableModel>(
 	beta_type: &BetaType<real_typ!(P)>,
 	model: &P,
-) -> Vec<real_typ!(P)> {
+) -> Vec<f64> {
 	match beta_type {
-		BetaType::Schedule(v) => v.clone(),
-		BetaType::Count(count) | BetaType::CountRange(count, _, _) => {
-			let (min, max) = if let BetaType::CountRange(_, min, max) = beta_type {
-				(*min, *max)
-			} else {
-				generate_beta_range(model)
-			};
+		BetaType::Schedule(v) => v.iter().map(|beta| beta.as_f64()).collect(),
+		BetaType::Auto => {
+			let (min, max) = generate_beta_range(model);
+			generate_beta_schedule(min, max, auto_beta_count(model.size()))
+		}
+		BetaType::Count(count) => {
+			let (min, max) = generate_beta_range(model);
 			generate_beta_schedule(min, max, *count)
 		}
+		BetaType::CountRange(count, min, max) | BetaType::Geometric(count, min, max) => {
+			generate_beta_schedule(min.as_f64(), max.as_f64(), *count)
+		}
+		BetaType::Linear(count, min, max) => {
+			generate_linear_schedule(min.as_f64(), max.as_f64(), *count)
+		}
 	}
 }
 
-fn generate_beta_schedule<R: Real>(beta_min: R, beta_max: R, count: usize) -> Vec<R> {
-	let r = f64::ln(beta_max.as_f64() / beta_min.as_f64()) / (count as f64 - 1.0);
+fn generate_beta_schedule(beta_min: f64, beta_max: f64, count: usize) -> Vec<f64> {
+	let r = f64::ln(beta_max / beta_min) / (count as f64 - 1.0);
 	(0..count)
-		.map(|index| R::from_f64(beta_min.as_f64() * f64::exp(index as f64 * r)))
+		.map(|index| beta_min * f64::exp(index as f64 * r))
 		.collect()
 }
+
+fn generate_linear_schedule(beta_min: f64, beta_max: f64, count: usize) -> Vec<f64> {
+	let d = (beta_max - beta_min) / (count as f64 - 1.0).max(1.0);
+	(0..count)
+		.map(|index| beta_min + index as f64 * d)
+		.collect()
+}
+
+#[test]
+fn auto_schedule_test() {
+	use annealers::model::FixedSingleQuadricModel;
+	use annealers::node::Binary;
+	let small = FixedSingleQuadricModel::new(Binary::<f64>::new(), 10);
+	let large = FixedSingleQuadricModel::new(Binary::<f64>::new(), 1000);
+	let small = generate_schedule(&BetaType::Auto, &small);
+	let large = generate_schedule(&BetaType::Auto, &large);
+	assert_eq!(small.len(), auto_beta_count(10));
+	assert_eq!(large.len(), auto_beta_count(1000));
+	assert!(large.len() > small.len());
+	assert!(auto_sweeps_per_round(1000) > auto_sweeps_per_round(10));
+}
+
+#[test]
+fn linear_geometric_schedule_test() {
+	use annealers::model::FixedSingleQuadricModel;
+	use annealers::node::Binary;
+	let model = FixedSingleQuadricModel::new(Binary::<f64>::new(), 10);
+	let linear = generate_schedule(&BetaType::Linear(5, 0.5, 4.5), &model);
+	assert_eq!(linear, vec![0.5, 1.5, 2.5, 3.5, 4.5]);
+	let geometric = generate_schedule(&BetaType::Geometric(5, 0.5, 8.0), &model);
+	assert_eq!(geometric.len(), 5);
+	assert!((geometric[0] - 0.5).abs() < 1e-9 && (geometric[4] - 8.0).abs() < 1e-9);
+	for w in linear.windows(3) {
+		assert!((w[2] - w[1] - (w[1] - w[0])).abs() < 1e-9);
+	}
+	for w in geometric.windows(3) {
+		assert!((w[2] / w[1] - w[1] / w[0]).abs() < 1e-9);
+	}
+	assert_eq!(
+		generate_schedule(&BetaType::CountRange(5, 0.5, 8.0), &model),
+		geometric
+	);
+}
+
+#[test]
+fn zero_model_schedule_test() {
+	use annealers::model::FixedSingleQuadricModel;
+	use annealers::node::Binary;
+	let mut model = FixedSingleQuadricModel::new(Binary::<f64>::new(), 4);
+	assert_eq!(generate_beta_range(&model), (0.1, 1.0));
+	model.add_weight(0, 1, 2.0);
+	model.add_weight(0, 1, -2.0);
+	assert_eq!(generate_beta_range(&model), (0.1, 1.0));
+	let schedule = generate_schedule(&BetaType::Count(10), &model);
+	assert!(schedule.iter().all(|beta| beta.is_finite() && *beta > 0.0));
+	model.add_weight(1, 1, -0.5);
+	model.add_weight(1, 2, 4.0);
+	let (min, max) = generate_beta_range(&model);
+	assert_eq!(min, f64::ln(2.0) / 4.5);
+	assert_eq!(max, f64::ln(100.0) / 4.0);
+}
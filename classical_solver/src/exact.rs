@@ -0,0 +1,127 @@
+use crate::ExactSolverError;
+use annealers::model::SingleModelView;
+use annealers::node::Binary;
+use annealers::order::Quadric;
+use annealers::solution::SingleSolution;
+use annealers::solver::{ClassicalSolver, Solver, SolverGenerator, UnstructuredSolverGenerator};
+use annealers::variable::Real;
+
+/// The default of [`ExactSolverGenerator::max_size`].
+pub const DEFAULT_MAX_SIZE: usize = 25;
+
+/// Generator of [`ExactSolver`], which finds the optimum of the model by
+/// enumerating all the `2^n` assignments. It is useful as a reference of the
+/// other solvers on small models.
+#[derive(Clone, Debug)]
+pub struct ExactSolverGenerator {
+	/// The maximum number of the nodes of the model. Larger models are
+	/// refused with [`ExactSolverError::TooLarge`].
+	pub max_size: usize,
+}
+
+pub struct ExactSolver<'a, P> {
+	model: &'a P,
+}
+
+impl ExactSolverGenerator {
+	pub fn new() -> Self {
+		Self {
+			max_size: DEFAULT_MAX_SIZE,
+		}
+	}
+}
+
+impl Default for ExactSolverGenerator {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<'a, R: Real, P: SingleModelView<Node = Binary<R>> + Sync + 'a> SolverGenerator<'a, P>
+	for ExactSolverGenerator
+{
+	type SolverType = ExactSolver<'a, P>;
+	type ErrorType = ExactSolverError;
+
+	fn generate(&self, model: &'a P) -> Result<Self::SolverType, Self::ErrorType> {
+		// The assignments are enumerated with a `usize` counter.
+		let max_size = self.max_size.min(usize::BITS as usize - 1);
+		if model.size() > max_size {
+			return Err(ExactSolverError::TooLarge {
+				size: model.size(),
+				max_size,
+			});
+		}
+		Ok(ExactSolver { model })
+	}
+}
+
+impl<'a, R: Real, P: SingleModelView<Node = Binary<R>> + Sync + 'a>
+	UnstructuredSolverGenerator<'a, P> for ExactSolverGenerator
+{
+	type Order = Quadric;
+	fn order(&self) -> Quadric {
+		Quadric
+	}
+}
+
+impl<'a, R: Real, P: SingleModelView<Node = Binary<R>> + Sync> Solver for ExactSolver<'a, P> {
+	type ErrorType = ExactSolverError;
+	type SolutionType = SingleSolution<Binary<R>>;
+}
+
+impl<'a, R: Real, P: SingleModelView<Node = Binary<R>> + Sync> ClassicalSolver
+	for ExactSolver<'a, P>
+{
+	/// Returns the optimum, which is the first one in the order of the
+	/// enumeration if there are several.
+	fn solve_with_rng<T: rand::Rng>(
+		&self,
+		_r: &mut T,
+	) -> Result<Vec<SingleSolution<Binary<R>>>, ExactSolverError> {
+		let size = self.model.size();
+		// For each node, the weights of the products containing it together
+		// with the other nodes of the products
+		let mut prods: Vec<Vec<(Vec<usize>, R)>> = vec![Vec::new(); size];
+		for p in self.model.prods() {
+			let weight = self.model.get_weight(&p);
+			if weight == R::zero() {
+				continue;
+			}
+			let nodes = annealers::set::NodeSet::into_vec(p);
+			for &u in nodes.iter() {
+				let others = nodes
+					.iter()
+					.filter(|v| **v != u)
+					.copied()
+					.collect::<Vec<_>>();
+				prods[u].push((others, weight));
+			}
+		}
+		// The energy of the all-false state is zero. Each step of the Gray code
+		// flips one node, so the energy is updated with its local field.
+		let mut state = vec![false; size];
+		let mut energy = R::zero();
+		let (mut best, mut best_energy) = (state.clone(), energy);
+		for i in 1..1usize << size {
+			let u = i.trailing_zeros() as usize;
+			let field = prods[u]
+				.iter()
+				.filter(|(others, _)| others.iter().all(|v| state[*v]))
+				.fold(R::zero(), |acc, (_, w)| acc + *w);
+			state[u] = !state[u];
+			if state[u] {
+				energy += field;
+			} else {
+				energy -= field;
+			}
+			if energy < best_energy {
+				best.copy_from_slice(&state);
+				best_energy = energy;
+			}
+		}
+		let mut sol = SingleSolution::from_vec(&best);
+		sol.energy = Some(best_energy);
+		Ok(vec![sol])
+	}
+}
@@ -18,6 +18,8 @@ extern crate rand;
 
 pub mod algo;
 pub mod beta;
+pub mod exact;
+pub mod multi_start;
 pub mod sa;
 
 /// `NoneError` means the error will never be returned. It will be replaced with
@@ -31,3 +33,45 @@ impl std::fmt::Display for NoneError {
 }
 
 impl std::error::Error for NoneError {}
+
+/// Error returned by [`sa::SimulatedAnnealerGenerator`].
+#[derive(Debug)]
+pub enum SimulatedAnnealerError {
+	/// The length of the initial state does not match the size of the model.
+	InitialStateSizeMismatch { expected: usize, actual: usize },
+}
+
+impl std::fmt::Display for SimulatedAnnealerError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::InitialStateSizeMismatch { expected, actual } => write!(
+				f,
+				"the size of the initial state is {} but the model has {} nodes",
+				actual, expected
+			),
+		}
+	}
+}
+
+impl std::error::Error for SimulatedAnnealerError {}
+
+/// Error returned by [`exact::ExactSolverGenerator`].
+#[derive(Debug)]
+pub enum ExactSolverError {
+	/// The model has more nodes than the limit.
+	TooLarge { size: usize, max_size: usize },
+}
+
+impl std::fmt::Display for ExactSolverError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::TooLarge { size, max_size } => write!(
+				f,
+				"the model has {} nodes but the exact solver accepts at most {}",
+				size, max_size
+			),
+		}
+	}
+}
+
+impl std::error::Error for ExactSolverError {}
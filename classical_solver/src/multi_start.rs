@@ -0,0 +1,103 @@
+use annealers::model::SingleModelView;
+use annealers::solution::SingleSolution;
+use annealers::solver::{ClassicalSolver, Solver, SolverGenerator, UnstructuredSolverGenerator};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Generator which runs the solver generated by `G` `restarts` times and keeps
+/// the best solution.
+#[derive(Clone, Debug)]
+pub struct MultiStart<G> {
+	pub inner: G,
+	pub restarts: usize,
+	/// If true, the solver returns all the solutions sorted by energy instead
+	/// of the best one.
+	pub keep_all: bool,
+}
+
+pub struct MultiStartSolver<'a, S, P> {
+	inner: S,
+	restarts: usize,
+	keep_all: bool,
+	model: &'a P,
+}
+
+impl<G> MultiStart<G> {
+	pub fn new(inner: G, restarts: usize) -> Self {
+		Self {
+			inner,
+			restarts,
+			keep_all: false,
+		}
+	}
+}
+
+impl<'a, G, P> SolverGenerator<'a, P> for MultiStart<G>
+where
+	G: UnstructuredSolverGenerator<'a, P>,
+	G::SolverType: Solver<SolutionType = SingleSolution<P::Node>>,
+	P: SingleModelView + Sync + 'a,
+{
+	type SolverType = MultiStartSolver<'a, G::SolverType, P>;
+	type ErrorType = G::ErrorType;
+
+	fn generate(&self, model: &'a P) -> Result<Self::SolverType, Self::ErrorType> {
+		Ok(MultiStartSolver {
+			inner: self.inner.generate(model)?,
+			restarts: self.restarts,
+			keep_all: self.keep_all,
+			model,
+		})
+	}
+}
+
+impl<'a, G, P> UnstructuredSolverGenerator<'a, P> for MultiStart<G>
+where
+	G: UnstructuredSolverGenerator<'a, P>,
+	G::SolverType: Solver<SolutionType = SingleSolution<P::Node>>,
+	P: SingleModelView + Sync + 'a,
+{
+	type Order = G::Order;
+
+	fn order(&self) -> Self::Order {
+		self.inner.order()
+	}
+
+	fn size(&self) -> Option<usize> {
+		self.inner.size()
+	}
+}
+
+impl<'a, S, P> Solver for MultiStartSolver<'a, S, P>
+where
+	S: Solver<SolutionType = SingleSolution<P::Node>>,
+	P: SingleModelView + Sync,
+{
+	type ErrorType = S::ErrorType;
+	type SolutionType = SingleSolution<P::Node>;
+}
+
+impl<'a, S, P> ClassicalSolver for MultiStartSolver<'a, S, P>
+where
+	S: ClassicalSolver<SolutionType = SingleSolution<P::Node>>,
+	P: SingleModelView + Sync,
+{
+	fn solve_with_rng<T: rand::Rng>(
+		&self,
+		r: &mut T,
+	) -> Result<Vec<SingleSolution<P::Node>>, S::ErrorType> {
+		let mut solutions = Vec::new();
+		for _ in 0..self.restarts {
+			let mut sub = StdRng::from_rng(&mut *r).unwrap();
+			for sol in self.inner.solve_with_rng(&mut sub)? {
+				solutions.push(sol.with_energy(self.model));
+			}
+		}
+		if self.keep_all {
+			solutions.sort_by(|a, b| a.compare_energy(b).unwrap_or(std::cmp::Ordering::Equal));
+			Ok(solutions)
+		} else {
+			Ok(SingleSolution::best_of(solutions).into_iter().collect())
+		}
+	}
+}
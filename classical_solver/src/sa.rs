@@ -1,25 +1,60 @@
-use crate::algo::simulated_annealing;
-use crate::beta::BetaType;
-use crate::NoneError;
-use annealers::model::SingleModelView;
-use annealers::node::{Binary, Node};
-use annealers::order::Quadric;
+use crate::algo::{simulated_annealing_with_rule, AcceptanceRule, DiagnosticCallback};
+use crate::beta::{auto_beta_count, auto_sweeps_per_round, BetaType};
+use crate::SimulatedAnnealerError;
+use annealers::model::{SingleModelView, SparseIterableModel};
+use annealers::node::{Node, SingleNode};
+use annealers::order::{Order, Quadric};
 use annealers::repr::BinaryRepr;
+use annealers::set::NodeSet;
 use annealers::solution::SingleSolution;
 use annealers::solver::{ClassicalSolver, Solver, SolverGenerator, UnstructuredSolverGenerator};
-use annealers::variable::Real;
 use std::marker::PhantomData;
+use std::sync::Arc;
 
 #[derive(Clone, Debug)]
 pub struct SimulatedAnnealerGenerator<'a, P: SingleModelView> {
 	pub sweeps_per_round: usize,
 	pub beta: BetaType<<P::Node as Node>::RealType>,
+	/// The initial state of the annealer. If `None`, the annealer starts from
+	/// a random state.
+	pub initial_state: Option<BinaryRepr>,
+	/// The rule to accept the flips, which is Metropolis by default.
+	pub acceptance: AcceptanceRule,
+	diagnostic: Option<Diagnostic>,
 	_phantom: PhantomData<&'a P>,
 }
 
+/// The callback set by [`SimulatedAnnealerGenerator::with_diagnostic_callback`].
+#[derive(Clone)]
+struct Diagnostic(Arc<DiagnosticCallback>);
+
+impl std::fmt::Debug for Diagnostic {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str("Diagnostic")
+	}
+}
+
+/// Simulated annealer generated by [`SimulatedAnnealerGenerator`]. The annealer
+/// borrows the model, so it cannot outlive the model.
+///
+/// ```compile_fail,E0597
+/// # use annealers::model::FixedSingleQuadricModel;
+/// # use annealers::node::Binary;
+/// # use annealers::prelude::*;
+/// # use classical_solver::sa::SimulatedAnnealerGenerator;
+/// let gen = SimulatedAnnealerGenerator::new();
+/// let solver = {
+///     let model = FixedSingleQuadricModel::new(Binary::<f64>::new(), 3);
+///     gen.generate(&model).unwrap()
+/// };
+/// solver.solve_with_rng(&mut rand::thread_rng()).unwrap();
+/// ```
 pub struct SimulatedAnnealer<'a, P: SingleModelView, R> {
 	sweeps_per_round: usize,
-	beta_schedule: Vec<<P::Node as Node>::RealType>,
+	beta_schedule: Vec<f64>,
+	initial_state: Option<BinaryRepr>,
+	acceptance: AcceptanceRule,
+	diagnostic: Option<Diagnostic>,
 	model: &'a P,
 	_phantom: PhantomData<R>,
 }
@@ -29,31 +64,65 @@ impl<'a, P: SingleModelView> SimulatedAnnealerGenerator<'a, P> {
 		Self {
 			sweeps_per_round: 30,
 			beta: BetaType::Count(100),
+			initial_state: None,
+			acceptance: AcceptanceRule::default(),
+			diagnostic: None,
 			_phantom: PhantomData,
 		}
 	}
+
+	/// Set the callback called after each beta step of the annealing with
+	/// `(step, beta, energy, acceptance_rate)`, where `acceptance_rate` is the
+	/// ratio of the accepted flips to the trials in the step. The energy and
+	/// the acceptances are not tracked without the callback.
+	pub fn with_diagnostic_callback<F>(mut self, cb: F) -> Self
+	where
+		F: Fn(usize, f64, f64, f64) + Send + Sync + 'static,
+	{
+		self.diagnostic = Some(Diagnostic(Arc::new(cb)));
+		self
+	}
+
+	/// Set `sweeps_per_round` and `beta` with the heuristics in
+	/// [`crate::beta`] for a model with `model_size` nodes.
+	pub fn auto_tune(mut self, model_size: usize) -> Self {
+		self.sweeps_per_round = auto_sweeps_per_round(model_size);
+		self.beta = BetaType::Count(auto_beta_count(model_size));
+		self
+	}
 }
 
-impl<'a, P: SingleModelView + Send + Sync> SolverGenerator<'a, P>
-	for SimulatedAnnealerGenerator<'a, P>
+impl<'a, 'b, P: SparseIterableModel + Send + Sync + 'a> SolverGenerator<'a, P>
+	for SimulatedAnnealerGenerator<'b, P>
 {
 	type SolverType = SimulatedAnnealer<'a, P, <P::Node as Node>::RealType>;
-	type ErrorType = NoneError;
+	type ErrorType = SimulatedAnnealerError;
 
 	fn generate(&self, model: &'a P) -> Result<Self::SolverType, Self::ErrorType> {
+		if let Some(state) = &self.initial_state {
+			if state.len() != model.size() {
+				return Err(SimulatedAnnealerError::InitialStateSizeMismatch {
+					expected: model.size(),
+					actual: state.len(),
+				});
+			}
+		}
 		// TODO: prevent copying model
 		let schedule = crate::beta::generate_schedule(&self.beta, model);
 		Ok(SimulatedAnnealer {
 			sweeps_per_round: self.sweeps_per_round,
 			beta_schedule: schedule,
+			initial_state: self.initial_state.clone(),
+			acceptance: self.acceptance,
+			diagnostic: self.diagnostic.clone(),
 			model: model,
 			_phantom: PhantomData,
 		})
 	}
 }
 
-impl<'a, P: SingleModelView + Send + Sync> UnstructuredSolverGenerator<'a, P>
-	for SimulatedAnnealerGenerator<'a, P>
+impl<'a, 'b, P: SparseIterableModel + Send + Sync + 'a> UnstructuredSolverGenerator<'a, P>
+	for SimulatedAnnealerGenerator<'b, P>
 {
 	type Order = Quadric;
 	fn order(&self) -> Quadric {
@@ -64,25 +133,43 @@ impl<'a, P: SingleModelView + Send + Sync> UnstructuredSolverGenerator<'a, P>
 impl<'a, P: SingleModelView + Send + Sync> Solver
 	for SimulatedAnnealer<'a, P, <P::Node as Node>::RealType>
 {
-	type ErrorType = NoneError;
+	type ErrorType = SimulatedAnnealerError;
 	type SolutionType = SingleSolution<P::Node>;
 }
 
-impl<'a, R: Real, P: SingleModelView<Node = Binary<R>> + Send + Sync> ClassicalSolver
-	for SimulatedAnnealer<'a, P, R>
+impl<'a, P: SparseIterableModel + Send + Sync> ClassicalSolver
+	for SimulatedAnnealer<'a, P, <P::Node as Node>::RealType>
 {
 	fn solve_with_rng<T: rand::Rng>(
 		&self,
 		r: &mut T,
-	) -> Result<Vec<SingleSolution<P::Node>>, NoneError> {
-		let mut state = BinaryRepr::new_random(self.model.size(), r);
+	) -> Result<Vec<SingleSolution<P::Node>>, SimulatedAnnealerError> {
+		let mut state = if let Some(state) = &self.initial_state {
+			state.clone()
+		} else {
+			BinaryRepr::new_random(self.model.size(), r)
+		};
+		if self.model.order().order() == 1 {
+			// The nodes of linear models are independent, so each of them is
+			// optimized without annealing.
+			let node = self.model.node();
+			for p in self.model.nonzero_prods() {
+				let w = self.model.get_weight(&p);
+				for i in p.iter() {
+					state.set(i, w * node.get_value(true) < w * node.get_value(false));
+				}
+			}
+			return Ok(vec![SingleSolution::from_state(state)]);
+		}
 		// let mut state = BinaryRepr::from_vec(&vec![true, false, true]);
-		simulated_annealing(
+		simulated_annealing_with_rule(
 			r,
 			&mut state,
 			self.beta_schedule.as_slice(),
 			self.sweeps_per_round,
 			self.model,
+			self.acceptance,
+			self.diagnostic.as_ref().map(|d| &*d.0),
 		);
 		Ok(vec![SingleSolution::from_state(state)])
 	}
@@ -0,0 +1,48 @@
+extern crate annealers;
+extern crate classical_solver;
+extern crate rand;
+
+use annealers::model::FixedSingleQuadricModel;
+use annealers::node::Binary;
+use annealers::prelude::*;
+use annealers::solution::SingleSolution;
+use classical_solver::exact::ExactSolverGenerator;
+use classical_solver::ExactSolverError;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+#[test]
+fn exact_test() {
+	let n = 10;
+	let mut r = StdRng::seed_from_u64(1);
+	let mut model = FixedSingleQuadricModel::new(Binary::new(), n);
+	for i in 0..n {
+		for j in i..n {
+			model.add_weight(i, j, r.gen_range(-10, 11) as f64);
+		}
+	}
+	let ground = (0..1usize << n)
+		.map(|s| {
+			let v = (0..n).map(|i| s >> i & 1 == 1).collect::<Vec<_>>();
+			SingleSolution::from_vec(&v).calculate_energy(&model)
+		})
+		.fold(f64::MAX, f64::min);
+
+	let solver = ExactSolverGenerator::new().generate(&model).unwrap();
+	let solutions = solver.solve_with_rng(&mut r).unwrap();
+	assert_eq!(solutions.len(), 1);
+	assert_eq!(solutions[0].energy, Some(ground));
+	let mut sol = solutions[0].clone();
+	sol.energy = None;
+	assert_eq!(sol.calculate_energy(&model), ground);
+
+	let mut gen = ExactSolverGenerator::new();
+	gen.max_size = n - 1;
+	assert!(matches!(
+		gen.generate(&model),
+		Err(ExactSolverError::TooLarge {
+			size: 10,
+			max_size: 9
+		})
+	));
+}
@@ -0,0 +1,50 @@
+extern crate annealers;
+extern crate classical_solver;
+extern crate rand;
+
+use annealers::model::FixedSingleQuadricModel;
+use annealers::node::Binary;
+use annealers::prelude::*;
+use annealers::repr::BinaryRepr;
+use annealers::solution::SingleSolution;
+use classical_solver::beta::BetaType;
+use classical_solver::multi_start::MultiStart;
+use classical_solver::sa::SimulatedAnnealerGenerator;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+#[test]
+fn multi_start_test() {
+	let n = 12;
+	let mut r = StdRng::seed_from_u64(1);
+	let mut model = FixedSingleQuadricModel::new(Binary::new(), n);
+	for i in 0..n {
+		for j in i..n {
+			model.add_weight(i, j, r.gen_range(-10, 11) as f64);
+		}
+	}
+	let ground = (0..1usize << n)
+		.map(|s| {
+			let v = (0..n).map(|i| s >> i & 1 == 1).collect::<Vec<_>>();
+			SingleSolution::from_state(BinaryRepr::from_vec(&v)).calculate_energy(&model)
+		})
+		.fold(f64::MAX, f64::min);
+
+	let mut gen = SimulatedAnnealerGenerator::new();
+	gen.sweeps_per_round = 1;
+	gen.beta = BetaType::Count(3);
+	let single = gen.generate(&model).unwrap();
+	let failures = (0..20)
+		.filter(|_| {
+			let sol = single.solve_with_rng(&mut r).unwrap().remove(0);
+			sol.calculate_energy(&model) > ground
+		})
+		.count();
+	assert!(failures > 0);
+
+	let gen = MultiStart::new(gen, 100);
+	let solver = gen.generate(&model).unwrap();
+	let solutions = solver.solve_with_rng(&mut r).unwrap();
+	assert_eq!(solutions.len(), 1);
+	assert_eq!(solutions[0].energy, Some(ground));
+}
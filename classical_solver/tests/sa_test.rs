@@ -2,23 +2,412 @@ extern crate annealers;
 extern crate classical_solver;
 extern crate rand;
 
-use annealers::model::FixedSingleQuadricModel;
-use annealers::node::Binary;
+use annealers::model::{
+	CsrQuadricModel, FixedSingleLinearModel, FixedSingleQuadricModel, SparseIterableModel,
+	SparseQuadricModel,
+};
+use annealers::node::{Binary, TwoVal};
+use annealers::order::Quadric;
 use annealers::prelude::*;
+use annealers::repr::BinaryRepr;
+use annealers::solution::SingleSolution;
+use classical_solver::algo::AcceptanceRule;
+use classical_solver::beta::BetaType;
 use classical_solver::sa::SimulatedAnnealerGenerator;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex};
+
+fn sa_test_model() -> FixedSingleQuadricModel<Binary<f64>> {
+	let mut model = FixedSingleQuadricModel::new(Binary::new(), 3);
+	model.add_weight(0, 1, 3.0f64);
+	model.add_weight(0, 2, 3.0);
+	model.add_weight(0, 0, -3.0);
+	model
+}
 
 #[test]
 fn sa_test() {
+	use rand::rngs::StdRng;
+	use rand::SeedableRng;
+	let model = sa_test_model();
+	let mut gen = SimulatedAnnealerGenerator::new();
+	gen.sweeps_per_round = 1;
+
+	let solver = gen.generate(&model).unwrap();
+	// The default schedule ends warm enough to leave the ground state with the
+	// probability of about 1%.
+	let solutions = solver
+		.solve_with_rng(&mut StdRng::seed_from_u64(0))
+		.unwrap();
+	for sol in solutions.iter() {
+		assert_eq!(sol.state.to_vec(), vec![true, false, false]);
+	}
+}
+
+#[test]
+fn warm_start_test() {
 	let mut model = FixedSingleQuadricModel::new(Binary::new(), 3);
 	model.add_weight(0, 1, 3.0f64);
 	model.add_weight(0, 2, 3.0);
 	model.add_weight(0, 0, -3.0);
 	let mut gen = SimulatedAnnealerGenerator::new();
 	gen.sweeps_per_round = 1;
+	gen.beta = BetaType::Schedule(vec![1e10]);
+	gen.initial_state = Some(BinaryRepr::from_vec(&[true, false, false]));
 
 	let solver = gen.generate(&model).unwrap();
 	let solutions = solver.solve_with_rng(&mut rand::thread_rng()).unwrap();
 	for sol in solutions.iter() {
 		assert_eq!(sol.state.to_vec(), vec![true, false, false]);
 	}
+
+	gen.initial_state = Some(BinaryRepr::from_vec(&[true, false]));
+	assert!(gen.generate(&model).is_err());
+}
+
+#[test]
+fn sparse_test() {
+	// A chain whose ground state is all true, sparse enough to use the
+	// adjacency list in the annealer.
+	let size = 40;
+	let mut sparse = SparseQuadricModel::new(Binary::new(), size);
+	let mut dense = FixedSingleQuadricModel::new(Binary::new(), size);
+	for i in 0..size {
+		sparse.add_weight(i, i, 0.5f64);
+		dense.add_weight(i, i, 0.5);
+		if i + 1 < size {
+			sparse.add_weight(i, i + 1, -1.0);
+			dense.add_weight(i, i + 1, -1.0);
+		}
+	}
+	// The default schedule ends where the ends of the chain are still flipped
+	// with the probability of about 1%, so it ends colder.
+	let beta = BetaType::Geometric(100, 0.1, 50.0);
+	let mut sparse_gen = SimulatedAnnealerGenerator::new();
+	sparse_gen.beta = beta.clone();
+	let mut dense_gen = SimulatedAnnealerGenerator::new();
+	dense_gen.beta = beta;
+	let mut r = rand::thread_rng();
+	let solutions = sparse_gen
+		.generate(&sparse)
+		.unwrap()
+		.solve_with_rng(&mut r)
+		.unwrap()
+		.into_iter()
+		.chain(
+			dense_gen
+				.generate(&dense)
+				.unwrap()
+				.solve_with_rng(&mut r)
+				.unwrap(),
+		);
+	for sol in solutions {
+		assert_eq!(sol.state.to_vec(), vec![true; size]);
+	}
+}
+
+#[test]
+fn diagnostic_callback_test() {
+	let mut model = FixedSingleQuadricModel::new(Binary::new(), 3);
+	model.add_weight(0, 1, 3.0f64);
+	model.add_weight(0, 2, 3.0);
+	model.add_weight(0, 0, -3.0);
+	let steps = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+	let recorded = steps.clone();
+	let mut gen = SimulatedAnnealerGenerator::new().with_diagnostic_callback(
+		move |step, beta, energy, acceptance_rate| {
+			recorded
+				.lock()
+				.unwrap()
+				.push((step, beta, energy, acceptance_rate));
+		},
+	);
+	gen.sweeps_per_round = 10;
+	gen.beta = BetaType::Schedule(vec![0.1, 1.0, 10.0]);
+
+	let solver = gen.generate(&model).unwrap();
+	let solutions = solver.solve_with_rng(&mut rand::thread_rng()).unwrap();
+	let steps = steps.lock().unwrap();
+	assert_eq!(
+		steps.iter().map(|s| (s.0, s.1)).collect::<Vec<_>>(),
+		vec![(0, 0.1), (1, 1.0), (2, 10.0)]
+	);
+	assert!(steps.iter().all(|s| (0.0..=1.0).contains(&s.3)));
+	assert_eq!(steps[2].2, solutions[0].calculate_energy(&model));
+}
+
+#[test]
+fn two_val_test() {
+	let node = TwoVal::new(2.0f64, -1.0);
+	let mut model = FixedSingleQuadricModel::new(node, 4);
+	let weights = [
+		(0, 0, 1.0),
+		(1, 1, -2.0),
+		(3, 3, 0.5),
+		(0, 1, 1.5),
+		(1, 2, -1.0),
+		(2, 3, 2.0),
+		(0, 3, -0.5),
+	];
+	for (i, j, w) in weights.iter() {
+		model.add_weight(*i, *j, *w);
+	}
+	let optimum = (0..1usize << 4)
+		.map(|bits| {
+			let v = (0..4).map(|i| bits >> i & 1 == 1).collect::<Vec<_>>();
+			SingleSolution::from_vec(&v).calculate_energy(&model)
+		})
+		.fold(f64::INFINITY, f64::min);
+	let mut gen = SimulatedAnnealerGenerator::new();
+	gen.sweeps_per_round = 10;
+	let solver = gen.generate(&model).unwrap();
+	let mut r = rand::thread_rng();
+	let best = (0..10)
+		.map(|_| solver.solve_with_rng(&mut r).unwrap()[0].calculate_energy(&model))
+		.fold(f64::INFINITY, f64::min);
+	assert_eq!(best, optimum);
+}
+
+/// Model which counts the weights read from the inner model.
+#[derive(Clone)]
+struct CountingModel {
+	inner: SparseQuadricModel<Binary<f64>>,
+	visited: Arc<AtomicUsize>,
+}
+
+impl SingleModelView for CountingModel {
+	type Node = Binary<f64>;
+	type NodesIter = std::ops::Range<usize>;
+	type ProdsIter = std::vec::IntoIter<[usize; 2]>;
+	type NeighborsIter = std::vec::IntoIter<[usize; 2]>;
+	type Order = Quadric;
+
+	fn order(&self) -> &Quadric {
+		self.inner.order()
+	}
+
+	fn node(&self) -> &Binary<f64> {
+		self.inner.node()
+	}
+
+	fn nodes(&self) -> Self::NodesIter {
+		self.inner.nodes()
+	}
+
+	fn size(&self) -> usize {
+		self.inner.size()
+	}
+
+	fn get_weight(&self, p: &[usize; 2]) -> f64 {
+		self.visited.fetch_add(1, AtomicOrdering::Relaxed);
+		self.inner.get_weight(p)
+	}
+
+	unsafe fn get_weight_unchecked(&self, p: &[usize; 2]) -> f64 {
+		self.visited.fetch_add(1, AtomicOrdering::Relaxed);
+		self.inner.get_weight_unchecked(p)
+	}
+
+	fn prods(&self) -> Self::ProdsIter {
+		self.inner.prods()
+	}
+
+	fn neighbors(&self, u: usize) -> Self::NeighborsIter {
+		self.inner.neighbors(u)
+	}
+}
+
+impl SparseIterableModel for CountingModel {
+	type NonzeroProdsIter = Vec<[usize; 2]>;
+
+	fn nonzero_prods(&self) -> Vec<[usize; 2]> {
+		self.inner.nonzero_prods()
+	}
+}
+
+#[test]
+fn large_sparse_chain_test() {
+	use rand::rngs::StdRng;
+	use rand::SeedableRng;
+	// The setup visits only the non-zero weights, so it is fast even for a
+	// large model.
+	let size = 10_000;
+	let mut inner = SparseQuadricModel::new(Binary::new(), size);
+	for i in 0..size {
+		inner.add_weight(i, i, -1.0f64);
+		if i + 1 < size {
+			inner.add_weight(i, i + 1, -1.0);
+		}
+	}
+	let visited = Arc::new(AtomicUsize::new(0));
+	let model = CountingModel {
+		inner,
+		visited: visited.clone(),
+	};
+	let steps = Arc::new(Mutex::new(Vec::new()));
+	let recorded = steps.clone();
+	let mut gen = SimulatedAnnealerGenerator::new().with_diagnostic_callback(move |_, _, e, _| {
+		recorded.lock().unwrap().push(e);
+	});
+	gen.sweeps_per_round = 5;
+	gen.beta = BetaType::Count(10);
+	let solutions = gen
+		.generate(&model)
+		.unwrap()
+		.solve_with_rng(&mut StdRng::seed_from_u64(0))
+		.unwrap();
+	assert_eq!(solutions[0].state.to_vec(), vec![true; size]);
+	// The weights are read a few times for each of the 2 * size - 1 non-zero
+	// ones, while the dense iteration would read about size^2 / 2 weights.
+	let visited = visited.load(AtomicOrdering::Relaxed);
+	assert!(visited >= 2 * size - 1 && visited <= 10 * size);
+	let steps = steps.lock().unwrap();
+	assert_eq!(steps.len(), 10);
+	assert_eq!(steps[9], -2.0 * size as f64 + 1.0);
+}
+
+#[test]
+fn csr_model_test() {
+	use rand::seq::SliceRandom;
+	use rand::Rng;
+	// A random 3-regular graph of a ring and a random perfect matching
+	let size = 100_000;
+	let mut r = rand::thread_rng();
+	let mut matching = (0..size).collect::<Vec<_>>();
+	matching.shuffle(&mut r);
+	let mut entries = Vec::new();
+	for i in 0..size {
+		entries.push(((i, i), r.gen_range(-2, 3) as f64));
+		entries.push(((i, (i + 1) % size), r.gen_range(-2, 3) as f64));
+	}
+	for pair in matching.chunks(2) {
+		entries.push(((pair[0], pair[1]), r.gen_range(-2, 3) as f64));
+	}
+	let model = CsrQuadricModel::from_entries(Binary::new(), size, entries);
+	let last = std::sync::Arc::new(std::sync::Mutex::new(None));
+	let recorded = last.clone();
+	let mut gen = SimulatedAnnealerGenerator::new().with_diagnostic_callback(move |_, _, e, _| {
+		*recorded.lock().unwrap() = Some(e);
+	});
+	gen.sweeps_per_round = 1;
+	gen.beta = BetaType::Schedule(vec![1.0]);
+	let solutions = gen
+		.generate(&model)
+		.unwrap()
+		.solve_with_rng(&mut r)
+		.unwrap();
+	// The energy tracked with the energy differences is the actual one
+	assert_eq!(
+		last.lock().unwrap().unwrap(),
+		solutions[0].calculate_energy(&model)
+	);
+}
+
+#[test]
+fn linear_model_test() {
+	let weights = [3.0f64, -1.0, 0.5, -2.5, 0.0, -0.25, 4.0, -3.0, 1.0, -0.5];
+	let mut model = FixedSingleLinearModel::new(Binary::new(), weights.len());
+	for (i, w) in weights.iter().enumerate() {
+		model.add_weight(i, *w);
+	}
+	let mut gen = SimulatedAnnealerGenerator::new();
+	gen.initial_state = Some(BinaryRepr::from_vec(&[false; 10]));
+	let solver = gen.generate(&model).unwrap();
+	let solutions = solver.solve_with_rng(&mut rand::thread_rng()).unwrap();
+	let expected = weights.iter().map(|w| *w < 0.0).collect::<Vec<_>>();
+	for sol in solutions.iter() {
+		assert_eq!(sol.state.to_vec(), expected);
+		assert_eq!(sol.calculate_energy(&model), -7.25);
+	}
+}
+
+#[test]
+fn acceptance_rule_test() {
+	use rand::rngs::StdRng;
+	use rand::SeedableRng;
+	use std::sync::{Arc, Mutex};
+	let model = sa_test_model();
+	for rule in [AcceptanceRule::Metropolis, AcceptanceRule::HeatBath] {
+		let mut gen = SimulatedAnnealerGenerator::new();
+		gen.acceptance = rule;
+		gen.beta = BetaType::Geometric(100, 0.1, 20.0);
+		let solver = gen.generate(&model).unwrap();
+		let solutions = solver
+			.solve_with_rng(&mut StdRng::seed_from_u64(0))
+			.unwrap();
+		for sol in solutions.iter() {
+			assert_eq!(sol.state.to_vec(), vec![true, false, false]);
+		}
+	}
+
+	// The energies in the middle of the annealing with the same seed
+	let trace = |rule| {
+		let energies = Arc::new(Mutex::new(Vec::new()));
+		let recorded = energies.clone();
+		let mut gen = SimulatedAnnealerGenerator::new()
+			.with_diagnostic_callback(move |_, _, energy, _| recorded.lock().unwrap().push(energy));
+		gen.acceptance = rule;
+		gen.sweeps_per_round = 1;
+		gen.beta = BetaType::Geometric(20, 0.1, 5.0);
+		gen.initial_state = Some(BinaryRepr::from_vec(&[false; 3]));
+		let solver = gen.generate(&model).unwrap();
+		solver
+			.solve_with_rng(&mut StdRng::seed_from_u64(0))
+			.unwrap();
+		let energies = energies.lock().unwrap().clone();
+		energies
+	};
+	assert_eq!(
+		trace(AcceptanceRule::Metropolis),
+		trace(AcceptanceRule::Metropolis)
+	);
+	assert_ne!(
+		trace(AcceptanceRule::Metropolis),
+		trace(AcceptanceRule::HeatBath)
+	);
+}
+
+#[test]
+fn disjoint_union_test() {
+	use rand::rngs::StdRng;
+	use rand::SeedableRng;
+	let mut a = FixedSingleQuadricModel::new(Binary::new(), 3);
+	a.add_weight(0, 1, 3.0f64);
+	a.add_weight(0, 2, 3.0);
+	a.add_weight(0, 0, -3.0);
+	let mut b = FixedSingleQuadricModel::new(Binary::new(), 2);
+	b.add_weight(0, 1, -3.0);
+	b.add_weight(0, 0, 1.0);
+	b.add_weight(1, 1, 1.0);
+	let union = a.disjoint_union(b);
+	let solve = |model: &FixedSingleQuadricModel<Binary<f64>>| {
+		let mut gen = SimulatedAnnealerGenerator::new();
+		gen.beta = BetaType::Geometric(100, 0.1, 20.0);
+		let solver = gen.generate(model).unwrap();
+		let solutions = solver
+			.solve_with_rng(&mut StdRng::seed_from_u64(0))
+			.unwrap();
+		let best = SingleSolution::best_of(
+			solutions
+				.into_iter()
+				.map(|sol| sol.with_energy(model))
+				.collect(),
+		)
+		.unwrap();
+		(best.energy.unwrap(), best.state.to_vec())
+	};
+	let components = union.connected_components();
+	assert_eq!(components, vec![vec![0, 1, 2], vec![3, 4]]);
+	let mut energy = 0.0;
+	let mut state = vec![false; 5];
+	for nodes in components.iter() {
+		let (sub, mapping) = union.induced_submodel(nodes);
+		let (e, v) = solve(&sub);
+		energy += e;
+		for (k, i) in mapping.into_iter().enumerate() {
+			state[i] = v[k];
+		}
+	}
+	assert_eq!(energy, -4.0);
+	assert_eq!(solve(&union), (energy, state));
 }
@@ -1,71 +1,6 @@
+use annealers::repr::BinaryRepr;
 use rand::Rng;
 
-pub struct QubitState {
-	state: Vec<u8>,
-	len: usize,
-}
-
-static BITVALUES: [u8; 8] = [1, 2, 4, 8, 16, 32, 64, 128];
-
-impl QubitState {
-	#[inline]
-	pub fn new_random<T: Rng>(len: usize, r: &mut T) -> Self {
-		let bytesize = std::mem::size_of::<u8>();
-		let size = (len + bytesize - 1) / bytesize;
-		let mut v = Vec::with_capacity(size);
-		unsafe {
-			v.set_len(size);
-		}
-		r.fill_bytes(&mut v);
-		Self { state: v, len }
-	}
-
-	#[inline]
-	pub fn len(&self) -> usize {
-		self.len
-	}
-
-	#[allow(unused)]
-	#[inline]
-	pub fn get(&self, loc: usize) -> bool {
-		let bytesize = std::mem::size_of::<u8>();
-		assert!(loc < self.len);
-		(self.state[loc / bytesize] & BITVALUES[loc % bytesize]) > 0
-	}
-
-	#[inline]
-	pub unsafe fn get_unchecked(&self, loc: usize) -> bool {
-		let bytesize = std::mem::size_of::<u8>();
-		(self.state.get_unchecked(loc / bytesize) & BITVALUES.get_unchecked(loc % bytesize)) > 0
-	}
-
-	#[allow(unused)]
-	#[inline]
-	pub fn flip(&mut self, loc: usize) {
-		let bytesize = std::mem::size_of::<u8>();
-		self.state[loc / bytesize] ^= BITVALUES[loc % bytesize];
-	}
-
-	#[inline]
-	pub unsafe fn flip_unchecked(&mut self, loc: usize) {
-		let bytesize = std::mem::size_of::<u8>();
-		*self.state.get_unchecked_mut(loc / bytesize) ^= BITVALUES.get_unchecked(loc % bytesize);
-	}
-}
-
-impl std::fmt::Debug for QubitState {
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		for i in 0..self.len {
-			if self.get(i) {
-				f.write_str("1")?;
-			} else {
-				f.write_str("0")?;
-			}
-		}
-		Ok(())
-	}
-}
-
 #[derive(Debug)]
 pub struct NullError;
 impl std::fmt::Display for NullError {
@@ -87,7 +22,7 @@ pub trait AnnealerInfo: std::marker::Send + std::marker::Sync {
 }
 
 pub trait Annealer<TErr>: std::marker::Send + std::marker::Sync {
-	fn anneal<T: Rng>(&self, r: &mut T) -> Result<QubitState, TErr>;
+	fn anneal<T: Rng>(&self, r: &mut T) -> Result<BinaryRepr, TErr>;
 }
 
 #[derive(Clone)]
@@ -103,7 +38,7 @@ impl BetaType {
 			.iter()
 			.chain(neighbors.iter().flat_map(|sl| sl.iter().map(|(_, f)| f)))
 			.map(|f| f64::abs(*f))
-			.fold(0.0 / 0.0 as f64, |p: f64, n: f64| n.max(p));
+			.fold(f64::NEG_INFINITY, |p: f64, n: f64| n.max(p));
 		let eg_max = h
 			.iter()
 			.enumerate()
@@ -113,8 +48,9 @@ impl BetaType {
 					.map(|(_, f)| f64::abs(*f) as f64)
 					.sum::<f64>() as f64
 			})
-			.fold(0.0 / 0.0 as f64, |p: f64, n: f64| n.max(p));
-		if eg_max.is_finite() && eg_min.is_finite() {
+			.fold(f64::NEG_INFINITY, |p: f64, n: f64| n.max(p));
+		// `eg_min` is zero if all the weights are zero.
+		if eg_max.is_finite() && eg_min.is_finite() && eg_min != 0.0 {
 			(f64::ln(2.0) / eg_max, f64::ln(100.0) / eg_min)
 		} else {
 			(0.1, 1.0)
@@ -187,7 +123,7 @@ impl AnnealerInfo for InternalAnnealerInfo {
 impl InternalAnnealer {
 	fn run<T: Rng>(
 		&self,
-		state: &mut QubitState,
+		state: &mut BinaryRepr,
 		random: &mut T,
 		h: &[f64],
 		neighbors: &[Vec<(usize, f64)>],
@@ -237,8 +173,8 @@ impl InternalAnnealer {
 }
 
 impl Annealer<NullError> for InternalAnnealer {
-	fn anneal<T: Rng>(&self, r: &mut T) -> Result<QubitState, NullError> {
-		let mut state = QubitState::new_random(self.h.len(), r);
+	fn anneal<T: Rng>(&self, r: &mut T) -> Result<BinaryRepr, NullError> {
+		let mut state = BinaryRepr::new_random(self.h.len(), r);
 		self.run(&mut state, r, &self.h, &self.neighbors);
 		Ok(state)
 	}
@@ -299,7 +235,7 @@ mod external_apis {
 		}
 
 		impl Annealer<NullError> for InternalAnnealer {
-			fn anneal<T: Rng>(&self, _r: &mut T) -> Result<QubitState, NullError> {
+			fn anneal<T: Rng>(&self, _r: &mut T) -> Result<BinaryRepr, NullError> {
 				unimplemented!();
 			}
 		}
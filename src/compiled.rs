@@ -1,12 +1,29 @@
+use crate::embedding::{Embedding, EmbeddingError};
 use crate::expanded::Expanded;
 use crate::expr::StaticExpr;
 use crate::model::Constraint;
 use crate::wrapper::{Builder, Placeholder, Qubit};
 use crate::{TcType, TpType, TqType};
-use annealers::model::FixedSingleQuadricModel;
+use annealers::model::{FixedSingleQuadricModel, HighOrderModel, SingleModelView};
 use annealers::node::Binary;
+use annealers::solution::SingleSolution;
 use annealers::variable::Real;
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::Write;
+
+/// Strategy of the order reduction, used in
+/// [`Expr::compile_with_strategy()`](crate::Expr::compile_with_strategy).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ReductionStrategy {
+	/// Replace high-order terms with the formulas of Ishikawa, which introduce
+	/// up to `(d - 1) / 2` ancillas per term of degree `d`.
+	#[default]
+	Ishikawa,
+	/// Repeatedly replace the most frequent product `xy` in high-order terms
+	/// with a new ancilla `w`, adding the penalty of `w == xy`. This
+	/// typically uses fewer ancillas for sparse high-order terms.
+	SubstitutionByPair,
+}
 
 #[derive(Clone, Debug)]
 pub struct CompiledModel<Tp, Tq, Tc, R>
@@ -19,6 +36,12 @@ where
 	expanded: Expanded<Tp, Tq, Tc, R>,
 	constraints: Vec<Constraint<Tp, Tq, Tc, R>>,
 	builder: Builder<Tq>,
+	/// The objective before the order reduction. Penalty terms of constraints
+	/// are multiplied by constraint placeholders.
+	objective: Expanded<Tp, Tq, Tc, R>,
+	/// The qubits fixed by [`Self::propagate_units`], which are no longer in
+	/// the model.
+	fixed: HashMap<Tq, bool>,
 }
 
 impl<Tp, Tq, Tc, R> CompiledModel<Tp, Tq, Tc, R>
@@ -31,12 +54,15 @@ where
 	pub(crate) fn new(
 		expanded: Expanded<Tp, Tq, Tc, R>,
 		constraints: Vec<Constraint<Tp, Tq, Tc, R>>,
+		objective: Expanded<Tp, Tq, Tc, R>,
 	) -> Self {
 		let builder = Builder::new();
 		Self {
 			expanded,
 			constraints,
 			builder,
+			objective,
+			fixed: HashMap::new(),
 		}
 	}
 
@@ -47,6 +73,7 @@ where
 			.map(|(k, v)| (Placeholder::Placeholder(k), v))
 			.collect();
 		let expanded = self.expanded.feed_dict(&dict).drop_placeholder();
+		let objective = self.objective.feed_dict(&dict).drop_placeholder();
 		let constraints = self
 			.constraints
 			.into_iter()
@@ -56,6 +83,8 @@ where
 			expanded,
 			constraints,
 			builder: self.builder,
+			objective,
+			fixed: self.fixed,
 		}
 	}
 
@@ -150,22 +179,7 @@ where
 			// x * y -> min{1 + w * (3 - 2x - 2y)}, xyz = a * w
 			if let &[x, y] = &set.iter().take(2).collect::<Vec<&Qubit<Tq>>>() as &[&Qubit<Tq>] {
 				let w = builder.ancilla();
-				exp.insert(
-					Some(w.clone()).into_iter().collect(),
-					StaticExpr::Number(R::from_i32(3)),
-				);
-				exp.insert(
-					vec![x, &w].into_iter().cloned().collect(),
-					StaticExpr::Number(R::from_i32(-2)),
-				);
-				exp.insert(
-					(vec![y, &w]).into_iter().cloned().collect(),
-					StaticExpr::Number(R::from_i32(-2)),
-				);
-				exp.insert(
-					(vec![x, y]).into_iter().cloned().collect(),
-					StaticExpr::Number(R::from_i32(1)),
-				);
+				let exp = Self::pair_penalty(x, y, &w);
 				(Expanded::from_qubit(w), Some(exp))
 			} else {
 				panic!();
@@ -173,16 +187,129 @@ where
 		}
 	}
 
+	/// Penalty `3w + xy - 2xw - 2yw`, which is 0 if `w == xy` and positive
+	/// otherwise.
+	fn pair_penalty(x: &Qubit<Tq>, y: &Qubit<Tq>, w: &Qubit<Tq>) -> Expanded<Tp, Tq, Tc, R> {
+		let mut exp = Expanded::new();
+		exp.insert(
+			Some(w.clone()).into_iter().collect(),
+			StaticExpr::Number(R::from_i32(3)),
+		);
+		exp.insert(
+			vec![x, w].into_iter().cloned().collect(),
+			StaticExpr::Number(R::from_i32(-2)),
+		);
+		exp.insert(
+			(vec![y, w]).into_iter().cloned().collect(),
+			StaticExpr::Number(R::from_i32(-2)),
+		);
+		exp.insert(
+			(vec![x, y]).into_iter().cloned().collect(),
+			StaticExpr::Number(R::from_i32(1)),
+		);
+		exp
+	}
+
 	pub(crate) fn get_unsatisfied_constraints(
 		&self,
 		map: &HashMap<&Qubit<Tq>, bool>,
+		tol: f64,
 	) -> Vec<&Constraint<Tp, Tq, Tc, R>> {
 		self.constraints
 			.iter()
-			.filter(|cc| !cc.is_satisfied(map))
+			.filter(|cc| !cc.is_satisfied_within(map, tol))
 			.collect()
 	}
 
+	/// Get the violation of each labeled constraint with the given assignment.
+	/// Constraints whose violation cannot be calculated are omitted.
+	pub(crate) fn constraint_violations(&self, map: &HashMap<&Qubit<Tq>, bool>) -> Vec<(&Tc, R)> {
+		self.constraints
+			.iter()
+			.filter_map(|cc| Some((cc.label.as_ref()?, cc.violation(map)?)))
+			.collect()
+	}
+
+	/// Get the first constraint labeled with `label`.
+	pub fn get_constraint(&self, label: &Tc) -> Option<&Constraint<Tp, Tq, Tc, R>> {
+		self.constraints
+			.iter()
+			.find(|cc| cc.label.as_ref() == Some(label))
+	}
+
+	/// Get all the constraints labeled with `label`.
+	pub fn get_constraints(&self, label: &Tc) -> Vec<&Constraint<Tp, Tq, Tc, R>> {
+		self.constraints
+			.iter()
+			.filter(|cc| cc.label.as_ref() == Some(label))
+			.collect()
+	}
+
+	/// Calculate the energy of the objective with the given assignment, without
+	/// penalties of the constraints and ancilla qubits.
+	pub(crate) fn objective_energy(&self, map: &HashMap<&Qubit<Tq>, bool>) -> R {
+		self.objective.calculate(map, &mut |p| match p {
+			Placeholder::Constraint(_) | Placeholder::Soft(_) => R::zero(),
+			Placeholder::Placeholder(_) => panic!("placeholder remains"),
+		})
+	}
+
+	/// Calculate the energy of the model with the given assignment, where the
+	/// strength of each constraint is `strength(label)` and soft constraints
+	/// have their fixed penalties. Ancilla qubits are treated as in
+	/// [`Self::energy_of`].
+	pub(crate) fn penalized_energy<F>(&self, map: &HashMap<&Qubit<Tq>, bool>, strength: &mut F) -> R
+	where
+		F: FnMut(&Tc) -> R,
+	{
+		self.objective.calculate(map, &mut |p| match p {
+			Placeholder::Constraint(label) => strength(label),
+			Placeholder::Soft(_) => R::one(),
+			Placeholder::Placeholder(_) => panic!("placeholder remains"),
+		})
+	}
+
+	/// Calculate the penalty energy of each labeled constraint with the given
+	/// assignment, where the strength of the constraint is `strength(label)`.
+	/// Soft constraints are included with their fixed penalties. The sum of
+	/// them and [`Self::objective_energy`] is the energy of the model.
+	pub(crate) fn constraint_energies<F>(
+		&self,
+		map: &HashMap<&Qubit<Tq>, bool>,
+		strength: &mut F,
+	) -> HashMap<Tc, R>
+	where
+		F: FnMut(&Tc) -> R,
+	{
+		let objective = self.objective_energy(map);
+		self.constraints
+			.iter()
+			.filter_map(|cc| match (&cc.label, &cc.placeholder) {
+				(Some(label), Some(ph @ (Placeholder::Constraint(_) | Placeholder::Soft(_)))) => {
+					let s = match ph {
+						Placeholder::Soft(_) => R::one(),
+						_ => strength(label),
+					};
+					let energy = self.objective.calculate(map, &mut |p| match p {
+						Placeholder::Placeholder(_) => panic!("placeholder remains"),
+						_ if p == ph => s,
+						_ => R::zero(),
+					});
+					Some((label.clone(), energy - objective))
+				}
+				_ => None,
+			})
+			.collect()
+	}
+
+	/// Reduce the order of the model to 3 instead of 2, which saves the
+	/// ancilla qubits. The result is a PUBO with 3-body interactions, so it
+	/// cannot be solved by [`crate::solve::SimpleSolver`] but requires a solver
+	/// supporting them. See [`CompiledModel::to_pubo`].
+	pub fn reduce_to_pubo(self) -> Self {
+		self.reduce_order(3)
+	}
+
 	pub(crate) fn reduce_order(mut self, max_order: usize) -> Self {
 		let mut builder = self.builder.clone();
 		while self.expanded.get_order() > max_order {
@@ -226,14 +353,298 @@ where
 		self
 	}
 
+	pub(crate) fn reduce_order_with_strategy(
+		self,
+		max_order: usize,
+		strategy: ReductionStrategy,
+	) -> Self {
+		match strategy {
+			ReductionStrategy::Ishikawa => self.reduce_order(max_order),
+			ReductionStrategy::SubstitutionByPair => self.reduce_order_by_pair(max_order),
+		}
+	}
+
+	fn reduce_order_by_pair(mut self, max_order: usize) -> Self {
+		let mut builder = self.builder.clone();
+		while self.expanded.get_order() > max_order {
+			let pair: BTreeSet<Qubit<Tq>> = if let Some(((pair, _), _)) = self
+				.expanded
+				.count_qubit_subsets(max_order, 2, Some(2))
+				.into_iter()
+				.max_by(|(k1, v1), (k2, v2)| v1.cmp(v2).then_with(|| k2.cmp(k1)))
+			{
+				pair.into_iter().cloned().collect()
+			} else {
+				break;
+			};
+			let w = builder.ancilla();
+			// The penalty must be stronger than the sum of absolute values of
+			// the coefficients replaced with w.
+			let mut strength = vec![StaticExpr::Number(R::from_i32(1))];
+			let mut new_expanded = Expanded::new();
+			for (mut set, exp) in self.expanded.drain() {
+				if set.len() > max_order && set.is_superset(&pair) {
+					for term in exp.clone().expand_add() {
+						if let Some(false) = term.is_positive() {
+							strength.push(StaticExpr::Mul(vec![
+								StaticExpr::Number(R::from_i32(-1)),
+								term,
+							]));
+						} else {
+							strength.push(term);
+						}
+					}
+					for q in pair.iter() {
+						set.remove(q);
+					}
+					set.insert(w.clone());
+				}
+				new_expanded += Expanded::from(set, exp);
+			}
+			let mut it = pair.iter();
+			let penalty = Self::pair_penalty(it.next().unwrap(), it.next().unwrap(), &w);
+			new_expanded += penalty.clone() * StaticExpr::Add(strength).simplify();
+			self.expanded = new_expanded;
+			self.constraints
+				.push(Constraint::from_raw(None, penalty.into(), None));
+		}
+		self.builder = builder;
+		self
+	}
+
 	pub(crate) fn get_qubits(&self) -> BTreeSet<&Qubit<Tq>> {
 		self.expanded.get_qubits()
 	}
 
+	/// Get all qubits, including ancillas, with their indices in the solution
+	/// vector. The order is the same as the one used by `SimpleSolver`: user
+	/// qubits come first and ancilla qubits follow them.
+	pub fn qubits_indexed(&self) -> Vec<(usize, &Qubit<Tq>)> {
+		self.get_qubits().into_iter().enumerate().collect()
+	}
+
+	/// Split the qubits, including ancillas, into the groups which do not
+	/// interact with each other, i.e. two qubits are in the same group if they
+	/// appear in the same monomial. Each group is sorted, and the groups are
+	/// sorted by their first qubits.
+	pub fn connected_components(&self) -> Vec<Vec<Qubit<Tq>>> {
+		let qubits = self.expanded.get_qubits().into_iter().collect::<Vec<_>>();
+		let index = qubits
+			.iter()
+			.enumerate()
+			.map(|(i, q)| (*q, i))
+			.collect::<HashMap<_, _>>();
+		// Each monomial connects its first qubit to the others
+		let edges = self.expanded.keys().flat_map(|set| {
+			let mut it = set.iter().map(|q| index[q]);
+			let first = it.next();
+			it.filter_map(move |i| first.map(|first| (first, i)))
+		});
+		annealers::set::connected_components(qubits.len(), edges)
+			.into_iter()
+			.map(|component| component.into_iter().map(|i| qubits[i].clone()).collect())
+			.collect()
+	}
+
 	pub fn get_placeholders(&self) -> BTreeSet<&Placeholder<Tp, Tc>> {
 		self.expanded.get_placeholders()
 	}
 
+	/// The ratio of the monomials of the model, including the ones of the
+	/// ancillas, to the `n * (n + 1) / 2` monomials of a dense QUBO of the `n`
+	/// qubits. The constant term is not counted.
+	pub fn density(&self) -> f64 {
+		self.expanded.density()
+	}
+
+	/// `1 - density()`.
+	pub fn sparsity(&self) -> f64 {
+		self.expanded.sparsity()
+	}
+
+	/// The qubit appearing in the most monomials and the number of them, which
+	/// may be a bottleneck of the model. Returns `None` if there is no qubit.
+	pub fn max_degree_node(&self) -> Option<(&Qubit<Tq>, usize)> {
+		self.expanded.max_degree_node()
+	}
+
+	/// The maximum absolute linear and quadratic weights, where the
+	/// placeholders are evaluated as `1`.
+	fn max_abs_weights(&self) -> (R, R) {
+		let (mut max_h, mut max_j) = (R::zero(), R::zero());
+		for (set, exp) in self.expanded.iter() {
+			let w = exp.calculate(&mut |_| R::one()).abs();
+			match set.len() {
+				1 => max_h = max_h.max(w),
+				2 => max_j = max_j.max(w),
+				_ => (),
+			}
+		}
+		(max_h, max_j)
+	}
+
+	/// Scale all the weights uniformly so that the maximum absolute weight,
+	/// where the placeholders are evaluated as `1`, is `1`. The weights are
+	/// measured as in [`CompiledModel::rescale_for_hardware`], so this is the
+	/// same as the one with the unit ranges. Penalties of the constraints are
+	/// multiplied by their placeholders, so they are scaled together with the
+	/// objective for any strength, and the constraints themselves are kept to
+	/// be checked with the same tolerance.
+	///
+	/// Returns the scaled model and the factor, by which the energies of the
+	/// model are multiplied. The factor is `1` if all the weights are zero.
+	///
+	/// # Panics
+	///
+	/// Panics if `R` is an integer type, whose factor would be truncated.
+	pub fn normalize(&self) -> (Self, R) {
+		assert!(
+			R::from_f64(0.5) != R::zero(),
+			"normalize: R must be a floating-point type"
+		);
+		let (max_h, max_j) = self.max_abs_weights();
+		let max = max_h.max(max_j);
+		let mut ret = self.clone();
+		if max == R::zero() {
+			return (ret, R::one());
+		}
+		let scale = R::one() / max;
+		ret.expanded *= StaticExpr::Number(scale);
+		ret.objective *= StaticExpr::Number(scale);
+		(ret, scale)
+	}
+
+	/// Fix the qubits which appear only in a single linear term, and are not
+	/// coupled to any other qubit, to the value minimizing the term: `false`
+	/// if its coefficient is positive, and `true` if negative. Constraint
+	/// placeholders are assumed positive. The fixed qubits are removed from the
+	/// model and returned, and the solutions of
+	/// [`SimpleSolver`](crate::solve::SimpleSolver) still report them.
+	pub fn propagate_units(mut self) -> (Self, HashMap<Tq, bool>) {
+		let mut count = HashMap::<&Qubit<Tq>, usize>::new();
+		for set in self.expanded.keys() {
+			for q in set.iter() {
+				*count.entry(q).or_insert(0) += 1;
+			}
+		}
+		let units = self
+			.expanded
+			.iter()
+			.filter_map(|(set, exp)| match (set.len(), set.iter().next()) {
+				(1, Some(q @ Qubit::Qubit(label))) if count[q] == 1 => {
+					let value = match exp {
+						StaticExpr::Number(n) if *n == R::zero() => Some(false),
+						_ => exp.is_positive().map(|positive| !positive),
+					};
+					value.map(|v| (q.clone(), label.clone(), v))
+				}
+				_ => None,
+			})
+			.collect::<Vec<_>>();
+		let mut fixed = HashMap::new();
+		for (q, label, value) in units {
+			self.expanded = self.expanded.fix_qubit(&q, value);
+			self.objective = self.objective.fix_qubit(&q, value);
+			self.constraints = self
+				.constraints
+				.into_iter()
+				.map(|cc| cc.fix_qubit(&q, value))
+				.collect();
+			fixed.insert(label, value);
+		}
+		self.fixed.extend(fixed.clone());
+		(self, fixed)
+	}
+
+	/// The qubits fixed by [`Self::propagate_units`].
+	pub(crate) fn fixed(&self) -> &HashMap<Tq, bool> {
+		&self.fixed
+	}
+
+	/// Embed the model into the physical qubits of a hardware graph with
+	/// `embedding`, which maps each qubit to a chain of the physical qubits,
+	/// e.g. the one found by an external minor-embedding tool. The chains are
+	/// bound by [`Embedding::chain_couplers`] with the penalty
+	/// `chain_strength * (a + b - 2ab)`, which is zero if the qubits agree, and
+	/// the terms are placed as in [`Embedding::embed_model`]. The chains of the labels which
+	/// are not in the model are ignored.
+	///
+	/// Ancilla qubits have no label to be given a chain, so the model must be
+	/// quadratic without the order reduction.
+	pub fn embed_into(
+		self,
+		embedding: HashMap<&Tq, Vec<usize>>,
+		couplers: &HashSet<BTreeSet<usize>>,
+		chain_strength: R,
+	) -> Result<EmbeddedModel<Tp, Tq, Tc, R>, EmbeddingError> {
+		let mut labels = Vec::new();
+		let mut chains = Vec::new();
+		for q in self.get_qubits() {
+			let label = match q {
+				Qubit::Qubit(label) => label,
+				_ => {
+					return Err(EmbeddingError::InvalidEmbedding(
+						"ancilla qubits cannot be embedded",
+					))
+				}
+			};
+			let chain = embedding
+				.get(label)
+				.filter(|chain| !chain.is_empty())
+				.ok_or(EmbeddingError::InvalidEmbedding("some qubit has no chain"))?;
+			labels.push(label.clone());
+			chains.push(chain.clone());
+		}
+		let mut used = HashSet::new();
+		if !chains.iter().flatten().all(|n| used.insert(*n)) {
+			return Err(EmbeddingError::InvalidEmbedding("chains overlap"));
+		}
+		let embedding = Embedding { chains };
+		let (mut expanded, mut objective) = {
+			let index = self
+				.qubits_indexed()
+				.into_iter()
+				.map(|(i, q)| (q, i))
+				.collect::<HashMap<_, _>>();
+			match (
+				self.expanded.embed(&index, &embedding, couplers),
+				self.objective.embed(&index, &embedding, couplers),
+			) {
+				(Some(expanded), Some(objective)) => (expanded, objective),
+				_ => {
+					return Err(EmbeddingError::InvalidEmbedding(
+						"some coupling has no coupler",
+					))
+				}
+			}
+		};
+		let chain_couplers =
+			embedding
+				.chain_couplers(couplers)
+				.ok_or(EmbeddingError::InvalidEmbedding(
+					"some chain is not connected by the couplers",
+				))?;
+		for (u, v) in chain_couplers {
+			let (a, b) = (Qubit::Qubit(u), Qubit::Qubit(v));
+			// (a - b)^2 = a + b - 2ab
+			let mut penalty = Expanded::from_qubit(a.clone()) + Expanded::from_qubit(b.clone());
+			penalty.insert(
+				vec![a, b].into_iter().collect(),
+				StaticExpr::Number(R::from_i32(-2)),
+			);
+			let penalty = penalty * StaticExpr::Number(chain_strength);
+			expanded += penalty.clone();
+			objective += penalty;
+		}
+		let physical = CompiledModel::new(expanded, Vec::new(), objective);
+		Ok(EmbeddedModel {
+			logical: self,
+			physical,
+			labels,
+			embedding,
+		})
+	}
+
 	// TODO: support HashMap-based model
 	pub(crate) fn generate_qubo<F>(
 		&self,
@@ -246,3 +657,671 @@ where
 		self.expanded.generate_qubo(qubits, ph_feedback)
 	}
 }
+
+impl<Tq, Tc, R> CompiledModel<(), Tq, Tc, R>
+where
+	Tq: TqType,
+	Tc: TcType,
+	R: Real,
+{
+	/// Write the QUBO in a plain text format accepted by external QUBO / MaxSAT
+	/// tools.
+	///
+	/// The output starts with a `p qubo <n> <nterms>` header followed by
+	/// `i j weight` lines (`i <= j`) for each non-zero weight. User qubits are
+	/// numbered first and ancilla qubits follow them. Constraint placeholders
+	/// are evaluated with unit strength, and the constant offset is emitted as
+	/// a `c offset <value>` comment line if it is not zero.
+	pub fn write_qubo_text<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+		// `Qubit::Qubit(_)` is ordered before `Qubit::Ancilla(_)`
+		let qubits = self.get_qubits().into_iter().collect::<Vec<_>>();
+		let (c, model) = self.generate_qubo(&qubits, &mut |_| R::one());
+		let terms = model
+			.prods()
+			.filter(|p| model.get_weight(p) != R::zero())
+			.collect::<Vec<_>>();
+		writeln!(w, "p qubo {} {}", qubits.len(), terms.len())?;
+		if c != R::zero() {
+			writeln!(w, "c offset {}", c)?;
+		}
+		for p in terms.iter() {
+			writeln!(w, "{} {} {}", p[0], p[1], model.get_weight(p))?;
+		}
+		Ok(())
+	}
+
+	/// Scale all the weights uniformly so that the linear weights fit in
+	/// `[-h_max, h_max]` and the quadratic ones fit in `[-j_max, j_max]`, as
+	/// required by the `h_range` and `j_range` of hardware annealers. The
+	/// factor is `min(h_max / max_h, j_max / max_j)`, where `max_h` and `max_j`
+	/// are the maximum absolute linear and quadratic weights. Constraint
+	/// placeholders are evaluated with unit strength as in
+	/// [`Self::write_qubo_text`].
+	///
+	/// Returns the scaled model and the factor, by which the energies of the
+	/// scaled model are divided to get the original ones. The factor is `1` if
+	/// all the weights are zero.
+	///
+	/// # Panics
+	///
+	/// Panics if `R` is an integer type, whose factor would be truncated.
+	pub fn rescale_for_hardware(mut self, h_max: R, j_max: R) -> (Self, R) {
+		assert!(
+			R::from_f64(0.5) != R::zero(),
+			"rescale_for_hardware: R must be a floating-point type"
+		);
+		let (max_h, max_j) = self.max_abs_weights();
+		let scale = [(h_max, max_h), (j_max, max_j)]
+			.iter()
+			.filter(|(_, m)| *m != R::zero())
+			.map(|(target, m)| *target / *m)
+			.reduce(R::min)
+			.unwrap_or_else(R::one);
+		let factor = StaticExpr::Number(scale);
+		self.expanded *= factor.clone();
+		self.objective *= factor;
+		(self, scale)
+	}
+
+	/// Calculate the energy of the model with the given assignment of the
+	/// labels, where the constraint placeholders are evaluated with unit
+	/// strength as in [`Self::write_qubo_text`]. Returns `None` if some qubit
+	/// of the model is not assigned.
+	///
+	/// Ancilla qubits cannot be assigned. They are assumed to take the values
+	/// which minimize the energy, for which the order reduction does not
+	/// change the energy, so the energy is calculated with the model before
+	/// the reduction. It equals the energy of the QUBO if the ancillas of the
+	/// solution are optimal.
+	pub fn energy_of(&self, assignment: &HashMap<Tq, bool>) -> Option<R> {
+		let qubits = assignment
+			.iter()
+			.map(|(k, v)| (Qubit::Qubit(k.clone()), *v))
+			.collect::<Vec<_>>();
+		let map = qubits
+			.iter()
+			.map(|(q, v)| (q, *v))
+			.collect::<HashMap<_, _>>();
+		if !self
+			.objective
+			.get_qubits()
+			.iter()
+			.all(|q| map.contains_key(q))
+		{
+			return None;
+		}
+		Some(self.objective.calculate(&map, &mut |p| match p {
+			Placeholder::Constraint(_) | Placeholder::Soft(_) => R::one(),
+			Placeholder::Placeholder(_) => panic!("placeholder remains"),
+		}))
+	}
+
+	/// Generate the PUBO of the model reduced by [`Self::reduce_to_pubo`], with
+	/// the penalty strength of each constraint given in `penalties`. Returns
+	/// the constant offset, the model and the qubits in the order of its
+	/// indices.
+	#[allow(clippy::type_complexity)]
+	pub fn to_pubo(
+		&self,
+		penalties: &HashMap<Tc, R>,
+	) -> (R, HighOrderModel<Binary<R>>, Vec<&Qubit<Tq>>) {
+		let qubits = self.get_qubits().into_iter().collect::<Vec<_>>();
+		let (c, model) = self.expanded.generate_pubo(&qubits, &mut |p| match p {
+			Placeholder::Constraint(label) => penalties[label],
+			Placeholder::Soft(_) => R::one(),
+			Placeholder::Placeholder(_) => panic!("placeholder remains"),
+		});
+		(c, model, qubits)
+	}
+}
+
+/// Model embedded into the physical qubits by [`CompiledModel::embed_into()`].
+#[derive(Clone, Debug)]
+pub struct EmbeddedModel<Tp, Tq, Tc, R>
+where
+	Tp: TpType, // Placeholder
+	Tq: TqType,
+	Tc: TcType,
+	R: Real,
+{
+	logical: CompiledModel<Tp, Tq, Tc, R>,
+	physical: CompiledModel<Tp, usize, Tc, R>,
+	/// The label of each logical qubit of `embedding`.
+	labels: Vec<Tq>,
+	embedding: Embedding,
+}
+
+impl<Tp, Tq, Tc, R> EmbeddedModel<Tp, Tq, Tc, R>
+where
+	Tp: TpType, // Placeholder
+	Tq: TqType,
+	Tc: TcType,
+	R: Real,
+{
+	/// The model before the embedding, which keeps the constraints.
+	pub fn logical(&self) -> &CompiledModel<Tp, Tq, Tc, R> {
+		&self.logical
+	}
+
+	/// The model labeled with the physical qubits, which has no constraints.
+	pub fn physical(&self) -> &CompiledModel<Tp, usize, Tc, R> {
+		&self.physical
+	}
+
+	/// The embedding, whose `i`-th chain is the one of `self.labels()[i]`.
+	pub fn embedding(&self) -> &Embedding {
+		&self.embedding
+	}
+
+	/// The labels of the qubits in the order of the chains of the embedding.
+	pub fn labels(&self) -> &[Tq] {
+		&self.labels
+	}
+
+	/// Feed real values to fill the placeholders of both models.
+	pub fn feed_dict(self, dict: HashMap<Tp, R>) -> EmbeddedModel<(), Tq, Tc, R> {
+		EmbeddedModel {
+			logical: self.logical.feed_dict(dict.clone()),
+			physical: self.physical.feed_dict(dict),
+			labels: self.labels,
+			embedding: self.embedding,
+		}
+	}
+
+	/// The solution of the physical qubits, where missing ones are false.
+	fn physical_solution(&self, physical: &HashMap<usize, bool>) -> SingleSolution<Binary<R>> {
+		let size = self
+			.embedding
+			.chains
+			.iter()
+			.flatten()
+			.max()
+			.map_or(0, |n| n + 1);
+		let v = (0..size)
+			.map(|n| physical.get(&n).copied().unwrap_or(false))
+			.collect::<Vec<_>>();
+		SingleSolution::from_vec(&v)
+	}
+
+	/// Get the values of the qubits from the values of the physical qubits by
+	/// [`Embedding::unembed`]. Missing physical qubits are treated as false.
+	/// The qubits fixed by [`CompiledModel::propagate_units`] are also
+	/// reported.
+	pub fn unembed(&self, physical: &HashMap<usize, bool>) -> HashMap<Tq, bool> {
+		let sol = self.embedding.unembed(&self.physical_solution(physical));
+		let mut ret = self
+			.labels
+			.iter()
+			.enumerate()
+			.map(|(i, q)| (q.clone(), sol[i]))
+			.collect::<HashMap<_, _>>();
+		ret.extend(self.logical.fixed().clone());
+		ret
+	}
+
+	/// The qubits whose chains have physical qubits of different values.
+	pub fn broken_chains(&self, physical: &HashMap<usize, bool>) -> Vec<&Tq> {
+		let sol = self.physical_solution(physical);
+		self.labels
+			.iter()
+			.zip(self.embedding.chains.iter())
+			.filter(|(_, chain)| chain.iter().any(|n| sol[*n] != sol[chain[0]]))
+			.map(|(q, _)| q)
+			.collect()
+	}
+}
+
+#[test]
+fn rescale_for_hardware_test() {
+	use crate::expr::Expr;
+	let (a, b) = (Expr::Binary("a"), Expr::Binary("b"));
+	let hmlt: Expr<(), _, (), f64> = 4.0 * a.clone() - 4.0 * a * b.clone() + b + 3.0;
+	let compiled = hmlt.compile();
+	let assignment = vec![("a", true), ("b", true)].into_iter().collect();
+	let energy = compiled.energy_of(&assignment).unwrap();
+	let (scaled, factor) = compiled.rescale_for_hardware(2.0, 1.0);
+	assert_eq!(factor, 0.25);
+	let qubits = scaled.get_qubits().into_iter().collect::<Vec<_>>();
+	let (c, model) = scaled.generate_qubo(&qubits, &mut |_| 1.0);
+	assert_eq!(c, 0.75);
+	assert_eq!(model.get_weight(&[0, 0]), 1.0);
+	assert_eq!(model.get_weight(&[0, 1]), -1.0);
+	assert_eq!(model.get_weight(&[1, 1]), 0.25);
+	assert_eq!(scaled.energy_of(&assignment).unwrap() / factor, energy);
+
+	let (_, factor) = Expr::<(), &str, (), f64>::Number(1.0)
+		.compile()
+		.rescale_for_hardware(2.0, 1.0);
+	assert_eq!(factor, 1.0);
+}
+
+#[test]
+#[should_panic]
+fn rescale_for_hardware_integer_test() {
+	use crate::expr::Expr;
+	let hmlt: Expr<(), _, (), i32> = 4 * Expr::Binary("a") * Expr::Binary("b");
+	hmlt.compile().rescale_for_hardware(2, 1);
+}
+
+#[test]
+fn write_qubo_text_test() {
+	use crate::expr::Expr;
+	let hmlt: Expr<(), _, _, f64> =
+		Expr::Constraint {
+			label: "c",
+			expr: Box::new((Expr::Binary("a") + Expr::Binary("b") - 1.0) ^ 2usize),
+		} + 3.0 * Expr::Binary("a") * Expr::Binary("b") * Expr::Binary("c");
+	let compiled = hmlt.compile();
+	let mut buf = Vec::new();
+	compiled.write_qubo_text(&mut buf).unwrap();
+	let text = String::from_utf8(buf).unwrap();
+
+	let qubits = compiled.get_qubits().into_iter().collect::<Vec<_>>();
+	assert!(qubits.len() > 3);
+	let (c, model) = compiled.generate_qubo(&qubits, &mut |_| 1.0);
+	let mut lines = text.lines().filter(|l| !l.starts_with('c'));
+	let header = lines.next().unwrap().split(' ').collect::<Vec<_>>();
+	assert_eq!(header[..2], ["p", "qubo"]);
+	assert_eq!(header[2].parse::<usize>().unwrap(), qubits.len());
+	let mut count = 0;
+	for line in lines {
+		let v = line.split(' ').collect::<Vec<_>>();
+		let (i, j) = (v[0].parse().unwrap(), v[1].parse().unwrap());
+		assert!(i <= j);
+		assert_eq!(v[2].parse::<f64>().unwrap(), model.get_weight(&[i, j]));
+		count += 1;
+	}
+	assert_eq!(header[3].parse::<usize>().unwrap(), count);
+	assert_eq!(
+		count,
+		model.prods().filter(|p| model.get_weight(p) != 0.0).count()
+	);
+	assert!(text.contains(&format!("c offset {}", c)));
+}
+
+#[test]
+fn reduction_strategy_test() {
+	use crate::expr::Expr;
+	use annealers::solution::SingleSolution;
+	fn b(i: usize) -> Expr<(), usize, (), i32> {
+		Expr::Binary(i)
+	}
+	// x0 * x1 * x2 is shared between two terms of degree 4
+	let hmlt = 2 * b(0) * b(1) * b(2) * b(3) + 3 * b(0) * b(1) * b(2) * b(4)
+		- 4 * b(3) * b(4)
+		- b(0) + b(5) * b(2);
+	let original = hmlt.clone();
+	let count_ancillas = |compiled: &CompiledModel<(), usize, (), i32>| {
+		compiled
+			.get_qubits()
+			.into_iter()
+			.filter(|q| matches!(q, Qubit::Ancilla(_)))
+			.count()
+	};
+	let min_energy = |compiled: &CompiledModel<(), usize, (), i32>| {
+		let qubits = compiled.get_qubits().into_iter().collect::<Vec<_>>();
+		let (c, model) = compiled.generate_qubo(&qubits, &mut |_| 1);
+		(0..1usize << qubits.len())
+			.map(|bits| {
+				let v = (0..qubits.len())
+					.map(|i| bits & (1 << i) != 0)
+					.collect::<Vec<_>>();
+				SingleSolution::from_vec(&v).calculate_energy(&model) + c
+			})
+			.min()
+			.unwrap()
+	};
+	let ishikawa = hmlt
+		.clone()
+		.compile_with_strategy(ReductionStrategy::Ishikawa);
+	let by_pair = hmlt.compile_with_strategy(ReductionStrategy::SubstitutionByPair);
+	assert!(count_ancillas(&by_pair) < count_ancillas(&ishikawa));
+	let labels = (0..6).collect::<Vec<usize>>();
+	let expected = (0..1usize << 6)
+		.map(|bits| {
+			let map = labels.iter().map(|i| (i, bits & (1 << i) != 0)).collect();
+			original.calculate(&map).unwrap()
+		})
+		.min()
+		.unwrap();
+	assert_eq!(min_energy(&by_pair), expected);
+}
+
+#[test]
+fn qubits_indexed_test() {
+	use crate::expr::Expr;
+	let expr: Expr<(), usize, (), i32> =
+		Expr::Binary(0) * Expr::Binary(1) * Expr::Binary(2) + Expr::Binary(3) * Expr::Binary(1);
+	let compiled = expr.compile();
+	let indexed = compiled.qubits_indexed();
+	assert!(indexed.len() > 4);
+	for (i, (j, _)) in indexed.iter().enumerate() {
+		assert_eq!(i, *j);
+	}
+	let qubits = compiled.get_qubits().into_iter().collect::<Vec<_>>();
+	assert_eq!(qubits, indexed.iter().map(|(_, q)| *q).collect::<Vec<_>>());
+	let first_ancilla = indexed
+		.iter()
+		.position(|(_, q)| matches!(q, Qubit::Ancilla(_)))
+		.unwrap();
+	assert_eq!(first_ancilla, 4);
+	assert!(indexed[first_ancilla..]
+		.iter()
+		.all(|(_, q)| matches!(q, Qubit::Ancilla(_))));
+}
+
+#[test]
+fn get_constraint_test() {
+	use crate::expr::Expr;
+	let b = |i: usize| Expr::Binary(i);
+	let expr: Expr<(), usize, &str, i32> = Expr::Constraint {
+		label: "a",
+		expr: Box::new((b(0) + b(1) - 1) ^ 2usize),
+	} + Expr::Constraint {
+		label: "b",
+		expr: Box::new((b(1) + b(2) - 1) ^ 2usize),
+	} + Expr::Constraint {
+		label: "a",
+		expr: Box::new((b(2) + b(3) - 1) ^ 2usize),
+	};
+	let compiled = expr.compile();
+	assert_eq!(compiled.get_constraint(&"a").unwrap().label, Some("a"));
+	assert_eq!(compiled.get_constraint(&"b").unwrap().label, Some("b"));
+	assert!(compiled.get_constraint(&"c").is_none());
+	assert_eq!(compiled.get_constraints(&"a").len(), 2);
+	assert_eq!(compiled.get_constraints(&"b").len(), 1);
+	assert!(compiled.get_constraints(&"c").is_empty());
+}
+
+#[test]
+fn connected_components_test() {
+	use crate::expr::Expr;
+	fn b(i: usize) -> Expr<(), usize, &'static str, i32> {
+		Expr::Binary(i)
+	}
+	let hmlt = b(0) * b(1)
+		+ Expr::Constraint {
+			label: "c",
+			expr: Box::new((b(1) + b(2) - 1) ^ 2usize),
+		} + 2 * b(3) * b(4)
+		- b(5);
+	let components = hmlt.compile().connected_components();
+	let q = |i| Qubit::Qubit(i);
+	assert_eq!(
+		components,
+		vec![vec![q(0), q(1), q(2)], vec![q(3), q(4)], vec![q(5)]]
+	);
+}
+
+#[test]
+fn reduce_to_pubo_test() {
+	use crate::expr::Expr;
+	use annealers::order::Order;
+	use annealers::solution::SingleSolution;
+	fn b(i: usize) -> Expr<(), usize, (), i32> {
+		Expr::Binary(i)
+	}
+	let hmlt = 2 * b(0) * b(1) * b(2) * b(3) + 3 * b(0) * b(1) * b(4) - 4 * b(3) * b(4) - b(0);
+	let pubo = hmlt.clone().compile_to_pubo();
+	let qubo = hmlt.clone().compile();
+	let count_ancillas = |compiled: &CompiledModel<(), usize, (), i32>| {
+		compiled
+			.get_qubits()
+			.into_iter()
+			.filter(|q| matches!(q, Qubit::Ancilla(_)))
+			.count()
+	};
+	assert!(count_ancillas(&pubo) < count_ancillas(&qubo));
+	let (c, model, qubits) = pubo.to_pubo(&HashMap::new());
+	assert_eq!(model.order().order(), 3);
+	assert!(model.prods().any(|p| p.len() == 3));
+	assert!(model.prods().all(|p| p.len() <= 3));
+	let min_energy = (0..1usize << qubits.len())
+		.map(|bits| {
+			let v = (0..qubits.len())
+				.map(|i| bits & (1 << i) != 0)
+				.collect::<Vec<_>>();
+			SingleSolution::from_vec(&v).calculate_energy(&model) + c
+		})
+		.min()
+		.unwrap();
+	let expected = (0..1usize << 5)
+		.map(|bits| {
+			let map = (0..5).map(|i| (i, bits & (1 << i) != 0)).collect();
+			hmlt.evaluate(&map).unwrap()
+		})
+		.min()
+		.unwrap();
+	assert_eq!(min_energy, expected);
+}
+
+#[test]
+fn energy_of_test() {
+	use crate::expr::Expr;
+	use annealers::solver::{ClassicalSolver, SolverGenerator};
+	use classical_solver::beta::BetaType;
+	use classical_solver::sa::SimulatedAnnealerGenerator;
+	use rand::SeedableRng;
+	let hmlt: Expr<(), &str, &str, f64> =
+		Expr::Constraint {
+			label: "c",
+			expr: Box::new((Expr::Binary("a") + Expr::Binary("b") - 1.0) ^ 2usize),
+		} + 3.0 * Expr::Binary("a") * Expr::Binary("b") * Expr::Binary("c")
+			- 2.0 * Expr::Binary("c") * Expr::Binary("d");
+	let compiled = hmlt.compile();
+	let qubits = compiled.get_qubits().into_iter().collect::<Vec<_>>();
+	assert!(qubits.iter().any(|q| matches!(q, Qubit::Ancilla(_))));
+	let (c, model) = compiled.generate_qubo(&qubits, &mut |_| 1.0);
+	let mut generator = SimulatedAnnealerGenerator::new();
+	generator.beta = BetaType::Schedule(vec![1.0, 10.0, 100.0]);
+	let solver = generator.generate(&model).unwrap();
+	let mut r = rand::rngs::StdRng::seed_from_u64(0);
+	for sol in solver.solve_with_rng(&mut r).unwrap() {
+		let assignment = qubits
+			.iter()
+			.enumerate()
+			.filter_map(|(i, q)| match q {
+				Qubit::Qubit(label) => Some((*label, sol[i])),
+				Qubit::Ancilla(_) => None,
+			})
+			.collect::<HashMap<_, _>>();
+		assert_eq!(
+			compiled.energy_of(&assignment),
+			Some(sol.calculate_energy(&model) + c)
+		);
+	}
+	assert_eq!(
+		compiled.energy_of(&vec![("a", true)].into_iter().collect()),
+		None
+	);
+}
+
+#[test]
+fn density_test() {
+	use crate::expr::Expr;
+	let b = Expr::Binary;
+	let expr: Expr<(), _, (), f64> =
+		b("a") * b("b") + b("a") * b("c") - 2.0 * b("a") + b("c") + Expr::Number(3.0);
+	let compiled = expr.compile();
+	// 4 monomials out of the 6 of 3 qubits
+	assert!((compiled.density() - 4.0 / 6.0).abs() < 1e-12);
+	assert!((compiled.sparsity() - 2.0 / 6.0).abs() < 1e-12);
+	assert_eq!(compiled.max_degree_node(), Some((&Qubit::Qubit("a"), 3)));
+	let empty: CompiledModel<(), &str, (), f64> = Expr::Number(1.0).compile();
+	assert_eq!(empty.density(), 0.0);
+	assert_eq!(empty.max_degree_node(), None);
+
+	// The cancelled monomial and its qubits are not counted
+	let expr: Expr<(), _, (), f64> = b("a") * b("b") - b("a") * b("b") + b("c");
+	let compiled = expr.compile();
+	assert_eq!(compiled.density(), 1.0);
+	assert_eq!(compiled.max_degree_node(), Some((&Qubit::Qubit("c"), 1)));
+	let cancelled: Expr<(), _, (), f64> = b("a") * b("b") - b("a") * b("b");
+	let compiled = cancelled.compile();
+	assert_eq!(compiled.density(), 0.0);
+	assert_eq!(compiled.max_degree_node(), None);
+}
+
+#[test]
+fn embed_into_test() {
+	use crate::expr::Expr;
+	use annealers::solution::SingleSolution;
+	let (a, b) = (Expr::Binary("a"), Expr::Binary("b"));
+	let hmlt: Expr<&str, _, (), f64> =
+		Expr::Placeholder("p") * a.clone() - 3.0 * a.clone() * b.clone() + b.clone();
+	let pair = |u: usize, v: usize| vec![u, v].into_iter().collect::<BTreeSet<_>>();
+	let couplers = vec![pair(0, 1), pair(1, 2)].into_iter().collect();
+	let embedding = vec![(&"a", vec![0, 1]), (&"b", vec![2])]
+		.into_iter()
+		.collect::<HashMap<_, _>>();
+	let embedded = hmlt
+		.clone()
+		.compile()
+		.embed_into(embedding.clone(), &couplers, 10.0)
+		.unwrap()
+		.feed_dict(vec![("p", 1.0)].into_iter().collect());
+	let physical = embedded.physical();
+	let qubits = physical.get_qubits().into_iter().collect::<Vec<_>>();
+	assert_eq!(
+		qubits,
+		vec![&Qubit::Qubit(0), &Qubit::Qubit(1), &Qubit::Qubit(2)]
+	);
+	let (c, model) = physical.generate_qubo(&qubits, &mut |_| 1.0);
+	assert_eq!(c, 0.0);
+	assert_eq!(model.get_weight(&[0, 0]), 11.0);
+	assert_eq!(model.get_weight(&[0, 1]), -20.0);
+	assert_eq!(model.get_weight(&[1, 1]), 10.0);
+	assert_eq!(model.get_weight(&[1, 2]), -3.0);
+	assert_eq!(model.get_weight(&[2, 2]), 1.0);
+	assert_eq!(model.get_weight(&[0, 2]), 0.0);
+	let (energy, bits) = (0..1usize << 3)
+		.map(|bits| {
+			let v = (0..3).map(|i| bits & (1 << i) != 0).collect::<Vec<_>>();
+			(SingleSolution::from_vec(&v).calculate_energy(&model), bits)
+		})
+		.fold((f64::INFINITY, 0), |m, e| if e.0 < m.0 { e } else { m });
+	assert_eq!(energy, -1.0);
+	let assignment = (0..3).map(|i| (i, bits & (1 << i) != 0)).collect();
+	assert!(embedded.broken_chains(&assignment).is_empty());
+	let sol = embedded.unembed(&assignment);
+	assert_eq!(embedded.logical().energy_of(&sol), Some(energy));
+	let broken = vec![(0, true), (1, false), (2, true)].into_iter().collect();
+	assert_eq!(embedded.broken_chains(&broken), vec![&"a"]);
+	assert!(embedded.unembed(&broken)[&"a"]);
+
+	assert_eq!(embedded.labels(), &["a", "b"]);
+	assert_eq!(embedded.embedding().chains, vec![vec![0, 1], vec![2]]);
+
+	let invalid =
+		|embedding, couplers| match hmlt.clone().compile().embed_into(embedding, couplers, 10.0) {
+			Err(EmbeddingError::InvalidEmbedding(msg)) => msg,
+			_ => panic!("the embedding must be invalid"),
+		};
+	// The chain of "a" is not coupled
+	let coupler = vec![pair(1, 2)].into_iter().collect();
+	assert_eq!(
+		invalid(embedding.clone(), &coupler),
+		"some chain is not connected by the couplers"
+	);
+	// "b" has no chain
+	let partial = vec![(&"a", vec![0, 1])].into_iter().collect();
+	assert_eq!(invalid(partial, &couplers), "some qubit has no chain");
+	let overlap = vec![(&"a", vec![0, 1]), (&"b", vec![1])]
+		.into_iter()
+		.collect();
+	assert_eq!(invalid(overlap, &couplers), "chains overlap");
+	// The ancilla of the order reduction has no chain
+	let cubic = a.clone() * b.clone() * Expr::Binary("c");
+	let embedding = vec![(&"a", vec![0]), (&"b", vec![1]), (&"c", vec![2])]
+		.into_iter()
+		.collect();
+	assert!(matches!(
+		cubic.compile().embed_into(embedding, &couplers, 10.0),
+		Err(EmbeddingError::InvalidEmbedding(_))
+	));
+
+	// A chain is bound by its couplers, not by the order of its qubits
+	let couplers = vec![pair(0, 1), pair(0, 2), pair(2, 3)]
+		.into_iter()
+		.collect();
+	let embedding = vec![(&"a", vec![1, 2, 0]), (&"b", vec![3])]
+		.into_iter()
+		.collect();
+	let embedded = hmlt
+		.compile()
+		.embed_into(embedding, &couplers, 10.0)
+		.unwrap()
+		.feed_dict(vec![("p", 1.0)].into_iter().collect());
+	let physical = embedded.physical();
+	let qubits = physical.get_qubits().into_iter().collect::<Vec<_>>();
+	let (_, model) = physical.generate_qubo(&qubits, &mut |_| 1.0);
+	assert_eq!(model.get_weight(&[0, 1]), -20.0);
+	assert_eq!(model.get_weight(&[0, 2]), -20.0);
+	assert_eq!(model.get_weight(&[1, 2]), 0.0);
+	assert_eq!(model.get_weight(&[2, 3]), -3.0);
+	let broken = vec![(0, true), (1, true), (2, false), (3, true)]
+		.into_iter()
+		.collect();
+	assert_eq!(embedded.broken_chains(&broken), vec![&"a"]);
+	assert_eq!(
+		embedded.unembed(&broken),
+		vec![("a", true), ("b", true)].into_iter().collect()
+	);
+}
+
+#[test]
+fn normalize_test() {
+	use crate::expr::Expr;
+	use annealers::solution::SingleSolution;
+	let b = |s| Expr::Binary(s);
+	let hmlt: Expr<&str, _, _, f64> = Expr::Constraint {
+		label: "c",
+		expr: Box::new((b("a") + b("b") - 1.0) ^ 2usize),
+	} + Expr::Placeholder("p") * b("a") * b("c")
+		- 8.0 * b("b") * b("c")
+		+ 2.0 * b("a")
+		+ 5.0;
+	let compiled = hmlt.compile();
+	let (normalized, scale) = compiled.normalize();
+	assert_eq!(scale, 0.125);
+	let max = normalized
+		.expanded
+		.iter()
+		.filter(|(set, _)| !set.is_empty())
+		.map(|(_, exp)| exp.calculate(&mut |_| 1.0).abs())
+		.fold(0.0, f64::max);
+	assert!((max - 1.0).abs() < 1e-12);
+	let dict = vec![("p", 3.0)].into_iter().collect::<HashMap<_, _>>();
+	let original = compiled.feed_dict(dict.clone());
+	let normalized = normalized.feed_dict(dict);
+	let ground = |compiled: &CompiledModel<(), &str, &str, f64>| {
+		let qubits = compiled.get_qubits().into_iter().collect::<Vec<_>>();
+		let (c, model) = compiled.generate_qubo(&qubits, &mut |_| 10.0);
+		(0..1usize << qubits.len())
+			.map(|bits| {
+				let v = (0..qubits.len())
+					.map(|i| bits & (1 << i) != 0)
+					.collect::<Vec<_>>();
+				(
+					SingleSolution::from_vec(&v).calculate_energy(&model) + c,
+					bits,
+				)
+			})
+			.fold((f64::INFINITY, 0), |m, e| if e.0 < m.0 { e } else { m })
+	};
+	let (energy, bits) = ground(&original);
+	let (normalized_energy, normalized_bits) = ground(&normalized);
+	assert_eq!(bits, normalized_bits);
+	assert!((normalized_energy - energy * scale).abs() < 1e-12);
+
+	let (_, scale) = Expr::<(), &str, (), f64>::Number(2.0).compile().normalize();
+	assert_eq!(scale, 1.0);
+}
+
+#[test]
+#[should_panic]
+fn normalize_integer_test() {
+	use crate::expr::Expr;
+	let hmlt: Expr<(), _, (), i32> = 4 * Expr::Binary("a");
+	hmlt.compile().normalize();
+}
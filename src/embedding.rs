@@ -0,0 +1,350 @@
+//! Embedding of the QUBO into structured solvers, whose topology restricts
+//! the allowed couplers.
+use annealers::model::{FixedSingleQuadricModel, SingleModelView};
+use annealers::node::Binary;
+use annealers::order::Quadric;
+use annealers::solution::SingleSolution;
+use annealers::solver::{
+	ClassicalSolver, Solver, SolverGenerator, StructuredSolverGenerator,
+	UnstructuredSolverGenerator,
+};
+use annealers::variable::Real;
+use std::collections::{BTreeSet, HashSet};
+use std::error::Error;
+use std::sync::Arc;
+
+type Model<R> = FixedSingleQuadricModel<Binary<R>>;
+
+/// Mapping from each logical qubit to a chain of the target nodes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Embedding {
+	/// `chains[i]` is the chain of the logical qubit `i`. The nodes of a chain
+	/// must be connected by the couplers of the target.
+	pub chains: Vec<Vec<usize>>,
+}
+
+impl Embedding {
+	/// Build the model on the target nodes. The couplers of a spanning tree of
+	/// each chain are given the penalty `chain_strength * (a + b - 2ab)`.
+	/// Returns `None` if some chain is not connected by the couplers in
+	/// `target_prods`, or some logical coupler has no corresponding coupler.
+	pub fn embed_model<R: Real>(
+		&self,
+		logical: &Model<R>,
+		target_prods: &HashSet<BTreeSet<usize>>,
+		chain_strength: R,
+	) -> Option<Model<R>> {
+		let size = self.chains.iter().flatten().max().map_or(0, |n| n + 1);
+		let mut model = FixedSingleQuadricModel::new(Binary::new(), size);
+		for (u, v) in self.chain_couplers(target_prods)? {
+			// (a - b)^2 = a + b - 2ab
+			model.add_weight(u, u, chain_strength);
+			model.add_weight(v, v, chain_strength);
+			model.add_weight(u, v, -R::from_i32(2) * chain_strength);
+		}
+		for p in logical.prods() {
+			let weight = logical.get_weight(&p);
+			if weight == R::zero() {
+				continue;
+			}
+			let (i, j) = (p[0], p[1]);
+			if i == j {
+				model.add_weight(self.chains[i][0], self.chains[i][0], weight);
+			} else {
+				let (u, v) = self.coupler(i, j, target_prods)?;
+				model.add_weight(u, v, weight);
+			}
+		}
+		Some(model)
+	}
+
+	/// The couplers of a spanning tree of each chain, which bind the nodes of
+	/// the chain. Returns `None` if some chain is not connected by the couplers
+	/// in `target_prods`.
+	pub fn chain_couplers(
+		&self,
+		target_prods: &HashSet<BTreeSet<usize>>,
+	) -> Option<Vec<(usize, usize)>> {
+		let mut ret = Vec::new();
+		for chain in self.chains.iter() {
+			// Breadth-first search from the first node
+			let mut visited = vec![false; chain.len()];
+			let mut queue = std::collections::VecDeque::new();
+			if !chain.is_empty() {
+				visited[0] = true;
+				queue.push_back(0);
+			}
+			while let Some(a) = queue.pop_front() {
+				for b in 0..chain.len() {
+					let pair = vec![chain[a], chain[b]].into_iter().collect();
+					if !visited[b] && target_prods.contains(&pair) {
+						visited[b] = true;
+						ret.push((chain[a], chain[b]));
+						queue.push_back(b);
+					}
+				}
+			}
+			if visited.iter().any(|v| !v) {
+				return None;
+			}
+		}
+		Some(ret)
+	}
+
+	/// The first coupler in `target_prods` between the chains of the logical
+	/// qubits `i` and `j`, on which their coupling is put.
+	pub fn coupler(
+		&self,
+		i: usize,
+		j: usize,
+		target_prods: &HashSet<BTreeSet<usize>>,
+	) -> Option<(usize, usize)> {
+		self.chains[i].iter().find_map(|u| {
+			self.chains[j]
+				.iter()
+				.find(|v| target_prods.contains(&vec![*u, **v].into_iter().collect()))
+				.map(|v| (*u, *v))
+		})
+	}
+
+	/// Get the values of the logical qubits from a solution of the target by
+	/// the majority vote of each chain. Ties are broken by the first node.
+	pub fn unembed<R: Real>(&self, sol: &SingleSolution<Binary<R>>) -> SingleSolution<Binary<R>> {
+		let v = self
+			.chains
+			.iter()
+			.map(|chain| {
+				let count = chain.iter().filter(|n| sol[**n]).count();
+				match (2 * count).cmp(&chain.len()) {
+					std::cmp::Ordering::Greater => true,
+					std::cmp::Ordering::Less => false,
+					std::cmp::Ordering::Equal => sol[chain[0]],
+				}
+			})
+			.collect::<Vec<_>>();
+		SingleSolution::from_vec(&v)
+	}
+}
+
+/// Finds an [`Embedding`] of a logical model into the target graph. This is
+/// the integration point of minor-embedding algorithms.
+pub trait Embedder<R: Real>: Send + Sync {
+	fn embed(
+		&self,
+		logical: &Model<R>,
+		target_nodes: &[usize],
+		target_prods: &HashSet<BTreeSet<usize>>,
+	) -> Option<Embedding>;
+}
+
+/// Embedder which maps the logical qubit `i` to the `i`-th target node, which
+/// is suitable for the targets of complete graphs.
+#[derive(Clone, Debug, Default)]
+pub struct IdentityEmbedder;
+
+impl<R: Real> Embedder<R> for IdentityEmbedder {
+	fn embed(
+		&self,
+		logical: &Model<R>,
+		target_nodes: &[usize],
+		_target_prods: &HashSet<BTreeSet<usize>>,
+	) -> Option<Embedding> {
+		if logical.size() > target_nodes.len() {
+			return None;
+		}
+		Some(Embedding {
+			chains: target_nodes[..logical.size()]
+				.iter()
+				.map(|n| vec![*n])
+				.collect(),
+		})
+	}
+}
+
+/// Error of [`EmbeddedSolverGenerator`] and [`CompiledModel::embed_into`].
+///
+/// [`CompiledModel::embed_into`]: crate::compiled::CompiledModel::embed_into
+#[derive(Debug)]
+pub enum EmbeddingError<E = std::convert::Infallible> {
+	/// The embedder could not embed the model into the target.
+	NotFound,
+	/// The given embedding is invalid for the model or the target.
+	InvalidEmbedding(&'static str),
+	/// The underlying solver returned an error.
+	Solver(E),
+}
+
+impl<E: Error> std::fmt::Display for EmbeddingError<E> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::NotFound => write!(f, "embedding not found"),
+			Self::InvalidEmbedding(msg) => write!(f, "invalid embedding: {}", msg),
+			Self::Solver(e) => write!(f, "solver error: {}", e),
+		}
+	}
+}
+
+impl<E: Error + 'static> Error for EmbeddingError<E> {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		if let Self::Solver(e) = self {
+			Some(e)
+		} else {
+			None
+		}
+	}
+}
+
+/// Unstructured solver generator which embeds the model into the structured
+/// solver `G`, and unembeds the solutions.
+pub struct EmbeddedSolverGenerator<G, R: Real> {
+	generator: Arc<G>,
+	embedder: Arc<dyn Embedder<R>>,
+}
+
+impl<G, R: Real> Clone for EmbeddedSolverGenerator<G, R> {
+	fn clone(&self) -> Self {
+		Self {
+			generator: self.generator.clone(),
+			embedder: self.embedder.clone(),
+		}
+	}
+}
+
+impl<G, R: Real> EmbeddedSolverGenerator<G, R> {
+	pub fn new(generator: G, embedder: Box<dyn Embedder<R>>) -> Self {
+		Self {
+			generator: Arc::new(generator),
+			embedder: Arc::from(embedder),
+		}
+	}
+}
+
+pub struct EmbeddedSolver<G, R: Real> {
+	generator: Arc<G>,
+	embedding: Embedding,
+	target: Model<R>,
+}
+
+impl<'a, G, E, R> SolverGenerator<'a, Model<R>> for EmbeddedSolverGenerator<G, R>
+where
+	G: for<'m> StructuredSolverGenerator<'m, Model<R>, ErrorType = E> + Send + Sync,
+	E: Error + Send + Sync + 'static,
+	R: Real,
+{
+	type SolverType = EmbeddedSolver<G, R>;
+	type ErrorType = EmbeddingError<E>;
+
+	fn generate(&self, model: &'a Model<R>) -> Result<Self::SolverType, Self::ErrorType> {
+		let target_nodes = self.generator.nodes().collect::<Vec<_>>();
+		let target_prods = self.generator.prods().collect::<HashSet<_>>();
+		let embedding = self
+			.embedder
+			.embed(model, &target_nodes, &target_prods)
+			.ok_or(EmbeddingError::NotFound)?;
+		// The chains must be stronger than any weight of the model.
+		let chain_strength = model
+			.prods()
+			.map(|p| model.get_weight(&p).abs())
+			.fold(R::one(), R::max)
+			* R::from_i32(2);
+		let target = embedding
+			.embed_model(model, &target_prods, chain_strength)
+			.ok_or(EmbeddingError::NotFound)?;
+		Ok(EmbeddedSolver {
+			generator: self.generator.clone(),
+			embedding,
+			target,
+		})
+	}
+}
+
+impl<'a, G, E, R> UnstructuredSolverGenerator<'a, Model<R>> for EmbeddedSolverGenerator<G, R>
+where
+	G: for<'m> StructuredSolverGenerator<'m, Model<R>, ErrorType = E> + Send + Sync,
+	E: Error + Send + Sync + 'static,
+	R: Real,
+{
+	type Order = Quadric;
+
+	fn order(&self) -> Quadric {
+		Quadric
+	}
+}
+
+impl<G, E, R> Solver for EmbeddedSolver<G, R>
+where
+	G: for<'m> StructuredSolverGenerator<'m, Model<R>, ErrorType = E> + Send + Sync,
+	E: Error + Send + Sync + 'static,
+	R: Real,
+{
+	type ErrorType = EmbeddingError<E>;
+	type SolutionType = SingleSolution<Binary<R>>;
+}
+
+impl<G, E, R> ClassicalSolver for EmbeddedSolver<G, R>
+where
+	G: for<'m> StructuredSolverGenerator<'m, Model<R>, ErrorType = E> + Send + Sync,
+	for<'m> <G as SolverGenerator<'m, Model<R>>>::SolverType:
+		ClassicalSolver<SolutionType = SingleSolution<Binary<R>>>,
+	E: Error + Send + Sync + 'static,
+	R: Real,
+{
+	fn solve_with_rng<T: rand::Rng>(
+		&self,
+		r: &mut T,
+	) -> Result<Vec<SingleSolution<Binary<R>>>, EmbeddingError<E>> {
+		// The solver borrows the target model, so it is generated on each solve.
+		let solver = self
+			.generator
+			.generate(&self.target)
+			.map_err(EmbeddingError::Solver)?;
+		Ok(solver
+			.solve_with_rng(r)
+			.map_err(EmbeddingError::Solver)?
+			.iter()
+			.map(|sol| self.embedding.unembed(sol))
+			.collect())
+	}
+}
+
+#[test]
+fn embedding_test() {
+	let mut logical = FixedSingleQuadricModel::new(Binary::new(), 2);
+	logical.add_weight(0, 0, 1.0f64);
+	logical.add_weight(0, 1, -3.0);
+	let embedding = Embedding {
+		chains: vec![vec![0, 1], vec![2]],
+	};
+	let prods = vec![vec![0, 1], vec![1, 2]]
+		.into_iter()
+		.map(|p| p.into_iter().collect())
+		.collect();
+	let target = embedding.embed_model(&logical, &prods, 10.0).unwrap();
+	assert_eq!(target.get_weight(&[0, 0]), 11.0);
+	assert_eq!(target.get_weight(&[0, 1]), -20.0);
+	assert_eq!(target.get_weight(&[1, 1]), 10.0);
+	assert_eq!(target.get_weight(&[1, 2]), -3.0);
+	assert_eq!(target.get_weight(&[0, 2]), 0.0);
+	let sol = embedding.unembed(&SingleSolution::<Binary<f64>>::from_vec(&[
+		true, false, true,
+	]));
+	assert_eq!(sol.state.to_vec(), vec![true, true]);
+	assert!(embedding
+		.embed_model(&logical, &HashSet::new(), 10.0)
+		.is_none());
+
+	// The chain is bound by a spanning tree of its couplers, in any order
+	let embedding = Embedding {
+		chains: vec![vec![0, 2, 1]],
+	};
+	let prods = vec![vec![0, 1], vec![1, 2]]
+		.into_iter()
+		.map(|p| p.into_iter().collect())
+		.collect();
+	assert_eq!(embedding.chain_couplers(&prods), Some(vec![(0, 1), (1, 2)]));
+	let prods = vec![vec![0, 1]]
+		.into_iter()
+		.map(|p| p.into_iter().collect())
+		.collect();
+	assert_eq!(embedding.chain_couplers(&prods), None);
+}
@@ -1,10 +1,12 @@
+use crate::embedding::Embedding;
 use crate::expr::{Expr, StaticExpr};
 use crate::wrapper::{Placeholder, Qubit};
 use crate::{TcType, TpType, TqType};
-use annealers::model::FixedSingleQuadricModel;
+use annealers::model::{FixedSingleQuadricModel, HighOrderModel};
 use annealers::node::Binary;
+use annealers::order::HighOrder;
 use annealers::variable::Real;
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::convert::From;
 use std::hash::Hash;
 use std::num::NonZeroUsize;
@@ -175,6 +177,18 @@ where
 		)
 	}
 
+	/// Substitute `value` for the qubit `q`.
+	pub fn fix_qubit(self, q: &Qubit<Tq>, value: bool) -> Self {
+		let mut ret = Self::new();
+		for (mut set, exp) in self.0.into_iter() {
+			if set.remove(q) && !value {
+				continue;
+			}
+			ret += Self::from(set, exp);
+		}
+		ret
+	}
+
 	pub fn get_placeholders(&self) -> BTreeSet<&Placeholder<Tp, Tc>> {
 		let mut ret = BTreeSet::new();
 		for (_, exp) in self.0.iter() {
@@ -189,7 +203,102 @@ where
 	pub fn get_qubits(&self) -> BTreeSet<&Qubit<Tq>> {
 		let mut ret = BTreeSet::new();
 		for (qubits, _) in self.0.iter() {
-			ret = ret.union(&qubits.iter().collect()).cloned().collect()
+			ret.extend(qubits.iter());
+		}
+		ret
+	}
+
+	/// The monomials whose coefficients do not simplify to zero, e.g. which
+	/// are not cancelled as `ab - ab`.
+	fn nonzero_monomials(&self) -> impl Iterator<Item = &BTreeSet<Qubit<Tq>>> {
+		self.0
+			.iter()
+			.filter(|(_, exp)| match (*exp).clone().simplify() {
+				StaticExpr::Number(n) => n != R::zero(),
+				_ => true,
+			})
+			.map(|(set, _)| set)
+	}
+
+	/// The ratio of the non-constant monomials to the `n * (n + 1) / 2`
+	/// monomials of a QUBO of the `n` qubits, or `0` if there is no qubit.
+	/// Monomials whose coefficients simplify to zero are not counted, nor are
+	/// the qubits appearing only in them.
+	pub fn density(&self) -> f64 {
+		let monomials = self
+			.nonzero_monomials()
+			.filter(|set| !set.is_empty())
+			.collect::<Vec<_>>();
+		let n = monomials
+			.iter()
+			.flat_map(|set| set.iter())
+			.collect::<BTreeSet<_>>()
+			.len();
+		if n == 0 {
+			return 0.0;
+		}
+		monomials.len() as f64 / (n * (n + 1) / 2) as f64
+	}
+
+	/// `1 - density()`.
+	pub fn sparsity(&self) -> f64 {
+		1.0 - self.density()
+	}
+
+	/// The qubit appearing in the most monomials and the number of them, where
+	/// the monomials whose coefficients simplify to zero are not counted. Ties
+	/// are broken by the order of the qubits.
+	pub fn max_degree_node(&self) -> Option<(&Qubit<Tq>, usize)> {
+		let mut degrees = BTreeMap::new();
+		for set in self.nonzero_monomials() {
+			for q in set.iter() {
+				*degrees.entry(q).or_insert(0) += 1;
+			}
+		}
+		degrees.into_iter().fold(None, |best, (q, d)| match best {
+			Some((_, max)) if max >= d => best,
+			_ => Some((q, d)),
+		})
+	}
+
+	/// Relabel the qubits with the target nodes of `embedding`, where `index`
+	/// gives the logical qubit of each qubit. Linear terms are put on the first
+	/// node of the chain, and quadratic terms on [`Embedding::coupler`].
+	/// Returns `None` if some qubit has no index, some quadratic term has no
+	/// coupler, or the order is higher than 2.
+	pub fn embed(
+		&self,
+		index: &HashMap<&Qubit<Tq>, usize>,
+		embedding: &Embedding,
+		couplers: &HashSet<BTreeSet<usize>>,
+	) -> Option<Expanded<Tp, usize, Tc, R>> {
+		let mut ret = Expanded::new();
+		for (set, exp) in self.0.iter() {
+			let nodes = match set.iter().collect::<Vec<_>>().as_slice() {
+				[] => vec![],
+				[q] => vec![embedding.chains[*index.get(q)?][0]],
+				[q1, q2] => {
+					let (u, v) = embedding.coupler(*index.get(q1)?, *index.get(q2)?, couplers)?;
+					vec![u, v]
+				}
+				_ => return None,
+			};
+			ret += Expanded::from(nodes.into_iter().map(Qubit::Qubit).collect(), exp.clone());
+		}
+		Some(ret)
+	}
+
+	/// Calculate the value with the given assignment. Qubits missing in `map`
+	/// are treated as false.
+	pub fn calculate<F>(&self, map: &HashMap<&Qubit<Tq>, bool>, ph_feedback: &mut F) -> R
+	where
+		F: FnMut(&Placeholder<Tp, Tc>) -> R,
+	{
+		let mut ret = R::zero();
+		for (set, expr) in self.0.iter() {
+			if set.iter().all(|q| map.get(q) == Some(&true)) {
+				ret += expr.calculate(ph_feedback);
+			}
 		}
 		ret
 	}
@@ -234,6 +343,34 @@ where
 		(c, model)
 	}
 
+	/// Same as [`Self::generate_qubo`], keeping the interactions of any order.
+	pub fn generate_pubo<F>(
+		&self,
+		qubits: &[&Qubit<Tq>],
+		ph_feedback: &mut F,
+	) -> (R, HighOrderModel<Binary<R>>)
+	where
+		F: FnMut(&Placeholder<Tp, Tc>) -> R,
+	{
+		let dict = qubits
+			.iter()
+			.cloned()
+			.enumerate()
+			.map(|(i, q)| (q, i))
+			.collect::<HashMap<&Qubit<Tq>, usize>>();
+		let mut c = R::from_i32(0);
+		let mut model = HighOrderModel::new(Binary::new(), HighOrder::new(self.get_order()));
+		for (set, expr) in self.0.iter() {
+			let val = expr.calculate(ph_feedback);
+			if set.is_empty() {
+				c += val;
+			} else {
+				model.add_weight(set.iter().map(|q| dict[q]).collect(), val);
+			}
+		}
+		(c, model)
+	}
+
 	pub fn count_qubit_subsets(
 		&self,
 		max_order: usize,
@@ -1,11 +1,13 @@
-use crate::compiled::CompiledModel;
+use crate::compiled::{CompiledModel, ReductionStrategy};
 use crate::model::Model;
 use crate::wrapper::Placeholder;
 use crate::{TcType, TpType, TqType};
 use annealers::variable::{ConvertFrom, Real};
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::mem::MaybeUninit;
-use std::ops::{Add, AddAssign, BitXor, BitXorAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::ops::{
+	Add, AddAssign, BitXor, BitXorAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign,
+};
 
 // TODO: hide the implementation from public
 #[derive(PartialEq, Clone, Debug)]
@@ -23,8 +25,36 @@ where
 	// TODO: use annealers_rust::node::{Spin,Binary}
 	Binary(Tq), // Qubit represented with +1, 0
 	Spin(Tq),   // Qubit represented with +1, -1
-	Constraint { label: Tc, expr: Box<Self> },
-	WithPenalty { expr: Box<Self>, penalty: Box<Self> },
+	Constraint {
+		label: Tc,
+		expr: Box<Self>,
+	},
+	/// Constraint whose penalty is multiplied by `weight`, which should be
+	/// positive. The adaptive penalty strength is applied on top of it.
+	WeightedConstraint {
+		label: Tc,
+		expr: Box<Self>,
+		weight: R,
+	},
+	/// Constraint `expr == 0` with the fixed penalty `penalty * expr^2`, which
+	/// is added to the objective instead of being tuned adaptively. It is
+	/// reported as unsatisfied like [`Expr::Constraint`]. See
+	/// [`Expr::soft_constraint`].
+	SoftConstraint {
+		label: Tc,
+		expr: Box<Self>,
+		penalty: R,
+	},
+	WithPenalty {
+		expr: Box<Self>,
+		penalty: Box<Self>,
+	},
+	/// Subexpression annotated with `label` for debugging, which is the same
+	/// as `expr` in the compilation. See [`Expr::find_by_label`].
+	WithLabel {
+		label: String,
+		expr: Box<Self>,
+	},
 }
 
 impl<Tp, Tq, Tc, R> Expr<Tp, Tq, Tc, R>
@@ -43,6 +73,107 @@ where
 		Self::Number(R::from_i32(1))
 	}
 
+	/// Create the constraint that exactly `k` of `qubits` are 1, whose
+	/// expression is `(sum(qubits) - k)^2`.
+	///
+	/// # Panics
+	///
+	/// Panics if `k` is not in `0..=qubits.len()`, because the constraint
+	/// could never be satisfied.
+	pub fn sum_equals(label: Tc, qubits: Vec<Tq>, k: i32) -> Self {
+		assert!(
+			0 <= k && k as usize <= qubits.len(),
+			"sum_equals: k = {} must be in 0..={} for {} qubits",
+			k,
+			qubits.len(),
+			qubits.len()
+		);
+		let sum = qubits
+			.into_iter()
+			.fold(Self::Number(R::from_i32(-k)), |e, q| e + Self::Binary(q));
+		Self::Constraint {
+			label,
+			expr: Box::new(sum ^ 2),
+		}
+	}
+
+	/// Create the quadratic form `sum(matrix[i][j] * x_i * x_j)` for `i <= j`,
+	/// where `x_i` is the binary qubit `qubits[i]`. The entries below the
+	/// diagonal are ignored unless `symmetric` is true, in which case the
+	/// matrix `A` is symmetrized as `(A + A^T) / 2`, i.e. `matrix[i][j]` and
+	/// `matrix[j][i]` are averaged into one coupling. This is the convention
+	/// of `FixedSingleQuadricModel::from_matrix_market` for `general`
+	/// matrices, so a symmetric matrix gives the same model either way.
+	///
+	/// # Panics
+	///
+	/// Panics if `matrix` is not `n x n` for `n` qubits.
+	pub fn quadratic_form(qubits: Vec<Tq>, matrix: Vec<Vec<R>>, symmetric: bool) -> Self {
+		let n = qubits.len();
+		assert!(
+			matrix.len() == n && matrix.iter().all(|row| row.len() == n),
+			"quadratic_form: the matrix must be {} x {}",
+			n,
+			n
+		);
+		let mut ret = Self::zero();
+		for i in 0..n {
+			for j in i..n {
+				let w = if i == j {
+					matrix[i][i]
+				} else if symmetric {
+					(matrix[i][j] + matrix[j][i]) / R::from_i32(2)
+				} else {
+					matrix[i][j]
+				};
+				if w == R::zero() {
+					continue;
+				}
+				ret += if i == j {
+					Self::Number(w) * Self::Binary(qubits[i].clone())
+				} else {
+					Self::Number(w)
+						* Self::Binary(qubits[i].clone())
+						* Self::Binary(qubits[j].clone())
+				};
+			}
+		}
+		ret
+	}
+
+	/// Create the polynomial `sum(w * x_1 * x_2 * ...)` of the binary qubits from
+	/// the monomials `terms`, whose keys are the qubits of the products. The
+	/// empty key is the constant term. The keys are sorted and deduplicated
+	/// since `x^2 = x`, so the order of the qubits in the keys does not matter.
+	pub fn from_polynomial(terms: HashMap<Vec<Tq>, R>) -> Self {
+		let mut monomials = BTreeMap::new();
+		for (mut qubits, w) in terms.into_iter() {
+			qubits.sort();
+			qubits.dedup();
+			*monomials.entry(qubits).or_insert_with(R::zero) += w;
+		}
+		monomials
+			.into_iter()
+			.filter(|(_, w)| *w != R::zero())
+			.map(|(qubits, w)| {
+				qubits
+					.into_iter()
+					.fold(Self::Number(w), |e, q| e * Self::Binary(q))
+			})
+			.collect::<ExprBuilder<_, _, _, _>>()
+			.finish()
+	}
+
+	/// Create [`Expr::SoftConstraint`], which requires `expr == 0` with the
+	/// fixed penalty `penalty * expr^2`.
+	pub fn soft_constraint(label: Tc, expr: Self, penalty: R) -> Self {
+		Self::SoftConstraint {
+			label,
+			expr: Box::new(expr),
+			penalty,
+		}
+	}
+
 	pub fn map<F>(self, f: &mut F) -> Self
 	where
 		F: FnMut(Self) -> Self,
@@ -54,13 +185,109 @@ where
 				label,
 				expr: Box::new(expr.map(f)),
 			},
+			Self::WeightedConstraint {
+				label,
+				expr,
+				weight,
+			} => Self::WeightedConstraint {
+				label,
+				expr: Box::new(expr.map(f)),
+				weight,
+			},
+			Self::SoftConstraint {
+				label,
+				expr,
+				penalty,
+			} => Self::SoftConstraint {
+				label,
+				expr: Box::new(expr.map(f)),
+				penalty,
+			},
 			Self::WithPenalty { expr, penalty } => Self::WithPenalty {
 				expr: Box::new(expr.map(f)),
 				penalty: Box::new(penalty.map(f)),
 			},
+			Self::WithLabel { label, expr } => Self::WithLabel {
+				label,
+				expr: Box::new(expr.map(f)),
+			},
 			o => o,
 		}
 	}
+
+	/// The labels of all the qubits, either [`Expr::Binary`] or
+	/// [`Expr::Spin`], in the expression.
+	pub fn variables(&self) -> BTreeSet<&Tq> {
+		let mut ret = BTreeSet::new();
+		self.visit_leaves(&mut |e| {
+			if let Self::Binary(q) | Self::Spin(q) = e {
+				ret.insert(q);
+			}
+		});
+		ret
+	}
+
+	/// The labels of all the placeholders in the expression.
+	pub fn placeholders(&self) -> BTreeSet<&Tp> {
+		let mut ret = BTreeSet::new();
+		self.visit_leaves(&mut |e| {
+			if let Self::Placeholder(p) = e {
+				ret.insert(p);
+			}
+		});
+		ret
+	}
+
+	fn visit_leaves<'a, F: FnMut(&'a Self)>(&'a self, f: &mut F) {
+		match self {
+			Self::Add(a, b)
+			| Self::Mul(a, b)
+			| Self::WithPenalty {
+				expr: a,
+				penalty: b,
+			} => {
+				a.visit_leaves(f);
+				b.visit_leaves(f);
+			}
+			Self::Constraint { expr, .. }
+			| Self::WeightedConstraint { expr, .. }
+			| Self::SoftConstraint { expr, .. }
+			| Self::WithLabel { expr, .. } => expr.visit_leaves(f),
+			Self::Placeholder(_) | Self::Number(_) | Self::Binary(_) | Self::Spin(_) => f(self),
+		}
+	}
+
+	/// Find the subexpressions annotated with `label` by [`Expr::WithLabel`],
+	/// in the depth-first order.
+	pub fn find_by_label(&self, label: &str) -> Vec<&Self> {
+		let mut ret = Vec::new();
+		self.find_by_label_inner(label, &mut ret);
+		ret
+	}
+
+	fn find_by_label_inner<'a>(&'a self, label: &str, ret: &mut Vec<&'a Self>) {
+		match self {
+			Self::Add(a, b)
+			| Self::Mul(a, b)
+			| Self::WithPenalty {
+				expr: a,
+				penalty: b,
+			} => {
+				a.find_by_label_inner(label, ret);
+				b.find_by_label_inner(label, ret);
+			}
+			Self::Constraint { expr, .. }
+			| Self::WeightedConstraint { expr, .. }
+			| Self::SoftConstraint { expr, .. } => expr.find_by_label_inner(label, ret),
+			Self::WithLabel { label: lb, expr } => {
+				if lb == label {
+					ret.push(expr);
+				}
+				expr.find_by_label_inner(label, ret);
+			}
+			Self::Placeholder(_) | Self::Number(_) | Self::Binary(_) | Self::Spin(_) => (),
+		}
+	}
 	pub fn feed_dict(self, dict: &HashMap<Tp, R>) -> Self {
 		match self {
 			Self::Placeholder(p) => {
@@ -78,6 +305,10 @@ where
 				Box::new((*a).feed_dict(dict)),
 				Box::new((*b).feed_dict(dict)),
 			),
+			Self::WithLabel { label, expr } => Self::WithLabel {
+				label,
+				expr: Box::new(expr.feed_dict(dict)),
+			},
 			o => o,
 		}
 	}
@@ -119,18 +350,52 @@ where
 					None
 				}
 			}
-			Self::Constraint { label: _, expr: e } => e.calculate(map),
+			Self::Constraint { label: _, expr: e }
+			| Self::WeightedConstraint {
+				label: _,
+				expr: e,
+				weight: _,
+			} => e.calculate(map),
+			Self::SoftConstraint {
+				label: _,
+				expr: e,
+				penalty,
+			} => e.calculate(map).map(|v| *penalty * v * v),
 			Self::WithPenalty {
 				expr: e,
 				penalty: _,
 			} => e.calculate(map),
+			Self::WithLabel { label: _, expr: e } => e.calculate(map),
 		}
 	}
 
+	/// Evaluate the expression with the given assignment of qubits. Returns
+	/// `None` if some qubit is not assigned or a placeholder remains.
+	pub fn evaluate(&self, assignment: &HashMap<Tq, bool>) -> Option<R> {
+		let map = assignment.iter().map(|(k, v)| (k, *v)).collect();
+		self.calculate(&map)
+	}
+
 	pub fn compile(self) -> CompiledModel<Tp, Tq, Tc, R> {
 		self.to_model().to_compiled().reduce_order(2)
 	}
 
+	/// Compile the expression into a PUBO with 3-body interactions. See
+	/// [`CompiledModel::reduce_to_pubo`].
+	pub fn compile_to_pubo(self) -> CompiledModel<Tp, Tq, Tc, R> {
+		self.to_model().to_compiled().reduce_to_pubo()
+	}
+
+	/// Compile the expression, reducing the order with the given strategy.
+	pub fn compile_with_strategy(
+		self,
+		strategy: ReductionStrategy,
+	) -> CompiledModel<Tp, Tq, Tc, R> {
+		self.to_model()
+			.to_compiled()
+			.reduce_order_with_strategy(2, strategy)
+	}
+
 	#[allow(unused)] // TODO: ?
 	fn map_number<R2: ConvertFrom<R>>(self) -> Expr<Tp, Tq, Tc, R2> {
 		match self {
@@ -141,10 +406,32 @@ where
 				label,
 				expr: Box::new(expr.map_number()),
 			},
+			Self::WeightedConstraint {
+				label,
+				expr,
+				weight,
+			} => Expr::WeightedConstraint {
+				label,
+				expr: Box::new(expr.map_number()),
+				weight: <R2 as ConvertFrom<R>>::convert_from(weight),
+			},
+			Self::SoftConstraint {
+				label,
+				expr,
+				penalty,
+			} => Expr::SoftConstraint {
+				label,
+				expr: Box::new(expr.map_number()),
+				penalty: <R2 as ConvertFrom<R>>::convert_from(penalty),
+			},
 			Self::WithPenalty { expr, penalty } => Expr::WithPenalty {
 				expr: Box::new(expr.map_number()),
 				penalty: Box::new(penalty.map_number()),
 			},
+			Self::WithLabel { label, expr } => Expr::WithLabel {
+				label,
+				expr: Box::new(expr.map_number()),
+			},
 			Self::Placeholder(a) => Expr::Placeholder(a),
 			Self::Binary(a) => Expr::Binary(a),
 			Self::Spin(a) => Expr::Spin(a),
@@ -175,7 +462,13 @@ where
 			Self::Number(n) => Expr::Number(n),
 			Self::Binary(lb) => Expr::Binary(fq(lb)),
 			Self::Spin(lb) => Expr::Spin(fq(lb)),
+			Self::WithLabel { label, expr } => Expr::WithLabel {
+				label,
+				expr: Box::new(expr.map_label(fp, fq)),
+			},
 			Self::Constraint { label: _, expr: _ }
+			| Self::WeightedConstraint { .. }
+			| Self::SoftConstraint { .. }
 			| Self::WithPenalty {
 				expr: _,
 				penalty: _,
@@ -203,10 +496,135 @@ where
 					Some(Placeholder::Constraint(lb)),
 				)
 			}
+			Self::WeightedConstraint {
+				label: lb,
+				expr: e,
+				weight,
+			} => {
+				let ph: Model<Tp, Tq, Tc, R> =
+					Model::from(StaticExpr::Placeholder(Placeholder::Constraint(lb.clone())));
+				(e.clone().to_model() * Model::from(StaticExpr::Number(weight)) * ph)
+					.add_constraint(lb.clone(), *e, Some(Placeholder::Constraint(lb)))
+			}
+			Self::SoftConstraint {
+				label: lb,
+				expr: e,
+				penalty,
+			} => {
+				// The placeholder marks the penalty, whose strength is not tuned.
+				let ph: Model<Tp, Tq, Tc, R> =
+					Model::from(StaticExpr::Placeholder(Placeholder::Soft(lb.clone())));
+				let m = e.clone().to_model();
+				(m.clone() * m * Model::from(StaticExpr::Number(penalty)) * ph).add_constraint(
+					lb.clone(),
+					*e,
+					Some(Placeholder::Soft(lb)),
+				)
+			}
 			Self::WithPenalty {
 				expr: e,
 				penalty: p,
 			} => e.to_model().add_penalty(p.to_model()),
+			Self::WithLabel { label: _, expr: e } => e.to_model(),
+		}
+	}
+}
+
+/// Builder of the sum of many terms. `a + b + c + ...` makes a tree of
+/// [`Expr::Add`] as deep as the number of the terms, which overflows the stack
+/// in the compilation of large models. [`ExprBuilder::finish`] makes a balanced
+/// tree, whose depth is logarithmic in the number of the terms.
+#[derive(Clone, Debug)]
+pub struct ExprBuilder<Tp, Tq, Tc, R>
+where
+	Tp: TpType,
+	Tq: TqType,
+	Tc: TcType,
+	R: Real,
+{
+	terms: Vec<Expr<Tp, Tq, Tc, R>>,
+}
+
+impl<Tp, Tq, Tc, R> ExprBuilder<Tp, Tq, Tc, R>
+where
+	Tp: TpType,
+	Tq: TqType,
+	Tc: TcType,
+	R: Real,
+{
+	pub fn new() -> Self {
+		Self { terms: Vec::new() }
+	}
+
+	/// Add `term` to the sum.
+	pub fn push(&mut self, term: Expr<Tp, Tq, Tc, R>) -> &mut Self {
+		self.terms.push(term);
+		self
+	}
+
+	/// The number of the terms.
+	pub fn len(&self) -> usize {
+		self.terms.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.terms.is_empty()
+	}
+
+	/// The sum of the terms as a balanced tree, or `0` if there is no term.
+	pub fn finish(self) -> Expr<Tp, Tq, Tc, R> {
+		let mut terms = self.terms;
+		// Add the adjacent pairs until one term remains, which halves the
+		// terms without recursion.
+		while terms.len() > 1 {
+			let mut it = terms.into_iter();
+			let mut next = Vec::with_capacity(it.len() / 2 + 1);
+			while let Some(a) = it.next() {
+				next.push(match it.next() {
+					Some(b) => Expr::Add(Box::new(a), Box::new(b)),
+					None => a,
+				});
+			}
+			terms = next;
+		}
+		terms.pop().unwrap_or_else(|| Expr::Number(R::from_i32(0)))
+	}
+}
+
+impl<Tp, Tq, Tc, R> Default for ExprBuilder<Tp, Tq, Tc, R>
+where
+	Tp: TpType,
+	Tq: TqType,
+	Tc: TcType,
+	R: Real,
+{
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<Tp, Tq, Tc, R> Extend<Expr<Tp, Tq, Tc, R>> for ExprBuilder<Tp, Tq, Tc, R>
+where
+	Tp: TpType,
+	Tq: TqType,
+	Tc: TcType,
+	R: Real,
+{
+	fn extend<I: IntoIterator<Item = Expr<Tp, Tq, Tc, R>>>(&mut self, iter: I) {
+		self.terms.extend(iter);
+	}
+}
+
+impl<Tp, Tq, Tc, R> std::iter::FromIterator<Expr<Tp, Tq, Tc, R>> for ExprBuilder<Tp, Tq, Tc, R>
+where
+	Tp: TpType,
+	Tq: TqType,
+	Tc: TcType,
+	R: Real,
+{
+	fn from_iter<I: IntoIterator<Item = Expr<Tp, Tq, Tc, R>>>(iter: I) -> Self {
+		Self {
+			terms: iter.into_iter().collect(),
 		}
 	}
 }
@@ -384,6 +802,34 @@ impl_binary_op!(i128);
 impl_binary_op!(f32);
 impl_binary_op!(f64);
 
+/// Division by a scalar. It multiplies the expression by the reciprocal of
+/// `other`, so only scalar division is supported (not division by
+/// expressions).
+///
+/// # Panics
+/// Panics if `other` is zero, or if `R` is an integer type and `other` is not
+/// `1` or `-1`, whose reciprocal would be truncated.
+impl<Tp, Tq, Tc, R> Div<R> for Expr<Tp, Tq, Tc, R>
+where
+	Tp: TpType,
+	Tq: TqType,
+	Tc: TcType,
+	R: Real,
+{
+	type Output = Self;
+	#[inline]
+	fn div(self, other: R) -> Self {
+		if other == R::zero() {
+			panic!("division by zero");
+		}
+		let inv = R::one() / other;
+		if R::from_f64(0.5) == R::zero() && inv * other != R::one() {
+			panic!("{} has no reciprocal in the integer type", other);
+		}
+		Expr::Mul(Box::new(self), Box::new(Expr::Number(inv)))
+	}
+}
+
 impl<Tp, Tq, Tc, R> BitXor<usize> for Expr<Tp, Tq, Tc, R>
 where
 	Tp: TpType,
@@ -432,6 +878,7 @@ impl_assign_op!(MulAssign, Mul, mul_assign, mul, Expr<Tp, Tq, Tc, R>, Expr<Tp, T
 impl_assign_op!(AddAssign, Add, add_assign, add, R, Expr<Tp, Tq, Tc, R>);
 impl_assign_op!(SubAssign, Sub, sub_assign, sub, R, Expr<Tp, Tq, Tc, R>);
 impl_assign_op!(MulAssign, Mul, mul_assign, mul, R, Expr<Tp, Tq, Tc, R>);
+impl_assign_op!(DivAssign, Div, div_assign, div, R, R);
 impl_assign_op!(BitXorAssign, BitXor, bitxor_assign, bitxor, usize, usize);
 
 #[derive(PartialEq, Clone, Debug)]
@@ -700,3 +1147,181 @@ where
 // 		Self(NumberOrFloatInner::Float(f))
 // 	}
 // }
+
+#[test]
+fn div_test() {
+	fn qubo_text(expr: Expr<(), &'static str, (), f64>) -> String {
+		let mut buf = Vec::new();
+		expr.compile().write_qubo_text(&mut buf).unwrap();
+		String::from_utf8(buf).unwrap()
+	}
+	let expr = || 3.0 * Expr::Binary("a") * Expr::Binary("b") - Expr::Spin("c") + 1.0;
+	assert_eq!(qubo_text(expr() / 2.0), qubo_text(expr() * 0.5));
+	let mut e = expr();
+	e /= 2.0;
+	assert_eq!(qubo_text(e), qubo_text(expr() * 0.5));
+}
+
+#[test]
+fn integer_div_test() {
+	let expr: Expr<(), _, (), i32> = 3 * Expr::Binary("a") + 2;
+	let map = vec![(&"a", true)].into_iter().collect();
+	assert_eq!((expr.clone() / -1).calculate(&map), Some(-5));
+	assert!(std::panic::catch_unwind(|| expr / 2).is_err());
+}
+
+#[test]
+fn with_label_test() {
+	fn qubo_text(expr: Expr<(), &'static str, (), f64>) -> String {
+		let mut buf = Vec::new();
+		expr.compile().write_qubo_text(&mut buf).unwrap();
+		String::from_utf8(buf).unwrap()
+	}
+	fn with_label(
+		label: &str,
+		expr: Expr<(), &'static str, (), f64>,
+	) -> Expr<(), &'static str, (), f64> {
+		Expr::WithLabel {
+			label: label.to_owned(),
+			expr: Box::new(expr),
+		}
+	}
+	let cost = || 3.0 * Expr::Binary("a") * Expr::Binary("b");
+	let spin = || (-Expr::Spin("c") + 1.0) * with_label("cost", Expr::Binary("d"));
+	let labeled = with_label("cost", cost()) + with_label("spin", spin());
+	assert_eq!(
+		labeled.find_by_label("cost"),
+		vec![&cost(), &Expr::Binary("d")]
+	);
+	assert_eq!(labeled.find_by_label("spin"), vec![&spin()]);
+	assert!(labeled.find_by_label("none").is_empty());
+	let assignment = vec![("a", true), ("b", true), ("c", false), ("d", true)]
+		.into_iter()
+		.collect();
+	assert_eq!(labeled.evaluate(&assignment), Some(5.0));
+	assert_eq!(
+		qubo_text(labeled),
+		qubo_text(cost() + (-Expr::Spin("c") + 1.0) * Expr::Binary("d"))
+	);
+}
+
+#[test]
+fn sum_equals_test() {
+	let expr: Expr<(), &str, &str, f64> = Expr::sum_equals("two", vec!["a", "b", "c"], 2);
+	let sum = Expr::Number(-2.0) + Expr::Binary("a") + Expr::Binary("b") + Expr::Binary("c");
+	assert_eq!(
+		expr,
+		Expr::Constraint {
+			label: "two",
+			expr: Box::new(sum ^ 2),
+		}
+	);
+	let assignment = |v: [bool; 3]| {
+		vec![("a", v[0]), ("b", v[1]), ("c", v[2])]
+			.into_iter()
+			.collect()
+	};
+	assert_eq!(expr.evaluate(&assignment([true, false, true])), Some(0.0));
+	assert_eq!(expr.evaluate(&assignment([true, true, true])), Some(1.0));
+	assert_eq!(expr.evaluate(&assignment([false, false, false])), Some(4.0));
+	let out_of_range = std::panic::catch_unwind(|| {
+		Expr::<(), &str, &str, f64>::sum_equals("four", vec!["a", "b", "c"], 4)
+	});
+	assert!(out_of_range.is_err());
+}
+
+#[test]
+fn quadratic_form_test() {
+	fn qubo_text(expr: Expr<(), &'static str, (), f64>) -> String {
+		let mut buf = Vec::new();
+		expr.compile().write_qubo_text(&mut buf).unwrap();
+		String::from_utf8(buf).unwrap()
+	}
+	let b = Expr::Binary;
+	let expected = || 1.0 * b("a") - 2.0 * b("a") * b("b") + 3.0 * b("b") * b("c") - b("c");
+	let upper = vec![
+		vec![1.0, -2.0, 0.0],
+		vec![5.0, 0.0, 3.0],
+		vec![0.0, 0.0, -1.0],
+	];
+	let upper = Expr::quadratic_form(vec!["a", "b", "c"], upper, false);
+	let symmetric = vec![
+		vec![1.0, -2.0, 0.0],
+		vec![-2.0, 0.0, 3.0],
+		vec![0.0, 3.0, -1.0],
+	];
+	let symmetric = Expr::quadratic_form(vec!["a", "b", "c"], symmetric, true);
+	let assignment = vec![("a", true), ("b", true), ("c", true)]
+		.into_iter()
+		.collect();
+	assert_eq!(upper.evaluate(&assignment), Some(1.0));
+	assert_eq!(symmetric.evaluate(&assignment), Some(1.0));
+	assert_eq!(qubo_text(upper), qubo_text(expected()));
+	assert_eq!(qubo_text(symmetric), qubo_text(expected()));
+}
+
+#[test]
+fn expr_builder_test() {
+	let n = 20_000usize;
+	let builder = (0..n)
+		.map(|i| Expr::Number((i % 7 + 1) as f64) * Expr::Binary(i))
+		.collect::<ExprBuilder<(), usize, (), f64>>();
+	assert_eq!(builder.len(), n);
+	let expr = builder.finish();
+	fn depth<Tp: TpType, Tq: TqType, Tc: TcType, R: Real>(e: &Expr<Tp, Tq, Tc, R>) -> usize {
+		match e {
+			Expr::Add(a, b) => 1 + depth(a).max(depth(b)),
+			_ => 0,
+		}
+	}
+	assert_eq!(depth(&expr), 15);
+	let assignment = (0..n).map(|i| (i, i % 2 == 0)).collect::<HashMap<_, _>>();
+	let expected = (0..n)
+		.filter(|i| i % 2 == 0)
+		.map(|i| (i % 7 + 1) as f64)
+		.sum::<f64>();
+	assert_eq!(expr.evaluate(&assignment), Some(expected));
+	assert_eq!(expr.compile().qubits_indexed().len(), n);
+
+	let mut builder = ExprBuilder::new();
+	builder.push(Expr::Binary("a")).push(Expr::Binary("b"));
+	builder.extend(vec![Expr::Number(1.0)]);
+	assert_eq!(
+		builder.finish(),
+		(Expr::Binary("a") + Expr::Binary("b")) + Expr::<(), _, (), f64>::Number(1.0)
+	);
+	assert_eq!(
+		ExprBuilder::<(), &str, (), f64>::new().finish(),
+		Expr::zero()
+	);
+}
+
+#[test]
+fn from_polynomial_test() {
+	let b = Expr::Binary;
+	let terms = vec![
+		(vec![], 1.5),
+		(vec!["a"], 2.0),
+		(vec!["b", "a"], -3.0),
+		(vec!["a", "b"], 1.0),
+		(vec!["c", "a", "b"], 4.0),
+		(vec!["c", "c"], -1.0),
+	]
+	.into_iter()
+	.collect::<HashMap<_, _>>();
+	let expr: Expr<(), _, (), f64> = Expr::from_polynomial(terms);
+	let expected: Expr<(), _, (), f64> =
+		1.5 + 2.0 * b("a") - 2.0 * b("a") * b("b") + 4.0 * b("a") * b("b") * b("c") - b("c");
+	for bits in 0..8 {
+		let assignment = ["a", "b", "c"]
+			.iter()
+			.enumerate()
+			.map(|(i, q)| (*q, bits >> i & 1 == 1))
+			.collect();
+		assert_eq!(expr.evaluate(&assignment), expected.evaluate(&assignment));
+	}
+	assert_eq!(
+		Expr::<(), &str, (), f64>::from_polynomial(HashMap::new()),
+		Expr::zero()
+	);
+}
@@ -10,7 +10,7 @@
 //! # use rustqubo::solve::SimpleSolver;
 //! let hmlt = -Expr::Spin("a") * Expr::Spin("b") * Expr::Number(2) + Expr::Spin("a") * Expr::Number(3);
 //! let compiled = hmlt.compile();
-//! let solver = SimpleSolver::new(&compiled);
+//! let solver = SimpleSolver::new(&compiled).with_seed(0);
 //! let (c, qubits) = solver.solve().unwrap();
 //! // displays -5.0, {"a": false, "b": false}
 //! println!("{}, {:?}", &c, &qubits);
@@ -29,7 +29,7 @@
 //! 		expr: Box::new((Expr::Binary(0) + Expr::Binary(1) - Expr::Number(1)) ^ 2usize)
 //! 	} + Expr::Binary(0) * Expr::Number(30);
 //! let compiled = hmlt.compile();
-//! let solver = SimpleSolver::new(&compiled);
+//! let solver = SimpleSolver::new(&compiled).with_seed(0);
 //! let (c, qubits, unsatisfied) = solver.solve_with_constraints().unwrap();
 //! // displays 0, {0: false, 1: true}, []
 //! println!("{}, {:?}, {:?}", &c, &qubits, &unsatisfied);
@@ -37,6 +37,10 @@
 //! # assert_eq!(qubits.get(&0), Some(false));
 //! # assert_eq!(qubits.get(&1), Some(true));
 //! # assert_eq!(unsatisfied.len(), 0);
+//! // `c` is the energy of the reduced QUBO. Use `solve_verified()` to also get
+//! // the energy of the objective without the penalties of the constraints.
+//! let (_, objective, qubits, _) = solver.solve_verified().unwrap();
+//! # assert_eq!(objective, if qubits[&0] { 30 } else { 0 });
 //! ```
 use std::cmp::Ord;
 use std::fmt::Debug;
@@ -61,6 +65,7 @@ impl<T> TcType for T where T: LabelType + Hash + Ord {}
 
 // mod anneal;
 mod compiled;
+pub mod embedding;
 mod expanded;
 mod expr;
 mod model;
@@ -72,7 +77,10 @@ mod wrapper;
 #[cfg(feature = "python")]
 pub mod python;
 
-pub use expr::Expr;
+pub use compiled::{EmbeddedModel, ReductionStrategy};
+pub use expr::{Expr, ExprBuilder};
+pub use model::Constraint;
+pub use wrapper::Qubit;
 
 #[test]
 fn expr_test() {
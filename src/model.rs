@@ -60,7 +60,11 @@ where
 	}
 
 	pub fn to_compiled(self) -> CompiledModel<Tp, Tq, Tc, R> {
-		CompiledModel::new(self.expanded + self.penalties, self.constraints)
+		CompiledModel::new(
+			self.expanded.clone() + self.penalties,
+			self.constraints,
+			self.expanded,
+		)
 	}
 }
 
@@ -116,6 +120,8 @@ where
 	}
 }
 
+/// A constraint of a compiled model, e.g. returned by `get_constraint()` of
+/// the model.
 #[derive(Clone, Debug)]
 pub struct Constraint<Tp, Tq, Tc, R>
 where
@@ -164,13 +170,40 @@ where
 	}
 
 	pub fn is_satisfied(&self, map: &HashMap<&Qubit<Tq>, bool>) -> bool {
+		self.is_satisfied_within(map, 1.0e-4)
+	}
+
+	/// Whether the absolute value of the constraint expression is at most
+	/// `tol`, so a tolerance of zero requires the exact value. The constraint
+	/// is satisfied if the expression cannot be calculated.
+	pub fn is_satisfied_within(&self, map: &HashMap<&Qubit<Tq>, bool>, tol: f64) -> bool {
 		if let Some(i) = self.expr.calculate(map) {
-			i.as_f64().abs() < 1.0e-4
+			i.as_f64().abs() <= tol
 		} else {
 			true
 		}
 	}
 
+	/// The absolute value of the constraint expression, which is 0 if the
+	/// constraint is satisfied. The expression is the penalty of the
+	/// constraint, so this is the penalty energy at unit strength, e.g. the
+	/// square of the number of extra bits for `(sum - 1)^2`. Returns `None` if
+	/// the expression cannot be calculated, e.g. it has placeholders.
+	pub fn violation(&self, map: &HashMap<&Qubit<Tq>, bool>) -> Option<R> {
+		self.expr.calculate(map).map(|v| v.abs())
+	}
+
+	/// Substitute `value` for the qubit `q` in the constraint expression.
+	pub(crate) fn fix_qubit(mut self, q: &Qubit<Tq>, value: bool) -> Self {
+		let (binary, spin) = if value { (1, 1) } else { (0, -1) };
+		self.expr = self.expr.map(&mut |e| match e {
+			Expr::Binary(lb) if &lb == q => Expr::Number(R::from_i32(binary)),
+			Expr::Spin(lb) if &lb == q => Expr::Number(R::from_i32(spin)),
+			e => e,
+		});
+		self
+	}
+
 	pub fn feed_dict(mut self, dict: &HashMap<Placeholder<Tp, Tc>, R>) -> Self {
 		self.expr = self.expr.feed_dict(dict);
 		if let Some(p) = &self.placeholder {
@@ -1,6 +1,241 @@
+// `#[pymethods]` of pyo3 0.17 expands to impl blocks inside functions.
+#![allow(non_local_definitions)]
+
+use crate::compiled::CompiledModel;
+use crate::expr::Expr;
+use crate::solve::SimpleSolver;
+use crate::wrapper::Qubit;
+use annealers::model::SingleModelView;
+use numpy::ndarray::Array2;
+use numpy::{IntoPyArray, PyArray2};
+use pyo3::exceptions::PyRuntimeError;
 use pyo3::prelude::*;
+use std::collections::HashMap;
+
+type ExprType = Expr<String, String, String, f64>;
+
+/// Operand of the arithmetic operators of [`PyExpr`].
+#[derive(FromPyObject)]
+enum Operand {
+	Expr(PyExpr),
+	Number(f64),
+}
+
+impl From<Operand> for ExprType {
+	fn from(op: Operand) -> Self {
+		match op {
+			Operand::Expr(e) => e.0,
+			Operand::Number(n) => Expr::Number(n),
+		}
+	}
+}
+
+/// Python wrapper of [`Expr`], whose labels are strings.
+#[pyclass(name = "Expr")]
+#[derive(Clone)]
+pub struct PyExpr(ExprType);
+
+#[pymethods]
+impl PyExpr {
+	#[staticmethod]
+	fn binary(name: &str) -> Self {
+		Self(Expr::Binary(name.to_owned()))
+	}
+
+	#[staticmethod]
+	fn spin(name: &str) -> Self {
+		Self(Expr::Spin(name.to_owned()))
+	}
+
+	#[staticmethod]
+	fn number(val: f64) -> Self {
+		Self(Expr::Number(val))
+	}
+
+	#[staticmethod]
+	fn placeholder(name: &str) -> Self {
+		Self(Expr::Placeholder(name.to_owned()))
+	}
+
+	#[staticmethod]
+	fn constraint(label: &str, expr: PyExpr) -> Self {
+		Self(Expr::Constraint {
+			label: label.to_owned(),
+			expr: Box::new(expr.0),
+		})
+	}
+
+	fn __add__(&self, other: Operand) -> Self {
+		Self(self.0.clone() + ExprType::from(other))
+	}
+
+	fn __radd__(&self, other: Operand) -> Self {
+		Self(ExprType::from(other) + self.0.clone())
+	}
+
+	fn __sub__(&self, other: Operand) -> Self {
+		Self(self.0.clone() - ExprType::from(other))
+	}
+
+	fn __rsub__(&self, other: Operand) -> Self {
+		Self(ExprType::from(other) - self.0.clone())
+	}
+
+	fn __mul__(&self, other: Operand) -> Self {
+		Self(self.0.clone() * ExprType::from(other))
+	}
+
+	fn __rmul__(&self, other: Operand) -> Self {
+		Self(ExprType::from(other) * self.0.clone())
+	}
+
+	fn __neg__(&self) -> Self {
+		Self(-self.0.clone())
+	}
+
+	fn __pow__(&self, exp: usize, _modulo: Option<&PyAny>) -> Self {
+		Self(self.0.clone() ^ exp)
+	}
+
+	fn __repr__(&self) -> String {
+		format!("{:?}", self.0)
+	}
+
+	fn compile(&self) -> PyCompiledModel {
+		PyCompiledModel {
+			model: self.0.clone().compile(),
+			dict: HashMap::new(),
+		}
+	}
+}
+
+/// Python wrapper of [`CompiledModel`].
+#[pyclass(name = "CompiledModel")]
+#[derive(Clone)]
+pub struct PyCompiledModel {
+	model: CompiledModel<String, String, String, f64>,
+	/// The values of the placeholders given by `feed_dict()`.
+	dict: HashMap<String, f64>,
+}
+
+impl PyCompiledModel {
+	fn fed(&self, dict: HashMap<String, f64>) -> CompiledModel<(), String, String, f64> {
+		let mut dict = dict;
+		for (k, v) in self.dict.iter() {
+			dict.entry(k.clone()).or_insert(*v);
+		}
+		self.model.clone().feed_dict(dict)
+	}
+}
+
+#[pymethods]
+impl PyCompiledModel {
+	/// Return the model whose placeholders are filled with `dict`.
+	fn feed_dict(&self, dict: HashMap<String, f64>) -> Self {
+		let mut ret = self.clone();
+		ret.dict.extend(dict);
+		ret
+	}
+
+	/// Solve the model with the default parameters of [`SimpleSolver`] after
+	/// filling the placeholders with `feed_dict`. See [`PySimpleSolver::solve`]
+	/// for the return value.
+	fn solve(
+		&self,
+		py: Python<'_>,
+		feed_dict: Option<HashMap<String, f64>>,
+	) -> PyResult<(f64, HashMap<String, bool>, Vec<String>)> {
+		PySimpleSolver::new(self, feed_dict).solve(py)
+	}
+
+	/// Returns the `n x n` upper-triangular QUBO matrix filled with
+	/// `placeholder_values` and the labels of its rows and columns. Ancilla
+	/// qubits follow the user qubits and are labeled as `_ancilla<n>`.
+	/// Constraint placeholders are evaluated with unit strength and the
+	/// constant offset is dropped.
+	fn to_numpy_qubo(
+		&self,
+		py: Python<'_>,
+		placeholder_values: HashMap<String, f64>,
+	) -> (Py<PyArray2<f64>>, Vec<String>) {
+		let model = self.fed(placeholder_values);
+		let qubits = model.get_qubits().into_iter().collect::<Vec<_>>();
+		let (_, qubo) = model.generate_qubo(&qubits, &mut |_| 1.0);
+		let mut matrix = Array2::zeros((qubits.len(), qubits.len()));
+		for p in qubo.prods() {
+			let (i, j) = (p[0], *p.last().unwrap());
+			matrix[[i, j]] = qubo.get_weight(&p);
+		}
+		let labels = qubits
+			.into_iter()
+			.map(|q| match q {
+				Qubit::Qubit(s) => s.clone(),
+				Qubit::Ancilla(n) => format!("_ancilla{}", n),
+			})
+			.collect();
+		(matrix.into_pyarray(py).to_owned(), labels)
+	}
+}
+
+/// Python wrapper of [`SimpleSolver`], which owns the model with the
+/// placeholders filled.
+#[pyclass(name = "SimpleSolver")]
+pub struct PySimpleSolver {
+	model: CompiledModel<(), String, String, f64>,
+	#[pyo3(get, set)]
+	iterations: usize,
+	#[pyo3(get, set)]
+	samples: usize,
+	#[pyo3(get, set)]
+	generations: usize,
+	#[pyo3(get, set)]
+	coeff_strength: f64,
+}
+
+#[pymethods]
+impl PySimpleSolver {
+	#[new]
+	fn new(model: &PyCompiledModel, feed_dict: Option<HashMap<String, f64>>) -> Self {
+		let model = model.fed(feed_dict.unwrap_or_default());
+		let solver = SimpleSolver::new(&model);
+		let (iterations, samples, generations, coeff_strength) = (
+			solver.iterations,
+			solver.samples,
+			solver.generations,
+			solver.coeff_strength,
+		);
+		Self {
+			model,
+			iterations,
+			samples,
+			generations,
+			coeff_strength,
+		}
+	}
+
+	/// Returns the energy, the values of the qubits and the labels of the
+	/// unsatisfied constraints.
+	fn solve(&self, py: Python<'_>) -> PyResult<(f64, HashMap<String, bool>, Vec<String>)> {
+		let mut solver = SimpleSolver::new(&self.model);
+		solver.iterations = self.iterations;
+		solver.samples = self.samples;
+		solver.generations = self.generations;
+		solver.coeff_strength = self.coeff_strength;
+		let (energy, sol, unsatisfied) = py
+			.allow_threads(|| solver.solve_with_constraints())
+			.map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+		Ok((
+			energy,
+			sol.to_map(),
+			unsatisfied.into_iter().cloned().collect(),
+		))
+	}
+}
 
 #[pymodule]
-fn rustqubo(_py: Python<'_>, _m: &PyModule) -> PyResult<()> {
+fn rustqubo(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
+	m.add_class::<PyExpr>()?;
+	m.add_class::<PyCompiledModel>()?;
+	m.add_class::<PySimpleSolver>()?;
 	Ok(())
 }
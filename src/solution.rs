@@ -2,8 +2,9 @@ use crate::TqType;
 use annealers::node::Binary;
 use annealers::solution::SingleSolution;
 use annealers::variable::Real;
-use std::collections::HashMap;
+use std::collections::{hash_map, HashMap, HashSet};
 
+#[derive(Clone)]
 pub struct SolutionView<Tq: TqType, R: Real>(SingleSolution<Binary<R>>, HashMap<Tq, usize>);
 
 impl<Tq: TqType, R: Real> std::fmt::Debug for SolutionView<Tq, R> {
@@ -19,6 +20,24 @@ impl<Tq: TqType, R: Real> SolutionView<Tq, R> {
 		Self(sol, map)
 	}
 
+	/// Add the qubits fixed before solving, which are appended to the state.
+	pub(crate) fn with_fixed(mut self, fixed: &HashMap<Tq, bool>) -> Self {
+		if fixed.is_empty() {
+			return self;
+		}
+		let mut state = self.0.state.to_vec();
+		for (q, v) in fixed.iter() {
+			self.1.insert(q.clone(), state.len());
+			state.push(*v);
+		}
+		let mut sol = SingleSolution::from_vec(&state);
+		sol.energy = self.0.energy;
+		sol.occurrences = self.0.occurrences;
+		sol.local_field = self.0.local_field.take();
+		self.0 = sol;
+		self
+	}
+
 	pub fn occurrences(&self) -> usize {
 		self.0.occurrences
 	}
@@ -27,14 +46,76 @@ impl<Tq: TqType, R: Real> SolutionView<Tq, R> {
 		self.0.energy
 	}
 
+	/// The local field of `q`. It is `None` for the qubits fixed by
+	/// [`CompiledModel::propagate_units`].
+	///
+	/// [`CompiledModel::propagate_units`]: crate::compiled::CompiledModel::propagate_units
 	pub fn local_field(&self, q: &Tq) -> Option<R> {
-		self.0.local_field.as_ref().map(|v| v[self.1[q]])
+		self.0
+			.local_field
+			.as_ref()
+			.and_then(|v| v.get(self.1[q]).copied())
+	}
+
+	/// The qubits with their local fields, sorted by the absolute value of the
+	/// local field in descending order, i.e. from the most pinned qubit. Ties
+	/// are sorted by label. It is empty if the local fields are not known.
+	pub fn ranked_by_field(&self) -> Vec<(&Tq, R)> {
+		let mut ret = self
+			.1
+			.keys()
+			.filter_map(|q| self.local_field(q).map(|f| (q, f)))
+			.collect::<Vec<_>>();
+		ret.sort_by(|a, b| a.0.cmp(b.0));
+		ret.sort_by(|a, b| {
+			b.1.abs()
+				.partial_cmp(&a.1.abs())
+				.unwrap_or(std::cmp::Ordering::Equal)
+		});
+		ret
+	}
+
+	/// The qubits whose flipping would increase the energy by more than
+	/// `threshold`, in the order of [`Self::ranked_by_field`].
+	pub fn frozen_variables(&self, threshold: R) -> Vec<&Tq> {
+		self.ranked_by_field()
+			.into_iter()
+			.filter(|(_, f)| *f > threshold)
+			.map(|(q, _)| q)
+			.collect()
 	}
 
 	pub fn keys(&self) -> impl Iterator<Item = &Tq> {
 		self.1.keys()
 	}
 
+	/// Iterate over the pairs of the qubit label and its value.
+	pub fn iter(&self) -> Iter<'_, Tq, R> {
+		Iter(self.1.iter(), &self.0)
+	}
+
+	pub fn to_map(&self) -> HashMap<Tq, bool> {
+		self.iter().map(|(k, v)| (k.clone(), v)).collect()
+	}
+
+	/// The number of the qubits, excluding ancillas.
+	pub fn len(&self) -> usize {
+		self.1.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.1.is_empty()
+	}
+
+	/// Get the values of ancilla qubits in the order of their indices.
+	pub fn ancilla_values(&self) -> Vec<bool> {
+		let qubits = self.1.values().collect::<HashSet<_>>();
+		(0..self.0.state.len())
+			.filter(|i| !qubits.contains(i))
+			.map(|i| self.0.state.get(i))
+			.collect()
+	}
+
 	pub fn get(&self, q: &Tq) -> Option<bool> {
 		if self.1.contains_key(q) {
 			Some(self.0.state.get(self.1[q]))
@@ -42,6 +123,124 @@ impl<Tq: TqType, R: Real> SolutionView<Tq, R> {
 			None
 		}
 	}
+
+	/// Get the value of `q` as a spin, mapping `true` to `+1` and `false` to
+	/// `-1`. This is only meaningful for qubits declared with `Expr::Spin`.
+	pub fn spin(&self, q: &Tq) -> Option<i8> {
+		self.get(q).map(|v| if v { 1 } else { -1 })
+	}
+
+	/// The labels of the qubits, formatted with `Debug`, together with their
+	/// indices, sorted by label.
+	fn sorted_labels(&self) -> Vec<(String, usize)> {
+		let mut labels = self.1.iter().collect::<Vec<_>>();
+		labels.sort();
+		labels
+			.into_iter()
+			.map(|(q, i)| (format!("{:?}", q), *i))
+			.collect()
+	}
+
+	/// Render the solution as CSV, with the header `label,value` followed by
+	/// one row for each qubit sorted by label. The column `local_field` is
+	/// added if the local fields are known.
+	pub fn to_csv(&self) -> String {
+		let mut ret = String::from("label,value");
+		if self.0.local_field.is_some() {
+			ret.push_str(",local_field");
+		}
+		ret.push('\n');
+		for (label, i) in self.sorted_labels() {
+			ret.push_str(&csv_field(&label));
+			ret.push_str(if self.0.state.get(i) { ",1" } else { ",0" });
+			if let Some(field) = &self.0.local_field {
+				ret.push(',');
+				if let Some(field) = field.get(i) {
+					ret.push_str(&field.to_string());
+				}
+			}
+			ret.push('\n');
+		}
+		ret
+	}
+}
+
+impl<Tq: TqType, R: Real> SolutionView<Tq, R> {
+	/// The number of the qubits whose values differ from `other`, matched by
+	/// label. Ancilla qubits are not compared.
+	///
+	/// # Panics
+	///
+	/// Panics if the labels of the solutions differ.
+	pub fn distance(&self, other: &Self) -> usize {
+		assert!(
+			self.len() == other.len() && self.keys().all(|q| other.1.contains_key(q)),
+			"labels of the solutions differ"
+		);
+		if self.1 == other.1 && self.0.len() == self.len() && other.0.len() == self.len() {
+			// No ancillas, and the qubits share the indices
+			return annealers::solution::hamming_distance(&self.0, &other.0);
+		}
+		self.iter().filter(|(q, v)| other[q] != *v).count()
+	}
+}
+
+/// The mean of the distances of all the pairs of `solutions`, each normalized
+/// by the number of the qubits. It is `0` if there are less than two
+/// solutions.
+///
+/// # Panics
+///
+/// Panics if the labels of the solutions differ.
+pub fn diversity<Tq: TqType, R: Real>(solutions: &[SolutionView<Tq, R>]) -> f64 {
+	let mut sum = 0.0;
+	let mut pairs = 0;
+	for (i, a) in solutions.iter().enumerate() {
+		for b in solutions[i + 1..].iter() {
+			let d = a.distance(b);
+			if !a.is_empty() {
+				sum += d as f64 / a.len() as f64;
+			}
+			pairs += 1;
+		}
+	}
+	if pairs == 0 {
+		0.0
+	} else {
+		sum / pairs as f64
+	}
+}
+
+/// Quote a CSV field if it contains a separator, a quote or a line break.
+fn csv_field(s: &str) -> String {
+	if s.contains([',', '"', '\n', '\r']) {
+		format!("\"{}\"", s.replace('"', "\"\""))
+	} else {
+		s.to_owned()
+	}
+}
+
+/// Prints `label = 0/1` for each qubit in a line, sorted by label and aligned.
+/// The alternate form `{:#}` also prints the energy in the first line and the
+/// local field of each qubit, if they are known.
+impl<Tq: TqType, R: Real> std::fmt::Display for SolutionView<Tq, R> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let labels = self.sorted_labels();
+		let width = labels.iter().map(|(l, _)| l.len()).max().unwrap_or(0);
+		let mut lines = Vec::new();
+		if let (true, Some(energy)) = (f.alternate(), self.0.energy) {
+			lines.push(format!("energy = {}", energy));
+		}
+		for (label, i) in labels {
+			let mut line = format!("{:<width$} = {}", label, self.0.state.get(i) as u8);
+			let field = self.0.local_field.as_ref().and_then(|v| v.get(i));
+			if let (true, Some(field)) = (f.alternate(), field) {
+				line.push_str(&format!("  local_field = {}", field));
+			}
+			lines.push(line);
+		}
+		write!(f, "{}", lines.join("\n"))
+	}
 }
 
 impl<Tq: TqType, R: Real> std::ops::Index<&Tq> for SolutionView<Tq, R> {
@@ -55,3 +254,85 @@ impl<Tq: TqType, R: Real> std::ops::Index<&Tq> for SolutionView<Tq, R> {
 		}
 	}
 }
+
+/// Two views are equal when they assign the same values to the same qubits.
+impl<Tq: TqType, R: Real> PartialEq for SolutionView<Tq, R> {
+	fn eq(&self, other: &Self) -> bool {
+		self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+	}
+}
+
+pub struct Iter<'a, Tq: TqType, R: Real>(
+	hash_map::Iter<'a, Tq, usize>,
+	&'a SingleSolution<Binary<R>>,
+);
+
+impl<'a, Tq: TqType, R: Real> Iterator for Iter<'a, Tq, R> {
+	type Item = (&'a Tq, bool);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		self.0.next().map(|(k, v)| (k, self.1.state.get(*v)))
+	}
+}
+
+pub struct IntoIter<Tq: TqType, R: Real>(hash_map::IntoIter<Tq, usize>, SingleSolution<Binary<R>>);
+
+impl<Tq: TqType, R: Real> Iterator for IntoIter<Tq, R> {
+	type Item = (Tq, bool);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		self.0.next().map(|(k, v)| (k, self.1.state.get(v)))
+	}
+}
+
+impl<'a, Tq: TqType, R: Real> IntoIterator for &'a SolutionView<Tq, R> {
+	type Item = (&'a Tq, bool);
+	type IntoIter = Iter<'a, Tq, R>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+impl<Tq: TqType, R: Real> IntoIterator for SolutionView<Tq, R> {
+	type Item = (Tq, bool);
+	type IntoIter = IntoIter<Tq, R>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		IntoIter(self.1.into_iter(), self.0)
+	}
+}
+
+#[cfg(test)]
+fn view(v: &[bool], labels: &[(&'static str, usize)]) -> SolutionView<&'static str, f64> {
+	SolutionView::new(
+		SingleSolution::from_vec(v),
+		labels.iter().copied().collect(),
+	)
+}
+
+#[test]
+fn distance_test() {
+	let labels = [("a", 0), ("b", 1), ("c", 2)];
+	let a = view(&[true, false, true], &labels);
+	let b = view(&[false, false, false], &labels);
+	assert_eq!(a.distance(&b), 2);
+	assert_eq!(a.distance(&a), 0);
+	// Matched by label, ignoring the ancilla at 0
+	let c = view(&[true, true, false, false], &[("c", 1), ("b", 3), ("a", 2)]);
+	assert_eq!(a.distance(&c), 1);
+	assert_eq!(c.distance(&b), 1);
+	assert_eq!(
+		diversity(&[a, b, c]),
+		(2.0 / 3.0 + 1.0 / 3.0 + 1.0 / 3.0) / 3.0
+	);
+	assert_eq!(diversity::<&str, f64>(&[]), 0.0);
+}
+
+#[test]
+#[should_panic]
+fn distance_labels_test() {
+	let a = view(&[true, false], &[("a", 0), ("b", 1)]);
+	let b = view(&[true, false], &[("a", 0), ("c", 1)]);
+	a.distance(&b);
+}
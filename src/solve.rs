@@ -1,39 +1,230 @@
 extern crate classical_solver;
 
 use crate::compiled::CompiledModel;
+use crate::embedding::{EmbeddedSolverGenerator, Embedder};
 use crate::solution::SolutionView;
 use crate::wrapper::{Placeholder, Qubit};
-use crate::{TcType, TqType};
+use crate::{TcType, TpType, TqType};
 use annealers::model::{FixedSingleQuadricModel, SingleModelView};
 use annealers::node::Binary;
+use annealers::repr::BinaryRepr;
 use annealers::solution::SingleSolution;
-use annealers::solver::{ClassicalSolver, Solver, SolverGenerator, UnstructuredSolverGenerator};
+use annealers::solver::{
+	AsyncSolver, ClassicalSolver, SolverGenerator, StructuredSolverGenerator,
+	UnstructuredSolverGenerator,
+};
 use annealers::variable::Real;
-use classical_solver::sa::{SimulatedAnnealer, SimulatedAnnealerGenerator};
+use classical_solver::beta::BetaType;
+use classical_solver::exact::ExactSolverGenerator;
+use classical_solver::sa::SimulatedAnnealerGenerator;
 
 use rand::rngs::{OsRng, StdRng};
 use rand::SeedableRng;
 use rayon::prelude::*;
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
 use std::marker::PhantomData;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Progress of [`SimpleSolver`], which is passed to the progress callback once
+/// per generation.
+#[derive(Clone, Debug)]
+pub struct ProgressInfo<'a, Tc: TcType, R: Real> {
+	pub iteration: usize,
+	pub generation: usize,
+	/// The energy of the best solution found so far.
+	pub best_energy: Option<R>,
+	/// The number of unsatisfied constraints of the best solution found so far.
+	pub unsatisfied: usize,
+	/// The labels of the unsatisfied constraints of the best solution found so
+	/// far.
+	pub unsatisfied_labels: &'a [&'a Tc],
+}
+
+/// Callback invoked with [`ProgressInfo`]. Returning `ControlFlow::Break(())`
+/// aborts the solve, which then returns the best solution found so far.
+pub type ProgressCallback<Tc, R> =
+	Box<dyn FnMut(ProgressInfo<'_, Tc, R>) -> ControlFlow<()> + Send>;
+
+/// The iteration, the generation, the best energy and the labels of the
+/// unsatisfied constraints, sent to the coordinating thread by the iterations
+/// running in parallel.
+type ProgressEvent<'a, Tc, R> = (usize, usize, Option<R>, Vec<&'a Tc>);
+
+/// Hook invoked with the iteration, the generation, the constant offset and
+/// the QUBO generated for the generation.
+pub type DebugHook<R> = Box<dyn FnMut(usize, usize, R, &FixedSingleQuadricModel<Binary<R>>) + Send>;
+
+/// Statistics collected by [`SimpleSolver::solve_with_stats`].
+#[derive(Clone, Debug)]
+pub struct SolveStats<Tc: TcType, R: Real> {
+	/// The best energy of the annealer found so far in the current iteration,
+	/// recorded for each executed generation. It does not include the constant
+	/// offset of the model.
+	pub best_energies: Vec<R>,
+	/// The number of unsatisfied constraints of the best solution, recorded for
+	/// each executed generation.
+	pub unsatisfied: Vec<usize>,
+	/// The total number of samples returned by the annealer.
+	pub samples: usize,
+	pub elapsed: Duration,
+	/// The final penalty strength of each constraint.
+	pub strengths: HashMap<Tc, R>,
+	/// The penalty energy of each labeled constraint in the returned solution,
+	/// i.e. the value of its expression multiplied by its strength in the
+	/// generation where the solution was found. Soft constraints are included
+	/// with their fixed penalties. The sum of them and the energy of the
+	/// objective is the returned energy.
+	pub constraint_energies: HashMap<Tc, R>,
+}
+
+/// Error returned by [`SimpleSolver`].
+#[derive(Debug)]
+pub enum SolveError<E> {
+	/// The underlying solver returned an error.
+	Solver(E),
+	/// The solver did not find any solution, e.g. all the sampled energies
+	/// were NaN.
+	NoSolutionFound,
+	/// The compiled model has no qubits.
+	EmptyModel,
+	/// A parameter of [`SimpleSolver`] is invalid.
+	InvalidParameter(&'static str),
+}
+
+impl<E> From<E> for SolveError<E> {
+	fn from(e: E) -> Self {
+		Self::Solver(e)
+	}
+}
+
+impl<E: Error> std::fmt::Display for SolveError<E> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Solver(e) => write!(f, "solver error: {}", e),
+			Self::NoSolutionFound => write!(f, "no solution found"),
+			Self::EmptyModel => write!(f, "the model has no qubits"),
+			Self::InvalidParameter(msg) => write!(f, "invalid parameter: {}", msg),
+		}
+	}
+}
+
+impl<E: Error + 'static> Error for SolveError<E> {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		if let Self::Solver(e) = self {
+			Some(e)
+		} else {
+			None
+		}
+	}
+}
+
+/// Rule to update the weight of the penalty of a constraint when the best
+/// solution of a generation violates it. The penalty strength of a constraint
+/// is `coeff_strength` multiplied by the ratio of its weight to the sum of the
+/// weights, which start from 10.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PenaltyUpdate {
+	/// Add `step` to the weight.
+	Additive { step: f64 },
+	/// Multiply the weight by `factor`.
+	Multiplicative { factor: f64 },
+	/// Add `gain` multiplied by the absolute value of the constraint
+	/// expression to the weight.
+	ViolationProportional { gain: f64 },
+}
+
+impl Default for PenaltyUpdate {
+	fn default() -> Self {
+		Self::Additive { step: 1.0 }
+	}
+}
+
+impl PenaltyUpdate {
+	fn next_weight(&self, weight: f64, violation: f64) -> f64 {
+		match self {
+			Self::Additive { step } => weight + step,
+			Self::Multiplicative { factor } => weight * factor,
+			Self::ViolationProportional { gain } => weight + gain * violation,
+		}
+	}
+}
+
+/// Generates a solver generator which starts from a given state.
+type WarmStart<T> = Box<dyn Fn(&T, &mut StdRng) -> T + Send + Sync>;
+
+/// Solver of [`CompiledModel`], which repeatedly solves the QUBO of the model
+/// with the solver generated by `T`, raising the strengths of the unsatisfied
+/// constraints each generation.
+///
+/// [`SimpleSolver::new()`] uses [`SimulatedAnnealerGenerator`]. Any other
+/// unstructured solver generator of `FixedSingleQuadricModel<Binary<R>>`,
+/// e.g. a tabu search or a remote annealer, is used with
+/// [`SimpleSolver::with_solver()`]:
+///
+/// ```
+/// # use rustqubo::Expr;
+/// # use rustqubo::solve::SimpleSolver;
+/// use classical_solver::multi_start::MultiStart;
+/// use classical_solver::sa::SimulatedAnnealerGenerator;
+///
+/// let hmlt = Expr::Binary("a") * Expr::Binary("b") * Expr::Number(2.0) - Expr::Binary("a");
+/// let compiled = hmlt.compile();
+/// let generator = MultiStart::new(SimulatedAnnealerGenerator::new(), 4);
+/// let solver = SimpleSolver::with_solver(&compiled, generator);
+/// let (energy, qubits) = solver.solve().unwrap();
+/// assert_eq!(energy, -1.0);
+/// assert_eq!(qubits.get(&"a"), Some(true));
+/// assert_eq!(qubits.get(&"b"), Some(false));
+/// ```
 pub struct SimpleSolver<
 	'a,
 	Tq: TqType,
 	Tc: TcType,
-	T: UnstructuredSolverGenerator<'static, P>,
+	T: for<'m> UnstructuredSolverGenerator<'m, P>,
 	P: SingleModelView,
-	ST: Solver,
 	R: Real,
 > {
 	model: &'a CompiledModel<(), Tq, Tc, R>,
 	qubits: Vec<&'a Qubit<Tq>>,
-	_phantom: PhantomData<(P, ST)>,
+	_phantom: PhantomData<P>,
 	pub iterations: usize,
+	/// If true, the iterations run in parallel, each with its own placeholder
+	/// feedback, and the best solution among them is returned. The progress
+	/// callback then reports the best solution of each iteration. An iteration
+	/// which satisfies all the constraints does not stop the others, so the
+	/// solution is at least as good as the one of the sequential run with the
+	/// same seed, unless the solve is stopped by a limit or the callback.
+	pub parallel_iterations: bool,
+	/// The number of samples in each generation.
 	pub samples: usize,
 	// pub processes: usize,
 	pub generations: usize,
+	/// If set, an iteration is stopped when neither the best energy nor the
+	/// number of unsatisfied constraints has improved for this number of
+	/// consecutive generations.
+	pub patience: Option<usize>,
+	/// If true, the plateau detected with `patience` also stops the remaining
+	/// iterations.
+	pub patience_stops_solve: bool,
 	pub coeff_strength: R,
+	/// The tolerance of the constraints, which are satisfied if the absolute
+	/// value of the expression is at most it.
+	pub constraint_tolerance: f64,
+	coeff_schedule: Vec<R>,
 	pub solver_generator: T,
+	progress_callback: Option<Mutex<ProgressCallback<Tc, R>>>,
+	debug_hook: Option<Mutex<DebugHook<R>>>,
+	seed: Option<u64>,
+	timeout: Option<Duration>,
+	total_samples: Option<usize>,
+	penalty_update: PenaltyUpdate,
+	warm_start: Option<WarmStart<T>>,
 }
 
 impl<'a, Tq, Tc, R: Real>
@@ -43,23 +234,107 @@ impl<'a, Tq, Tc, R: Real>
 		Tc,
 		SimulatedAnnealerGenerator<'static, FixedSingleQuadricModel<Binary<R>>>,
 		FixedSingleQuadricModel<Binary<R>>,
-		SimulatedAnnealer<'static, FixedSingleQuadricModel<Binary<R>>, R>,
 		R,
-	> where
+	>
+where
 	Tq: TqType,
 	Tc: TcType,
 {
 	pub fn new(model: &'a CompiledModel<(), Tq, Tc, R>) -> Self {
 		Self::with_solver(model, SimulatedAnnealerGenerator::new())
 	}
+
+	/// Set the number of sweeps per beta step of the internal annealer.
+	pub fn sweeps_per_round(mut self, sweeps_per_round: usize) -> Self {
+		self.solver_generator.sweeps_per_round = sweeps_per_round;
+		self
+	}
+
+	/// Set the beta schedule of the internal annealer.
+	pub fn beta(mut self, beta: BetaType<R>) -> Self {
+		self.solver_generator.beta = beta;
+		self
+	}
+
+	/// Start one of the samples in each generation from the given assignment.
+	/// Ancilla qubits and qubits missing in `assignment` are randomized.
+	pub fn initial_state(mut self, assignment: &HashMap<Tq, bool>) -> Self {
+		let values = self
+			.qubits
+			.iter()
+			.map(|q| {
+				if let Qubit::Qubit(q) = q {
+					assignment.get(q).copied()
+				} else {
+					None
+				}
+			})
+			.collect::<Vec<_>>();
+		self.warm_start = Some(Box::new(move |gen, r| {
+			let mut state = BinaryRepr::new_random(values.len(), r);
+			for (i, v) in values.iter().enumerate() {
+				if let Some(v) = v {
+					state.set(i, *v);
+				}
+			}
+			let mut gen = gen.clone();
+			gen.initial_state = Some(state);
+			gen
+		}));
+		self
+	}
+}
+
+impl<'a, Tq, Tc, R: Real>
+	SimpleSolver<'a, Tq, Tc, ExactSolverGenerator, FixedSingleQuadricModel<Binary<R>>, R>
+where
+	Tq: TqType,
+	Tc: TcType,
+{
+	/// Create the solver which finds the optimum of the QUBO of each
+	/// generation by enumerating all the assignments, as a reference of the
+	/// other solvers. The solve fails with
+	/// [`classical_solver::ExactSolverError::TooLarge`] if the QUBO, including
+	/// the ancillas, has more qubits than `solver_generator.max_size`.
+	pub fn new_exact(model: &'a CompiledModel<(), Tq, Tc, R>) -> Self {
+		let mut solver = Self::with_solver(model, ExactSolverGenerator::new());
+		// All the samples would be the same.
+		solver.samples = 1;
+		solver
+	}
 }
 
-impl<'a, Tq, Tc, T: UnstructuredSolverGenerator<'static, P>, P: SingleModelView, R: Real>
-	SimpleSolver<'a, Tq, Tc, T, P, T::SolverType, R>
+impl<'a, Tq, Tc, G, E, R: Real>
+	SimpleSolver<'a, Tq, Tc, EmbeddedSolverGenerator<G, R>, FixedSingleQuadricModel<Binary<R>>, R>
 where
 	Tq: TqType,
 	Tc: TcType,
+	G: for<'m> StructuredSolverGenerator<'m, FixedSingleQuadricModel<Binary<R>>, ErrorType = E>
+		+ Send
+		+ Sync,
+	E: Error + Send + Sync + 'static,
 {
+	/// Create the solver with a structured solver, whose topology restricts the
+	/// allowed couplers. The QUBO of each generation is embedded into the
+	/// topology with `embedder`, and the solutions are unembedded.
+	pub fn with_structured_solver(
+		model: &'a CompiledModel<(), Tq, Tc, R>,
+		generator: G,
+		embedder: Box<dyn Embedder<R>>,
+	) -> Self {
+		Self::with_solver(model, EmbeddedSolverGenerator::new(generator, embedder))
+	}
+}
+
+impl<'a, Tq, Tc, T: for<'m> UnstructuredSolverGenerator<'m, P>, P: SingleModelView, R: Real>
+	SimpleSolver<'a, Tq, Tc, T, P, R>
+where
+	Tq: TqType,
+	Tc: TcType,
+{
+	/// Create the solver which solves the QUBO of each generation with the
+	/// solvers generated by `solver_generator`. See [`SimpleSolver`] for an
+	/// example.
 	pub fn with_solver(model: &'a CompiledModel<(), Tq, Tc, R>, solver_generator: T) -> Self {
 		let qubits = model.get_qubits().into_iter().collect::<Vec<_>>();
 		Self {
@@ -67,13 +342,137 @@ where
 			qubits,
 			samples: rayon::current_num_threads(),
 			iterations: 10,
+			parallel_iterations: false,
 			generations: 30,
+			patience: None,
+			patience_stops_solve: false,
 			coeff_strength: R::from_i32(50),
+			constraint_tolerance: 1.0e-4,
+			coeff_schedule: Vec::new(),
 			solver_generator,
+			progress_callback: None,
+			debug_hook: None,
+			seed: None,
+			timeout: None,
+			total_samples: None,
+			penalty_update: PenaltyUpdate::default(),
+			warm_start: None,
 			_phantom: PhantomData,
 		}
 	}
 
+	/// Use the given seed for random number generation to get reproducible
+	/// results. Each sample uses its own seed derived from `seed`.
+	pub fn with_seed(mut self, seed: u64) -> Self {
+		self.seed = Some(seed);
+		self
+	}
+
+	/// Stop solving when `timeout` is exceeded and return the best solution
+	/// found so far, which may violate some constraints. The deadline is checked
+	/// at the start of each generation, and at least one generation is run.
+	pub fn with_timeout(mut self, timeout: Duration) -> Self {
+		self.timeout = Some(timeout);
+		self
+	}
+
+	/// Stop solving after `total_samples` samples in total, which may violate
+	/// some constraints. The last generation may have fewer samples than
+	/// `samples`.
+	pub fn with_total_samples(mut self, total_samples: usize) -> Self {
+		self.total_samples = Some(total_samples);
+		self
+	}
+
+	/// Set the penalty strength for each generation. `schedule[g]` is used in
+	/// place of `coeff_strength` in generation `g`, and `coeff_strength` is used
+	/// after the end of the schedule.
+	pub fn with_coeff_schedule(mut self, schedule: Vec<R>) -> Self {
+		self.coeff_schedule = schedule;
+		self
+	}
+
+	/// Set the rule to update the penalty strengths of the constraints.
+	pub fn with_penalty_update(mut self, penalty_update: PenaltyUpdate) -> Self {
+		self.penalty_update = penalty_update;
+		self
+	}
+
+	/// Set the callback which is called from the coordinating thread after each
+	/// generation. If `parallel_iterations` is set, the progress of the
+	/// iterations is forwarded to it.
+	pub fn set_progress_callback(&mut self, cb: ProgressCallback<Tc, R>) {
+		self.progress_callback = Some(Mutex::new(cb));
+	}
+
+	/// Set the callback like [`Self::set_progress_callback`]. `cb` is called with
+	/// the iteration, the generation, the best energy found so far and the
+	/// labels of its unsatisfied constraints.
+	pub fn with_progress_callback<F>(mut self, mut cb: F) -> Self
+	where
+		F: FnMut(usize, usize, R, &[&Tc]) -> ControlFlow<()> + Send + 'static,
+	{
+		self.set_progress_callback(Box::new(move |info| {
+			if let Some(energy) = info.best_energy {
+				cb(
+					info.iteration,
+					info.generation,
+					energy,
+					info.unsatisfied_labels,
+				)
+			} else {
+				ControlFlow::Continue(())
+			}
+		}));
+		self
+	}
+
+	/// Set the hook which is called with the QUBO of each generation before it
+	/// is sampled. The QUBO is not passed anywhere if no hook is set.
+	pub fn with_debug_hook<F>(mut self, hook: F) -> Self
+	where
+		F: FnMut(usize, usize, R, &FixedSingleQuadricModel<Binary<R>>) + Send + 'static,
+	{
+		self.debug_hook = Some(Mutex::new(Box::new(hook)));
+		self
+	}
+
+	fn qubit_map(&self) -> HashMap<Tq, usize> {
+		self.qubits
+			.iter()
+			.enumerate()
+			.filter_map(|(i, q)| {
+				if let Qubit::Qubit(q) = q {
+					Some((q.clone(), i))
+				} else {
+					None
+				}
+			})
+			.collect()
+	}
+
+	/// The assignment of the qubits, excluding ancillas, in `view`.
+	fn view_assignment(&self, view: &SolutionView<Tq, R>) -> HashMap<&'a Qubit<Tq>, bool> {
+		self.qubits
+			.iter()
+			.filter_map(|q| {
+				if let Qubit::Qubit(label) = q {
+					Some((*q, view[label]))
+				} else {
+					None
+				}
+			})
+			.collect()
+	}
+
+	/// Get the violation of each labeled constraint in `view`, which is the
+	/// absolute value of the constraint expression, i.e. its penalty energy at
+	/// unit strength. See [`crate::Constraint::violation`].
+	pub fn constraint_violations(&self, view: &SolutionView<Tq, R>) -> Vec<(&'a Tc, R)> {
+		self.model
+			.constraint_violations(&self.view_assignment(view))
+	}
+
 	pub fn get_qubits(&self) -> Vec<&'a Tq> {
 		self.qubits
 			.iter()
@@ -88,23 +487,20 @@ where
 	}
 }
 
-// TODO: implement where ST: AsyncSolver
 impl<
 		'a,
 		Tq,
-		T: UnstructuredSolverGenerator<'static, FixedSingleQuadricModel<Binary<R>>, SolverType = ST>,
-		ST: ClassicalSolver<SolutionType = SingleSolution<Binary<R>>, ErrorType = T::ErrorType>,
+		T: for<'m> UnstructuredSolverGenerator<'m, FixedSingleQuadricModel<Binary<R>>, ErrorType = E>,
+		E: Error + Send + Sync,
 		R: Real,
-	> SimpleSolver<'a, Tq, (), T, FixedSingleQuadricModel<Binary<R>>, ST, R>
+	> SimpleSolver<'a, Tq, (), T, FixedSingleQuadricModel<Binary<R>>, R>
 where
 	Tq: TqType + Send + Sync,
+	T: Sync,
+	for<'m> <T as SolverGenerator<'m, FixedSingleQuadricModel<Binary<R>>>>::SolverType:
+		ClassicalSolver<SolutionType = SingleSolution<Binary<R>>, ErrorType = E>,
 {
-	pub fn solve(
-		&self,
-	) -> Result<
-		(R, SolutionView<Tq, R>),
-		<T as SolverGenerator<'static, FixedSingleQuadricModel<Binary<R>>>>::ErrorType,
-	> {
+	pub fn solve(&self) -> Result<(R, SolutionView<Tq, R>), SolveError<E>> {
 		// Drop constraint missing information
 		self.solve_with_constraints().map(|(a, b, _)| (a, b))
 	}
@@ -114,110 +510,882 @@ impl<
 		'a,
 		Tq,
 		Tc,
-		T: UnstructuredSolverGenerator<'static, FixedSingleQuadricModel<Binary<R>>, SolverType = ST>,
-		ST: ClassicalSolver<SolutionType = SingleSolution<Binary<R>>, ErrorType = T::ErrorType>,
+		T: for<'m> UnstructuredSolverGenerator<'m, FixedSingleQuadricModel<Binary<R>>, ErrorType = E>,
+		E: Error + Send + Sync,
 		R: Real,
-	> SimpleSolver<'a, Tq, Tc, T, FixedSingleQuadricModel<Binary<R>>, ST, R>
+	> SimpleSolver<'a, Tq, Tc, T, FixedSingleQuadricModel<Binary<R>>, R>
 where
 	Tq: TqType + Send + Sync,
 	Tc: TcType + Send + Sync,
+	T: Sync,
+	for<'m> <T as SolverGenerator<'m, FixedSingleQuadricModel<Binary<R>>>>::SolverType:
+		ClassicalSolver<SolutionType = SingleSolution<Binary<R>>, ErrorType = E>,
 {
 	/// Solve the model using internal annealer.
+	#[allow(clippy::type_complexity)]
 	pub fn solve_with_constraints(
 		&self,
-	) -> Result<
-		(R, SolutionView<Tq, R>, Vec<&Tc>),
-		<T as SolverGenerator<'static, FixedSingleQuadricModel<Binary<R>>>>::ErrorType,
-	> {
-		let ph = self.model.get_placeholders();
-		let mut ret = None;
-		let qubit_map: HashMap<Tq, usize> = self
-			.qubits
+	) -> Result<(R, SolutionView<Tq, R>, Vec<&Tc>), SolveError<E>> {
+		self.solve_with_stats().map(|(a, b, c, _)| (a, b, c))
+	}
+
+	/// Solve the model like [`Self::solve_with_constraints`], also returning the
+	/// energy of the objective. The first value is the energy of the reduced
+	/// QUBO, which includes penalties of the constraints and ancilla qubits,
+	/// and the second one is the energy of the original expression without the
+	/// penalties.
+	#[allow(clippy::type_complexity)]
+	pub fn solve_verified(&self) -> Result<(R, R, SolutionView<Tq, R>, Vec<&Tc>), SolveError<E>> {
+		let (energy, sol, unsatisfied) = self.solve_with_constraints()?;
+		let objective = self.model.objective_energy(&self.view_assignment(&sol));
+		Ok((energy, objective, sol, unsatisfied))
+	}
+
+	/// Solve the model like [`Self::solve_with_constraints`], also returning the
+	/// statistics of the run.
+	#[allow(clippy::type_complexity)]
+	pub fn solve_with_stats(
+		&self,
+	) -> Result<(R, SolutionView<Tq, R>, Vec<&Tc>, SolveStats<Tc, R>), SolveError<E>> {
+		self.solve_inner(self.init_state()?, |_, _, _| ())
+	}
+
+	/// Search an assignment which satisfies all the hard constraints, ignoring
+	/// the objective. The penalties of the soft constraints are kept, and the
+	/// violated ones are reported with the labels of the unsatisfied
+	/// constraints. The solve stops as soon as a sample satisfies the hard
+	/// constraints. If no such assignment is found within `max_attempts`
+	/// generations (or all the generations if `None`), the best assignment is
+	/// returned with the labels of its unsatisfied constraints.
+	#[allow(clippy::type_complexity)]
+	pub fn solve_feasible(
+		&self,
+		max_attempts: Option<usize>,
+	) -> Result<(SolutionView<Tq, R>, Vec<&Tc>), SolveError<E>> {
+		if max_attempts == Some(0) {
+			return Err(SolveError::InvalidParameter(
+				"max_attempts must be at least 1",
+			));
+		}
+		let mut state = self.init_state()?;
+		state.feasible_only = true;
+		state.max_generations = max_attempts;
+		self.solve_inner(state, |_, _, _| ())
+			.map(|(_, b, c, _)| (b, c))
+	}
+
+	/// Solve the model once with the constant penalty strength of each
+	/// constraint given in `penalties`, without adapting them. The best of
+	/// `samples` samples is returned.
+	#[allow(clippy::type_complexity)]
+	pub fn solve_fixed(
+		&self,
+		penalties: HashMap<Tc, R>,
+	) -> Result<(R, SolutionView<Tq, R>, Vec<&Tc>), SolveError<E>> {
+		let mut state = self.init_state()?;
+		let missing = state.placeholders.iter().any(|p| match p {
+			Placeholder::Constraint(label) => !penalties.contains_key(label),
+			Placeholder::Placeholder(_) | Placeholder::Soft(_) => false,
+		});
+		if missing {
+			return Err(SolveError::InvalidParameter(
+				"penalties must be given for all the constraints",
+			));
+		}
+		state.penalties = Some(penalties);
+		state.max_generations = Some(1);
+		self.solve_inner(state, |_, _, _| ())
+			.map(|(a, b, c, _)| (a, b, c))
+	}
+
+	/// Solve the model, returning every distinct solution sampled during the
+	/// solve, sorted by energy. All the iterations and generations are run,
+	/// even after the constraints are satisfied. The solutions are sampled with
+	/// different penalty strengths, so their energies are calculated again with
+	/// the final strengths in [`SolveStats::strengths`], like
+	/// [`CompiledModel::energy_of`] but with these strengths.
+	#[allow(clippy::type_complexity)]
+	pub fn solve_population(&self) -> Result<Vec<(R, SolutionView<Tq, R>)>, SolveError<E>> {
+		let qubit_map = self.qubit_map();
+		let mut population = HashMap::<BinaryRepr, SolutionView<Tq, R>>::new();
+		let mut state = self.init_state()?;
+		state.exhaustive = true;
+		let (_, _, _, stats) = self.solve_inner(state, |_, model, solutions| {
+			for sol in solutions.iter() {
+				population.entry(sol.state.clone()).or_insert_with(|| {
+					SolutionView::new(sol.clone().with_local_field(model), qubit_map.clone())
+						.with_fixed(self.model.fixed())
+				});
+			}
+		})?;
+		let strengths = stats.strengths;
+		let mut ret = population
+			.into_values()
+			.map(|view| {
+				let energy = self
+					.model
+					.penalized_energy(&self.view_assignment(&view), &mut |label| strengths[label]);
+				(energy, view)
+			})
+			.collect::<Vec<_>>();
+		ret.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+		Ok(ret)
+	}
+
+	/// Solve the model for each pair of the weights of the two objectives with
+	/// the parameters and the callbacks of this solver. `model` is the model of
+	/// this solver before the placeholders `objective_a` and `objective_b` are
+	/// fed. It is compiled only once and the placeholders are fed for each
+	/// pair. Returns the weights, the energy and the solution of each pair.
+	#[allow(clippy::type_complexity)]
+	pub fn sweep_weights<Tp>(
+		&mut self,
+		model: &CompiledModel<Tp, Tq, Tc, R>,
+		objective_a: &Tp,
+		objective_b: &Tp,
+		weights: &[(R, R)],
+	) -> Result<Vec<((R, R), R, SolutionView<Tq, R>)>, SolveError<E>>
+	where
+		Tp: TpType,
+		T: Clone,
+	{
+		let other = model.get_placeholders().into_iter().any(|p| match p {
+			Placeholder::Placeholder(p) => p != objective_a && p != objective_b,
+			Placeholder::Constraint(_) | Placeholder::Soft(_) => false,
+		});
+		if other {
+			return Err(SolveError::InvalidParameter(
+				"placeholders other than the objectives must be fed",
+			));
+		}
+		weights
 			.iter()
-			.enumerate()
-			.filter_map(|(i, q)| {
-				if let Qubit::Qubit(q) = q {
-					Some((q.clone(), i))
+			.map(|(a, b)| {
+				let dict = vec![(objective_a.clone(), *a), (objective_b.clone(), *b)]
+					.into_iter()
+					.collect();
+				let fed = model.clone().feed_dict(dict);
+				let mut solver = self.rebind(&fed);
+				let ret = if solver.qubits == self.qubits {
+					solver
+						.solve_with_constraints()
+						.map(|(energy, sol, _)| (energy, sol))
 				} else {
-					None
-				}
+					Err(SolveError::InvalidParameter(
+						"the model must have the qubits of the solver",
+					))
+				};
+				self.progress_callback = solver.progress_callback.take();
+				self.debug_hook = solver.debug_hook.take();
+				self.warm_start = solver.warm_start.take();
+				let (energy, sol) = ret?;
+				Ok(((*a, *b), energy, sol))
 			})
-			.collect();
-		for _ in 0..self.iterations {
-			let mut phdict: HashMap<&Placeholder<(), Tc>, usize> =
-				ph.iter().map(|p| (*p, 10)).collect();
-			let mut size = ph.len() * 10;
-			let mut old_energy = R::MAX;
-			for _ in 0..self.generations {
-				let (c, model) = self.model.generate_qubo(&self.qubits, &mut |p| {
-					if let Some(cnt) = phdict.get(&p) {
-						R::from_i32(*cnt as i32) / R::from_i32(size as i32) * self.coeff_strength
-					} else {
-						panic!()
-					}
+			.collect()
+	}
+
+	/// Move the parameters and the callbacks of this solver to a solver of
+	/// `model`. [`Self::sweep_weights`] moves the callbacks back after the
+	/// solve.
+	fn rebind<'b>(
+		&mut self,
+		model: &'b CompiledModel<(), Tq, Tc, R>,
+	) -> SimpleSolver<'b, Tq, Tc, T, FixedSingleQuadricModel<Binary<R>>, R>
+	where
+		T: Clone,
+	{
+		SimpleSolver {
+			model,
+			qubits: model.get_qubits().into_iter().collect(),
+			_phantom: PhantomData,
+			iterations: self.iterations,
+			parallel_iterations: self.parallel_iterations,
+			samples: self.samples,
+			generations: self.generations,
+			patience: self.patience,
+			patience_stops_solve: self.patience_stops_solve,
+			coeff_strength: self.coeff_strength,
+			constraint_tolerance: self.constraint_tolerance,
+			coeff_schedule: self.coeff_schedule.clone(),
+			solver_generator: self.solver_generator.clone(),
+			progress_callback: self.progress_callback.take(),
+			debug_hook: self.debug_hook.take(),
+			seed: self.seed,
+			timeout: self.timeout,
+			total_samples: self.total_samples,
+			penalty_update: self.penalty_update,
+			warm_start: self.warm_start.take(),
+		}
+	}
+
+	/// Run the solver. `on_generation` is called with the constant offset, the
+	/// generated model and the sampled solutions of each generation.
+	#[allow(clippy::type_complexity)]
+	fn solve_inner<F>(
+		&self,
+		mut state: SolveState<'a, Tq, Tc, R>,
+		on_generation: F,
+	) -> Result<(R, SolutionView<Tq, R>, Vec<&Tc>, SolveStats<Tc, R>), SolveError<E>>
+	where
+		F: FnMut(R, &FixedSingleQuadricModel<Binary<R>>, &[SingleSolution<Binary<R>>]) + Send,
+	{
+		let on_generation = Mutex::new(on_generation);
+		if self.parallel_iterations {
+			// Each iteration owns its state, and only the limits of the solve are
+			// shared. The progress is sent to this thread until all the senders
+			// are dropped.
+			let (tx, rx) = mpsc::channel();
+			state.progress = self.progress_callback.as_ref().map(|_| tx);
+			let budget = state.budget.clone();
+			let on_generation = &on_generation;
+			state = std::thread::scope(|s| {
+				let worker = s.spawn(move || {
+					(0..self.iterations)
+						.into_par_iter()
+						.map(|iteration| {
+							let mut state = state.clone();
+							self.run_iteration(&mut state, iteration, on_generation)?;
+							Ok(state)
+						})
+						.collect::<Result<Vec<_>, SolveError<E>>>()
+						.map(|states| {
+							let mut state = states.into_iter().reduce(SolveState::merge).unwrap();
+							state.progress = None;
+							state
+						})
 				});
-				let fut_ret = std::iter::repeat_with(|| {
-					self.solver_generator.generate(unsafe {
-						// SAFETY: model lives longer than solver
-						std::mem::transmute(&model as *const FixedSingleQuadricModel<_>)
-					})
+				for (iteration, generation, best_energy, labels) in rx {
+					if self
+						.report_progress(iteration, generation, best_energy, &labels)
+						.is_break()
+					{
+						budget.stop.store(true, AtomicOrdering::Relaxed);
+					}
+				}
+				worker
+					.join()
+					.unwrap_or_else(|e| std::panic::resume_unwind(e))
+			})?;
+		} else {
+			for iteration in 0..self.iterations {
+				self.run_iteration(&mut state, iteration, &on_generation)?;
+				if state.budget.stop.load(AtomicOrdering::Relaxed) {
+					break;
+				}
+			}
+		}
+		self.finish(state)
+	}
+
+	/// Run the generations of an iteration, setting `budget.stop` of the state
+	/// if the solve should be stopped.
+	fn run_iteration<F>(
+		&self,
+		state: &mut SolveState<'a, Tq, Tc, R>,
+		iteration: usize,
+		on_generation: &Mutex<F>,
+	) -> Result<(), SolveError<E>>
+	where
+		F: FnMut(R, &FixedSingleQuadricModel<Binary<R>>, &[SingleSolution<Binary<R>>]),
+	{
+		let seed = self.seed;
+		let (feasible_only, tol) = (state.feasible_only, self.constraint_tolerance);
+		let (compiled, qubits) = (self.model, &self.qubits);
+		state.reset();
+		for generation in 0..self.generations {
+			let samples = self.next_samples(state);
+			if samples == 0 {
+				state.budget.stop.store(true, AtomicOrdering::Relaxed);
+				break;
+			}
+			let (c, model) = self.generate_qubo(state, generation);
+			if let Some(hook) = &self.debug_hook {
+				(hook.lock().unwrap())(iteration, generation, c, &model);
+			}
+			let warm = self
+				.warm_start
+				.as_ref()
+				.map(|f| {
+					f(
+						&self.solver_generator,
+						&mut sample_rng(seed, iteration, generation, self.samples),
+					)
+					.generate(&model)
 				})
-				.take(self.samples)
-				.collect::<Result<Vec<_>, _>>()?
-				.par_iter()
-				.map(|solver| {
+				.transpose()?;
+			// The solver is generated once and shared by the samples, which are
+			// split into chunks, each of which reuses a random number generator.
+			let solver = self.solver_generator.generate(&model)?;
+			let chunk = samples.div_ceil(rayon::current_num_threads());
+			// With `feasible_only`, the samples after the first feasible one are
+			// skipped. The ones before it are always sampled, so the samples do
+			// not depend on the scheduling of the threads.
+			let first_feasible = AtomicUsize::new(usize::MAX);
+			let fut_ret = (0..samples.div_ceil(chunk))
+				.into_par_iter()
+				.map(|k| {
 					let mut r = StdRng::from_rng(OsRng).unwrap();
-					solver.solve_with_rng(&mut r).map(|v| v.into_iter())
+					let mut ret = Vec::new();
+					for i in k * chunk..samples.min((k + 1) * chunk) {
+						if i > first_feasible.load(AtomicOrdering::Relaxed) {
+							break;
+						}
+						let solver = warm.as_ref().filter(|_| i == 0).unwrap_or(&solver);
+						let sols = if seed.is_some() {
+							solver
+								.solve_with_rng(&mut sample_rng(seed, iteration, generation, i))?
+						} else {
+							solver.solve_with_rng(&mut r)?
+						};
+						if feasible_only
+							&& sols
+								.iter()
+								.any(|sol| is_feasible(compiled, qubits, sol, tol))
+						{
+							first_feasible.fetch_min(i, AtomicOrdering::Relaxed);
+						}
+						ret.extend(sols.into_iter().map(|sol| (i, sol.with_energy(&model))));
+					}
+					Ok(ret)
 				})
-				.collect::<Result<Vec<_>, _>>()?
+				.collect::<Result<Vec<_>, E>>()?;
+			let first_feasible = first_feasible.into_inner();
+			let fut_ret = fut_ret
 				.into_iter()
-				.flat_map(std::convert::identity)
-				.map(|sol| sol.with_energy(&model))
+				.flatten()
+				.filter(|(i, _)| *i <= first_feasible)
+				.map(|(_, sol)| sol)
 				.collect::<Vec<_>>();
-				let min: f64 = fut_ret
-					.iter()
-					.fold(0.0 / 0.0, |m, v| v.energy.unwrap().as_f64().min(m));
-				assert!(min.is_finite());
-				let sol = fut_ret
-					.into_iter()
-					.filter(|r| r.energy.unwrap().as_f64() == min)
-					.next()
-					.unwrap();
-				let energy = sol.energy.unwrap();
-				// println!("{}, {}, {}", min, old_energy, energy);
-				if old_energy <= energy {
-					continue;
-				}
-				old_energy = energy;
-				let ans: HashMap<&Qubit<Tq>, bool> = self
-					.qubits
-					.iter()
-					.enumerate()
-					.map(|(i, q)| (*q, sol[i]))
-					.collect();
-				let mut constraint_labels = Vec::new();
-				for c in self.model.get_unsatisfied_constraints(&ans) {
-					if let Some(ph) = &c.placeholder {
-						if let Some(point) = phdict.get_mut(ph) {
-							*point += 1;
-							size += 1;
-						}
+			(*on_generation.lock().unwrap())(c, &model, &fut_ret);
+			if self
+				.feed_solutions(state, iteration, generation, c, &model, fut_ret)
+				.is_break()
+			{
+				// The other iterations in parallel are stopped by the coordinating
+				// thread if the callback asks to.
+				if !self.parallel_iterations {
+					state.budget.stop.store(true, AtomicOrdering::Relaxed);
+				}
+				break;
+			}
+			if self.is_plateau(state) {
+				if self.patience_stops_solve {
+					state.budget.stop.store(true, AtomicOrdering::Relaxed);
+				}
+				break;
+			}
+		}
+		Ok(())
+	}
+}
+
+impl<
+		'a,
+		Tq,
+		Tc,
+		T: for<'m> UnstructuredSolverGenerator<'m, FixedSingleQuadricModel<Binary<R>>, ErrorType = E>,
+		E: Error + Send + Sync,
+		R: Real,
+	> SimpleSolver<'a, Tq, Tc, T, FixedSingleQuadricModel<Binary<R>>, R>
+where
+	Tq: TqType,
+	Tc: TcType,
+	for<'m> <T as SolverGenerator<'m, FixedSingleQuadricModel<Binary<R>>>>::SolverType:
+		AsyncSolver<SolutionType = SingleSolution<Binary<R>>, ErrorType = E>,
+{
+	/// Solve the model like [`Self::solve_with_constraints`] with a solver
+	/// which runs asynchronously, e.g. a remote annealer. The samples of each
+	/// generation are requested one after another.
+	pub async fn solve_async(&self) -> Result<(R, SolutionView<Tq, R>, Vec<&Tc>), SolveError<E>> {
+		let mut state = self.init_state()?;
+		'outer: for iteration in 0..self.iterations {
+			state.reset();
+			for generation in 0..self.generations {
+				let samples = self.next_samples(&state);
+				if samples == 0 {
+					break 'outer;
+				}
+				let (c, model) = self.generate_qubo(&mut state, generation);
+				if let Some(hook) = &self.debug_hook {
+					(hook.lock().unwrap())(iteration, generation, c, &model);
+				}
+				let solver = self.solver_generator.generate(&model)?;
+				let mut solutions = Vec::new();
+				for _ in 0..samples {
+					for sol in solver.solve_async().await? {
+						solutions.push(sol.with_energy(&model));
 					}
-					if let Some(label) = &c.label {
-						constraint_labels.push(label);
+				}
+				if self
+					.feed_solutions(&mut state, iteration, generation, c, &model, solutions)
+					.is_break()
+				{
+					break 'outer;
+				}
+				if self.is_plateau(&state) {
+					if self.patience_stops_solve {
+						break 'outer;
 					}
+					break;
+				}
+			}
+		}
+		self.finish(state).map(|(a, b, c, _)| (a, b, c))
+	}
+}
+
+/// Limits of the solve shared by the iterations, which may run in parallel.
+#[derive(Default)]
+struct Budget {
+	/// The number of samples drawn so far.
+	drawn: AtomicUsize,
+	/// The number of generations run so far.
+	generations: AtomicUsize,
+	/// Set when the solve should be stopped.
+	stop: AtomicBool,
+}
+
+/// State of the generation loop of [`SimpleSolver`].
+#[derive(Clone)]
+struct SolveState<'a, Tq: TqType, Tc: TcType, R: Real> {
+	start: Instant,
+	placeholders: BTreeSet<&'a Placeholder<(), Tc>>,
+	qubit_map: HashMap<Tq, usize>,
+	phdict: HashMap<&'a Placeholder<(), Tc>, f64>,
+	/// The sum of the values of `phdict`.
+	size: f64,
+	budget: Arc<Budget>,
+	max_generations: Option<usize>,
+	/// If true, the objective is removed from the QUBO and the sampling stops
+	/// as soon as a feasible sample is found.
+	feasible_only: bool,
+	/// If true, the solve is not stopped when all the constraints are
+	/// satisfied, to sample all the generations.
+	exhaustive: bool,
+	/// The constant penalty strengths given to `solve_fixed()`, which replace
+	/// the placeholder feedback.
+	penalties: Option<HashMap<Tc, R>>,
+	strength: R,
+	old_energy: R,
+	/// The number of consecutive generations without improvement.
+	stale: usize,
+	ret: Option<(R, SolutionView<Tq, R>, Vec<&'a Tc>)>,
+	/// The penalty strength of each constraint in the generation where `ret`
+	/// was found.
+	ret_strengths: HashMap<Tc, R>,
+	/// Set if the iteration runs in parallel, to send the progress to the
+	/// coordinating thread instead of calling the callback.
+	progress: Option<Sender<ProgressEvent<'a, Tc, R>>>,
+	stats: SolveStats<Tc, R>,
+}
+
+impl<'a, Tq: TqType, Tc: TcType, R: Real> SolveState<'a, Tq, Tc, R> {
+	/// Reset the placeholder feedback at the start of an iteration.
+	fn reset(&mut self) {
+		self.phdict = self.placeholders.iter().map(|p| (*p, 10.0)).collect();
+		self.size = self.placeholders.len() as f64 * 10.0;
+		self.old_energy = R::MAX;
+		self.stale = 0;
+	}
+
+	/// The strength of the placeholder in the current generation.
+	fn placeholder_strength(&self, p: &Placeholder<(), Tc>) -> R {
+		if let Placeholder::Soft(_) = p {
+			R::one()
+		} else if let (Some(penalties), Placeholder::Constraint(label)) = (&self.penalties, p) {
+			penalties[label]
+		} else if let Some(cnt) = self.phdict.get(&p) {
+			penalty_strength(*cnt, self.size, self.strength)
+		} else {
+			panic!()
+		}
+	}
+
+	/// The strength of each constraint in the current generation.
+	fn constraint_strengths(&self) -> HashMap<Tc, R> {
+		self.placeholders
+			.iter()
+			.filter_map(|p| match p {
+				Placeholder::Constraint(label) => {
+					Some((label.clone(), self.placeholder_strength(p)))
 				}
-				let is_satisfied = constraint_labels.len() == 0;
-				ret = Some((
+				_ => None,
+			})
+			.collect()
+	}
+
+	/// Merge the state of another iteration run in parallel, keeping the better
+	/// solution together with its placeholder feedback.
+	fn merge(mut self, other: Self) -> Self {
+		self.stats.best_energies.extend(other.stats.best_energies);
+		self.stats.unsatisfied.extend(other.stats.unsatisfied);
+		self.stats.samples += other.stats.samples;
+		if let Some((energy, _, labels)) = &other.ret {
+			if is_better(*energy, labels.len(), self.best()) {
+				self.phdict = other.phdict;
+				self.size = other.size;
+				self.strength = other.strength;
+				self.old_energy = other.old_energy;
+				self.ret = other.ret;
+				self.ret_strengths = other.ret_strengths;
+			}
+		}
+		self
+	}
+
+	/// The energy and the number of unsatisfied constraints of the best
+	/// solution found so far.
+	fn best(&self) -> Option<(R, usize)> {
+		self.ret.as_ref().map(|r| (r.0, r.2.len()))
+	}
+}
+
+/// Whether a solution is better than `best`: the fewest unsatisfied
+/// constraints first, then the lowest energy.
+fn is_better<R: Real>(energy: R, unsatisfied: usize, best: Option<(R, usize)>) -> bool {
+	best.is_none_or(|(e, n)| unsatisfied < n || unsatisfied == n && energy < e)
+}
+
+impl<
+		'a,
+		Tq,
+		Tc,
+		T: for<'m> UnstructuredSolverGenerator<'m, FixedSingleQuadricModel<Binary<R>>>,
+		R,
+	> SimpleSolver<'a, Tq, Tc, T, FixedSingleQuadricModel<Binary<R>>, R>
+where
+	Tq: TqType,
+	Tc: TcType,
+	R: Real,
+{
+	fn init_state<E>(&self) -> Result<SolveState<'a, Tq, Tc, R>, SolveError<E>> {
+		if self.iterations == 0 {
+			return Err(SolveError::InvalidParameter(
+				"iterations must be at least 1",
+			));
+		}
+		if self.generations == 0 {
+			return Err(SolveError::InvalidParameter(
+				"generations must be at least 1",
+			));
+		}
+		if self.samples == 0 {
+			return Err(SolveError::InvalidParameter("samples must be at least 1"));
+		}
+		if self.total_samples == Some(0) {
+			return Err(SolveError::InvalidParameter(
+				"total_samples must be at least 1",
+			));
+		}
+		if self.qubits.is_empty() {
+			return Err(SolveError::EmptyModel);
+		}
+		Ok(self.new_state())
+	}
+
+	fn new_state(&self) -> SolveState<'a, Tq, Tc, R> {
+		SolveState {
+			start: Instant::now(),
+			// Soft constraints are not tuned by the feedback.
+			placeholders: self
+				.model
+				.get_placeholders()
+				.into_iter()
+				.filter(|p| !matches!(p, Placeholder::Soft(_)))
+				.collect(),
+			qubit_map: self.qubit_map(),
+			phdict: HashMap::new(),
+			size: 0.0,
+			budget: Arc::default(),
+			max_generations: None,
+			feasible_only: false,
+			exhaustive: false,
+			penalties: None,
+			strength: self.coeff_strength,
+			old_energy: R::MAX,
+			stale: 0,
+			ret: None,
+			ret_strengths: HashMap::new(),
+			progress: None,
+			stats: SolveStats {
+				best_energies: Vec::new(),
+				unsatisfied: Vec::new(),
+				samples: 0,
+				elapsed: Duration::default(),
+				strengths: HashMap::new(),
+				constraint_energies: HashMap::new(),
+			},
+		}
+	}
+
+	/// Build the QUBO of the first generation, with the initial weights of the
+	/// placeholders, together with the qubits in the order of its indices,
+	/// which is also used by [`SolutionView`].
+	#[allow(clippy::type_complexity)]
+	pub fn debug_qubo(&self) -> (R, FixedSingleQuadricModel<Binary<R>>, Vec<&'a Qubit<Tq>>) {
+		let mut state = self.new_state();
+		state.reset();
+		let (c, model) = self.generate_qubo(&mut state, 0);
+		(c, model, self.qubits.clone())
+	}
+
+	/// Get the number of samples of the next generation and count them as
+	/// drawn. Returns 0 if the solve should be stopped.
+	fn next_samples(&self, state: &SolveState<'a, Tq, Tc, R>) -> usize {
+		let budget = &state.budget;
+		if budget.stop.load(AtomicOrdering::Relaxed) {
+			return 0;
+		}
+		if let Some(timeout) = self.timeout {
+			if state.ret.is_some() && state.start.elapsed() >= timeout {
+				return 0;
+			}
+		}
+		let max = state.max_generations;
+		if budget
+			.generations
+			.fetch_update(AtomicOrdering::Relaxed, AtomicOrdering::Relaxed, |g| {
+				Some(g + 1).filter(|_| max.is_none_or(|max| g < max))
+			})
+			.is_err()
+		{
+			return 0;
+		}
+		match self.total_samples {
+			Some(total) => {
+				let take = |drawn: usize| self.samples.min(total - drawn);
+				let drawn = budget
+					.drawn
+					.fetch_update(AtomicOrdering::Relaxed, AtomicOrdering::Relaxed, |d| {
+						Some(d + take(d))
+					})
+					.unwrap();
+				take(drawn)
+			}
+			None => self.samples,
+		}
+	}
+
+	/// Whether the best solution of the iteration has not improved for
+	/// `patience` generations.
+	fn is_plateau(&self, state: &SolveState<'a, Tq, Tc, R>) -> bool {
+		self.patience.is_some_and(|p| state.stale >= p)
+	}
+
+	/// Generate the QUBO of the generation, applying the placeholder feedback.
+	fn generate_qubo(
+		&self,
+		state: &mut SolveState<'a, Tq, Tc, R>,
+		generation: usize,
+	) -> (R, FixedSingleQuadricModel<Binary<R>>) {
+		state.strength = self
+			.coeff_schedule
+			.get(generation)
+			.copied()
+			.unwrap_or(self.coeff_strength);
+		let state = &*state;
+		let (mut c, mut model) = self
+			.model
+			.generate_qubo(&self.qubits, &mut |p| state.placeholder_strength(p));
+		if state.feasible_only {
+			// The weights are linear in the placeholders, so the objective is
+			// the QUBO with all the placeholders set to zero. The penalties of
+			// the soft constraints have placeholders and are kept.
+			let (c0, objective) = self.model.generate_qubo(&self.qubits, &mut |_| R::zero());
+			for p in objective.prods() {
+				model.add_weight(p[0], p[1], -objective.get_weight(&p));
+			}
+			c -= c0;
+		}
+		(c, model)
+	}
+
+	/// Update the state with the solutions sampled in a generation. Returns
+	/// `ControlFlow::Break` if the solve should be stopped.
+	fn feed_solutions(
+		&self,
+		state: &mut SolveState<'a, Tq, Tc, R>,
+		iteration: usize,
+		generation: usize,
+		c: R,
+		model: &FixedSingleQuadricModel<Binary<R>>,
+		solutions: Vec<SingleSolution<Binary<R>>>,
+	) -> ControlFlow<()> {
+		state.stats.samples += solutions.len();
+		let sol = match SingleSolution::best_of(solutions) {
+			Some(sol) => sol,
+			None => return ControlFlow::Continue(()),
+		};
+		let energy = sol.energy.unwrap();
+		let previous = (state.old_energy, state.best().map_or(usize::MAX, |b| b.1));
+		let mut is_satisfied = false;
+		if state.old_energy > energy || state.ret.is_none() {
+			state.old_energy = energy;
+			let (mut constraint_labels, mut hard) = (Vec::new(), false);
+			let ans = assignment(&self.qubits, &sol);
+			// Taken before the feedback changes the strengths.
+			let strengths = state.constraint_strengths();
+			for c in self
+				.model
+				.get_unsatisfied_constraints(&ans, self.constraint_tolerance)
+			{
+				if let Some(ph) = &c.placeholder {
+					if let Some(point) = state.phdict.get_mut(ph) {
+						let next = self
+							.penalty_update
+							.next_weight(*point, c.violation(&ans).map_or(0.0, |v| v.as_f64()));
+						state.size += next - *point;
+						*point = next;
+					}
+				}
+				if let Some(label) = &c.label {
+					hard |= !matches!(c.placeholder, Some(Placeholder::Soft(_)));
+					constraint_labels.push(label);
+				}
+			}
+			// Violated soft constraints are reported, but their penalties are
+			// not tuned, so they do not keep the solve running.
+			is_satisfied = !hard;
+			// `old_energy` is reset on each iteration, so keep the best solution
+			// across the iterations.
+			if is_better(energy + c, constraint_labels.len(), state.best()) {
+				state.ret = Some((
 					energy + c,
-					SolutionView::new(sol.with_local_field(&model), qubit_map.clone()),
+					SolutionView::new(sol.with_local_field(model), state.qubit_map.clone())
+						.with_fixed(self.model.fixed()),
 					constraint_labels,
 				));
-				if is_satisfied {
-					return Ok(ret.unwrap());
-				}
+				state.ret_strengths = strengths;
 			}
 		}
-		Ok(ret.unwrap())
+		let unsatisfied = state.ret.as_ref().map(|r| r.2.len()).unwrap_or(0);
+		state.stats.best_energies.push(state.old_energy);
+		state.stats.unsatisfied.push(unsatisfied);
+		if state.old_energy < previous.0 || unsatisfied < previous.1 {
+			state.stale = 0;
+		} else {
+			state.stale += 1;
+		}
+		// The progress is reported also for the generation which satisfies the
+		// constraints.
+		let best_energy = state.ret.as_ref().map(|r| r.0);
+		let labels = state.ret.as_ref().map(|r| &r.2[..]).unwrap_or(&[]);
+		let flow = if let Some(tx) = &state.progress {
+			// The receiver is alive until all the iterations are finished.
+			tx.send((iteration, generation, best_energy, labels.to_vec()))
+				.unwrap();
+			ControlFlow::Continue(())
+		} else {
+			self.report_progress(iteration, generation, best_energy, labels)
+		};
+		if is_satisfied && !state.exhaustive {
+			return ControlFlow::Break(());
+		}
+		flow
+	}
+
+	/// Call the progress callback, if any.
+	fn report_progress(
+		&self,
+		iteration: usize,
+		generation: usize,
+		best_energy: Option<R>,
+		unsatisfied_labels: &[&Tc],
+	) -> ControlFlow<()> {
+		if let Some(cb) = &self.progress_callback {
+			let info = ProgressInfo {
+				iteration,
+				generation,
+				best_energy,
+				unsatisfied: unsatisfied_labels.len(),
+				unsatisfied_labels,
+			};
+			return (cb.lock().unwrap())(info);
+		}
+		ControlFlow::Continue(())
+	}
+
+	#[allow(clippy::type_complexity)]
+	fn finish<E>(
+		&self,
+		mut state: SolveState<'a, Tq, Tc, R>,
+	) -> Result<(R, SolutionView<Tq, R>, Vec<&'a Tc>, SolveStats<Tc, R>), SolveError<E>> {
+		let (size, strength) = (state.size, state.strength);
+		state.stats.strengths = state
+			.phdict
+			.iter()
+			.filter_map(|(p, cnt)| {
+				if let Placeholder::Constraint(label) = p {
+					Some((label.clone(), penalty_strength(*cnt, size, strength)))
+				} else {
+					None
+				}
+			})
+			.collect();
+		if let Some((_, view, _)) = &state.ret {
+			let strengths = &state.ret_strengths;
+			state.stats.constraint_energies = self
+				.model
+				.constraint_energies(&self.view_assignment(view), &mut |label| strengths[label]);
+		}
+		state.stats.elapsed = state.start.elapsed();
+		let stats = state.stats;
+		state
+			.ret
+			.map(|(a, b, c)| (a, b, c, stats))
+			.ok_or(SolveError::NoSolutionFound)
+	}
+}
+
+fn assignment<'a, Tq: TqType, R: Real>(
+	qubits: &[&'a Qubit<Tq>],
+	sol: &SingleSolution<Binary<R>>,
+) -> HashMap<&'a Qubit<Tq>, bool> {
+	qubits
+		.iter()
+		.enumerate()
+		.map(|(i, q)| (*q, sol[i]))
+		.collect()
+}
+
+/// The penalty strength of a placeholder whose weight is `cnt` out of `size`.
+/// The ratio is calculated in `f64` and rounded up, so that the strength of
+/// integer types does not truncate to zero.
+fn penalty_strength<R: Real>(cnt: f64, size: f64, strength: R) -> R {
+	R::from_f64_ceil(cnt / size * strength.as_f64())
+}
+
+/// Whether `sol` satisfies all the labeled hard constraints of `model`.
+fn is_feasible<Tq: TqType, Tc: TcType, R: Real>(
+	model: &CompiledModel<(), Tq, Tc, R>,
+	qubits: &[&Qubit<Tq>],
+	sol: &SingleSolution<Binary<R>>,
+	tol: f64,
+) -> bool {
+	model
+		.get_unsatisfied_constraints(&assignment(qubits, sol), tol)
+		.iter()
+		.all(|c| c.label.is_none() || matches!(c.placeholder, Some(Placeholder::Soft(_))))
+}
+
+/// Create the random number generator for a sample. If `seed` is given, the
+/// generator is seeded with a value derived from the arguments, which is the
+/// same on every platform and Rust release.
+fn sample_rng(seed: Option<u64>, iteration: usize, generation: usize, index: usize) -> StdRng {
+	if let Some(seed) = seed {
+		let seed = [iteration, generation, index]
+			.iter()
+			.fold(splitmix64(seed), |h, v| splitmix64(h ^ *v as u64));
+		StdRng::seed_from_u64(seed)
+	} else {
+		StdRng::from_rng(OsRng).unwrap()
 	}
 }
+
+/// A step of the SplitMix64 generator, used to mix the seeds.
+fn splitmix64(x: u64) -> u64 {
+	let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+	z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+	z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+	z ^ (z >> 31)
+}
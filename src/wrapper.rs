@@ -48,6 +48,7 @@ where
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Hash, Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Placeholder<Tp, Tc>
 where
 	Tp: TpType,
@@ -55,6 +56,8 @@ where
 {
 	Placeholder(Tp),
 	Constraint(Tc),
+	/// Marks the penalty of a soft constraint, whose strength is always `1`.
+	Soft(Tc),
 }
 
 impl<Tp, Tc> Placeholder<Tp, Tc>
@@ -68,6 +71,7 @@ where
 				panic!("Placeholder {:?} must be fulfilled.", &p)
 			}
 			Self::Constraint(c) => Placeholder::Constraint(c),
+			Self::Soft(c) => Placeholder::Soft(c),
 		}
 	}
 }
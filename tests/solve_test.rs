@@ -0,0 +1,1140 @@
+extern crate annealers;
+extern crate async_trait;
+extern crate classical_solver;
+extern crate rustqubo;
+use annealers::model::{FixedSingleModelView, FixedSingleQuadricModel};
+use annealers::node::Binary;
+use annealers::order::Quadric;
+use annealers::solution::SingleSolution;
+use annealers::solver::{
+	AsyncSolver, ClassicalSolver, Solver, SolverGenerator, StructuredSolverGenerator,
+	UnstructuredSolverGenerator,
+};
+use async_trait::async_trait;
+use classical_solver::beta::BetaType;
+use classical_solver::sa::SimulatedAnnealerGenerator;
+use classical_solver::ExactSolverError;
+use rustqubo::embedding::{EmbeddingError, IdentityEmbedder};
+use rustqubo::solve::{PenaltyUpdate, SimpleSolver, SolveError};
+use rustqubo::{Constraint, Expr, Qubit};
+use std::collections::BTreeSet;
+use std::future::Future;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake};
+use std::time::Duration;
+
+fn one_hot(n: usize) -> Expr<(), usize, &'static str, f64> {
+	let inner = (0..n).fold(-Expr::one(), |e, i| e + Expr::Binary(i));
+	Expr::Constraint {
+		label: "one-hot",
+		expr: Box::new(inner ^ 2),
+	} + (0..n).fold(Expr::zero(), |e, i| e + (i as f64) * Expr::Binary(i))
+}
+
+#[test]
+fn progress_callback_cancel_test() {
+	let compiled = one_hot(8).compile();
+	let mut solver = SimpleSolver::new(&compiled);
+	let count = Arc::new(AtomicUsize::new(0));
+	let count_cb = count.clone();
+	solver.set_progress_callback(Box::new(move |info| {
+		assert_eq!(info.iteration, 0);
+		assert_eq!(info.generation, 0);
+		assert!(info.best_energy.is_some());
+		count_cb.fetch_add(1, Ordering::SeqCst);
+		ControlFlow::Break(())
+	}));
+	let (_, qubits, _) = solver.solve_with_constraints().unwrap();
+	assert_eq!(count.load(Ordering::SeqCst), 1);
+	for i in 0..8 {
+		assert!(qubits.get(&i).is_some());
+	}
+}
+
+#[test]
+fn parallel_progress_callback_test() {
+	// The constraints conflict, so no iteration stops early.
+	let hmlt: Expr<(), usize, &str, f64> = Expr::Constraint {
+		label: "one",
+		expr: Box::new((Expr::Binary(0) + Expr::Binary(1) - Expr::Number(1.0)) ^ 2),
+	} + Expr::Constraint {
+		label: "two",
+		expr: Box::new((Expr::Binary(0) + Expr::Binary(1) - Expr::Number(2.0)) ^ 2),
+	};
+	let compiled = hmlt.compile();
+	let mut solver = SimpleSolver::new(&compiled);
+	solver.iterations = 4;
+	solver.generations = 3;
+	solver.parallel_iterations = true;
+	let caller = std::thread::current().id();
+	let events = Arc::new(Mutex::new(Vec::new()));
+	let events_cb = events.clone();
+	solver.set_progress_callback(Box::new(move |info| {
+		assert_eq!(std::thread::current().id(), caller);
+		events_cb
+			.lock()
+			.unwrap()
+			.push((info.iteration, info.generation));
+		ControlFlow::Continue(())
+	}));
+	let (_, _, unsatisfied) = solver.solve_with_constraints().unwrap();
+	assert_eq!(unsatisfied.len(), 1);
+	let mut events = events.lock().unwrap().clone();
+	events.sort();
+	let expected = (0..4)
+		.flat_map(|i| (0..3).map(move |g| (i, g)))
+		.collect::<Vec<_>>();
+	assert_eq!(events, expected);
+}
+
+#[test]
+fn sa_parameters_test() {
+	let n = 40;
+	let hmlt: Expr<(), usize, (), f64> = (0..n).fold(Expr::zero(), |e, i| {
+		e + Expr::Spin(i) * Expr::Spin((i + 1) % n) - 0.1 * Expr::Spin(i)
+	});
+	let compiled = hmlt.compile();
+	// The number of the beta steps of each sample
+	let steps = |mut solver: SimpleSolver<_, _, SimulatedAnnealerGenerator<_>, _, _>| {
+		let count = Arc::new(AtomicUsize::new(0));
+		let count_cb = count.clone();
+		solver.solver_generator =
+			solver
+				.solver_generator
+				.clone()
+				.with_diagnostic_callback(move |_, _, _, _| {
+					count_cb.fetch_add(1, Ordering::SeqCst);
+				});
+		solver.samples = 1;
+		solver.iterations = 1;
+		solver.generations = 1;
+		let (_, sol) = solver.solve().unwrap();
+		assert_eq!(sol.keys().count(), n);
+		count.load(Ordering::SeqCst)
+	};
+	assert_eq!(steps(SimpleSolver::new(&compiled)), 100);
+
+	let solver = SimpleSolver::new(&compiled)
+		.sweeps_per_round(1)
+		.beta(BetaType::Count(5));
+	assert_eq!(solver.solver_generator.sweeps_per_round, 1);
+	assert!(matches!(solver.solver_generator.beta, BetaType::Count(5)));
+	assert_eq!(steps(solver), 5);
+}
+
+#[test]
+fn invalid_parameter_test() {
+	let compiled = one_hot(4).compile();
+	let mut solver = SimpleSolver::new(&compiled);
+	solver.generations = 0;
+	assert!(matches!(
+		solver.solve_with_constraints(),
+		Err(SolveError::InvalidParameter(_))
+	));
+}
+
+#[test]
+fn empty_model_test() {
+	let hmlt: Expr<(), usize, (), f64> = Expr::Number(1.0);
+	let compiled = hmlt.compile();
+	assert!(matches!(
+		SimpleSolver::new(&compiled).solve(),
+		Err(SolveError::EmptyModel)
+	));
+}
+
+#[test]
+fn with_seed_test() {
+	let compiled = one_hot(8).compile();
+	let run = || {
+		let (energy, sol, _) = SimpleSolver::new(&compiled)
+			.with_seed(42)
+			.solve_with_constraints()
+			.unwrap();
+		(energy, (0..8).map(|i| sol[&i]).collect::<Vec<_>>())
+	};
+	assert_eq!(run(), run());
+}
+
+#[test]
+fn evaluate_test() {
+	let hmlt: Expr<(), &str, (), f64> =
+		-Expr::Spin("a") * Expr::Spin("b") * 2.0 + Expr::Spin("a") * 3.0;
+	let assignment = vec![("a", false), ("b", false)].into_iter().collect();
+	assert_eq!(hmlt.evaluate(&assignment), Some(-5.0));
+	assert_eq!(
+		hmlt.evaluate(&vec![("a", true)].into_iter().collect()),
+		None
+	);
+
+	let compiled = hmlt.clone().compile();
+	let (c, sol) = SimpleSolver::new(&compiled).solve().unwrap();
+	let assignment = sol.keys().map(|k| (*k, sol[k])).collect();
+	assert_eq!(hmlt.evaluate(&assignment), Some(c));
+}
+
+#[test]
+fn solve_stats_test() {
+	let compiled = one_hot(8).compile();
+	let mut solver = SimpleSolver::new(&compiled);
+	solver.iterations = 1;
+	let (_, _, _, stats) = solver.solve_with_stats().unwrap();
+	let generations = stats.best_energies.len();
+	assert!(generations >= 1 && generations <= solver.generations);
+	assert_eq!(stats.unsatisfied.len(), generations);
+	assert_eq!(stats.samples, generations * solver.samples);
+	for w in stats.best_energies.windows(2) {
+		assert!(w[1] <= w[0]);
+	}
+	assert!(stats.strengths.contains_key(&"one-hot"));
+}
+
+#[test]
+fn get_constraint_test() {
+	let compiled = one_hot(3).compile();
+	let constraint: &Constraint<(), usize, &str, f64> =
+		compiled.get_constraint(&"one-hot").unwrap();
+	assert_eq!(constraint.label, Some("one-hot"));
+	let qubits = (0..3).map(Qubit::Qubit).collect::<Vec<_>>();
+	let map = qubits.iter().map(|q| (q, *q == Qubit::Qubit(1))).collect();
+	assert!(constraint.is_satisfied(&map));
+	let map = qubits.iter().map(|q| (q, true)).collect();
+	assert!(!constraint.is_satisfied(&map));
+}
+
+#[test]
+fn solve_population_test() {
+	let compiled = one_hot(4).compile();
+	let generations = Arc::new(AtomicUsize::new(0));
+	let generations_hook = generations.clone();
+	let mut solver = SimpleSolver::new(&compiled).with_debug_hook(move |_, _, _, _| {
+		generations_hook.fetch_add(1, Ordering::SeqCst);
+	});
+	solver.iterations = 2;
+	solver.generations = 3;
+	let population = solver.solve_population().unwrap();
+	// The solve is not stopped by the feasible solutions.
+	assert_eq!(generations.load(Ordering::SeqCst), 6);
+	assert!(!population.is_empty());
+	for w in population.windows(2) {
+		assert!(w[0].0 <= w[1].0);
+	}
+	let states = population
+		.iter()
+		.map(|(_, sol)| (0..4).map(|i| sol[&i]).collect::<Vec<_>>())
+		.collect::<Vec<_>>();
+	for (i, s) in states.iter().enumerate() {
+		assert!(!states[i + 1..].contains(s));
+	}
+	// The only constraint has the whole strength, which is 50 by default.
+	for (energy, state) in population.iter().zip(states.iter()) {
+		let ones = state.iter().filter(|b| **b).count() as f64;
+		let objective = (0..4).filter(|i| state[*i]).sum::<usize>() as f64;
+		assert_eq!(energy.0, objective + 50.0 * (ones - 1.0) * (ones - 1.0));
+	}
+}
+
+#[test]
+fn initial_state_test() {
+	let compiled = one_hot(8).compile();
+	let assignment = (0..8).map(|i| (i, i == 0)).collect();
+	let mut solver = SimpleSolver::new(&compiled)
+		.sweeps_per_round(1)
+		.beta(BetaType::Schedule(vec![100.0]))
+		.initial_state(&assignment);
+	solver.iterations = 1;
+	solver.generations = 1;
+	let (energy, sol, unsatisfied) = solver.solve_with_constraints().unwrap();
+	assert_eq!(energy, 0.0);
+	assert!(unsatisfied.is_empty());
+	for i in 0..8 {
+		assert_eq!(sol[&i], i == 0);
+	}
+}
+
+#[test]
+fn coeff_schedule_test() {
+	let compiled = one_hot(4).compile();
+	let mut solver = SimpleSolver::new(&compiled).with_coeff_schedule(vec![7.0]);
+	solver.iterations = 1;
+	solver.generations = 1;
+	let (_, _, _, stats) = solver.solve_with_stats().unwrap();
+	assert_eq!(stats.strengths[&"one-hot"], 7.0);
+}
+
+#[test]
+fn solution_view_test() {
+	let b = |i: usize| Expr::Binary(i);
+	let hmlt: Expr<(), usize, (), f64> = -2.0 * b(0) * b(1) * b(2) + b(0);
+	let compiled = hmlt.clone().compile();
+	let (_, sol) = SimpleSolver::new(&compiled).solve().unwrap();
+	assert_eq!(sol.len(), 3);
+	assert_eq!(sol.ancilla_values().len(), 1);
+	let map = sol.to_map();
+	assert_eq!(map.len(), 3);
+	for (k, v) in &sol {
+		assert_eq!(map[k], v);
+	}
+	let x = |i: usize| if map[&i] { 1.0 } else { 0.0 };
+	assert_eq!(hmlt.evaluate(&map), Some(-2.0 * x(0) * x(1) * x(2) + x(0)));
+	assert!(sol.clone() == sol);
+	let mut pairs = sol.into_iter().collect::<Vec<_>>();
+	pairs.sort();
+	assert_eq!(pairs, (0..3).map(|i| (i, map[&i])).collect::<Vec<_>>());
+}
+
+#[test]
+fn timeout_test() {
+	let compiled = one_hot(8).compile();
+	let solver = SimpleSolver::new(&compiled)
+		.with_coeff_schedule(vec![0.0; 30])
+		.with_timeout(Duration::from_secs(0));
+	let (_, _, unsatisfied, stats) = solver.solve_with_stats().unwrap();
+	assert_eq!(stats.best_energies.len(), 1);
+	assert_eq!(unsatisfied.len(), stats.unsatisfied[0]);
+}
+
+#[test]
+fn solve_verified_test() {
+	let b = |i: usize| Expr::Binary(i);
+	let objective: Expr<(), usize, &str, f64> = -2.0 * b(0) * b(1) * b(2) + b(0) + 3.0 * b(3);
+	let hmlt = one_hot(4) + objective.clone();
+	let compiled = hmlt.compile();
+	let (_, energy, sol, _) = SimpleSolver::new(&compiled).solve_verified().unwrap();
+	let map = sol.to_map();
+	let x = |i: usize| if map[&i] { 1.0 } else { 0.0 };
+	let one_hot_objective = (0..4).map(|i| i as f64 * x(i)).sum::<f64>();
+	assert_eq!(
+		objective.evaluate(&map).map(|e| e + one_hot_objective),
+		Some(energy)
+	);
+}
+
+#[test]
+fn solution_view_iter_test() {
+	let hmlt: Expr<(), usize, (), f64> = -10.0 * Expr::Binary(1usize) + 5.0 * Expr::Binary(2usize);
+	let compiled = hmlt.compile();
+	let mut solver = SimpleSolver::new(&compiled);
+	solver.samples = 8;
+	let (_, sol) = solver.solve().unwrap();
+	let mut pairs = sol.iter().map(|(k, v)| (*k, v)).collect::<Vec<_>>();
+	pairs.sort();
+	assert_eq!(pairs, vec![(1, true), (2, false)]);
+	assert_eq!((&sol).into_iter().count(), 2);
+}
+
+#[test]
+fn with_progress_callback_test() {
+	let hmlt: Expr<(), usize, &str, f64> = Expr::Constraint {
+		label: "pair",
+		expr: Box::new((Expr::Binary(0usize) + Expr::Binary(1usize) - 1.0) ^ 2usize),
+	} + 5.0 * Expr::Binary(0usize)
+		+ 5.0 * Expr::Binary(1usize);
+	let compiled = hmlt.compile();
+	let calls = Arc::new(Mutex::new(Vec::new()));
+	let calls_cb = calls.clone();
+	let solver = SimpleSolver::new(&compiled)
+		.beta(BetaType::Schedule(vec![100.0]))
+		.with_coeff_schedule(vec![0.0; 30])
+		.with_progress_callback(move |iteration, generation, _, labels| {
+			let labels = labels.iter().map(|l| **l).collect::<Vec<_>>();
+			calls_cb
+				.lock()
+				.unwrap()
+				.push((iteration, generation, labels));
+			ControlFlow::Break(())
+		});
+	let (_, _, unsatisfied) = solver.solve_with_constraints().unwrap();
+	assert_eq!(unsatisfied, vec![&"pair"]);
+	assert_eq!(*calls.lock().unwrap(), vec![(0, 0, vec!["pair"])]);
+}
+
+/// Generator of `MockSolver`, which records the weight of the first qubit of
+/// each generated model.
+struct MockGenerator(Arc<Mutex<Vec<f64>>>);
+
+struct MockSolver(usize);
+
+impl<'a> SolverGenerator<'a, FixedSingleQuadricModel<Binary<f64>>> for MockGenerator {
+	type SolverType = MockSolver;
+	type ErrorType = std::fmt::Error;
+
+	fn generate(
+		&self,
+		model: &'a FixedSingleQuadricModel<Binary<f64>>,
+	) -> Result<MockSolver, std::fmt::Error> {
+		let weight = FixedSingleModelView::get_weight(model, &[0, 0]);
+		self.0.lock().unwrap().push(weight);
+		Ok(MockSolver(FixedSingleModelView::size(model)))
+	}
+}
+
+impl<'a> UnstructuredSolverGenerator<'a, FixedSingleQuadricModel<Binary<f64>>> for MockGenerator {
+	type Order = Quadric;
+
+	fn order(&self) -> Quadric {
+		Quadric
+	}
+}
+
+impl Solver for MockSolver {
+	type ErrorType = std::fmt::Error;
+	type SolutionType = SingleSolution<Binary<f64>>;
+}
+
+#[async_trait]
+impl AsyncSolver for MockSolver {
+	async fn solve_async(&self) -> Result<Vec<SingleSolution<Binary<f64>>>, std::fmt::Error> {
+		Ok(vec![SingleSolution::from_vec(&vec![false; self.0])])
+	}
+}
+
+impl ClassicalSolver for MockSolver {
+	fn solve_with_rng<T: rand::Rng>(
+		&self,
+		_r: &mut T,
+	) -> Result<Vec<SingleSolution<Binary<f64>>>, std::fmt::Error> {
+		Ok(vec![SingleSolution::from_vec(&vec![false; self.0])])
+	}
+}
+
+struct ThreadWaker(std::thread::Thread);
+
+impl Wake for ThreadWaker {
+	fn wake(self: Arc<Self>) {
+		self.0.unpark();
+	}
+}
+
+fn block_on<F: Future>(fut: F) -> F::Output {
+	let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+	let mut cx = Context::from_waker(&waker);
+	let mut fut = Box::pin(fut);
+	loop {
+		match fut.as_mut().poll(&mut cx) {
+			Poll::Ready(v) => return v,
+			Poll::Pending => std::thread::park(),
+		}
+	}
+}
+
+#[test]
+fn solve_async_test() {
+	let compiled = one_hot(2).compile();
+	let weights = Arc::new(Mutex::new(Vec::new()));
+	let mut solver = SimpleSolver::with_solver(&compiled, MockGenerator(weights.clone()))
+		.with_coeff_schedule(vec![1.0, 2.0]);
+	solver.iterations = 1;
+	solver.generations = 2;
+	solver.samples = 1;
+	let (energy, sol, unsatisfied) = block_on(solver.solve_async()).unwrap();
+	// The all-false assignment violates the constraint in both generations,
+	// and the penalty strength follows the schedule. The first generation has
+	// the lower energy.
+	assert_eq!(*weights.lock().unwrap(), vec![-1.0, -2.0]);
+	assert_eq!(energy, 1.0);
+	assert_eq!(sol.get(&0), Some(false));
+	assert_eq!(unsatisfied, vec![&"one-hot"]);
+}
+
+#[test]
+fn spin_test() {
+	let hmlt: Expr<(), &str, (), f64> = Expr::Spin("a") - 2.0 * Expr::Spin("b");
+	let compiled = hmlt.compile();
+	let mut solver = SimpleSolver::new(&compiled).beta(BetaType::Schedule(vec![100.0]));
+	solver.samples = 8;
+	let (energy, sol) = solver.solve().unwrap();
+	assert_eq!(energy, -3.0);
+	assert_eq!(sol.spin(&"a"), Some(-1));
+	assert_eq!(sol.spin(&"b"), Some(1));
+	assert_eq!(sol.spin(&"c"), None);
+	let map = sol.to_map();
+	assert!(!map[&"a"]);
+	assert!(map[&"b"]);
+}
+
+#[test]
+fn batched_samples_test() {
+	let hmlt: Expr<(), usize, (), f64> = (0..100).fold(Expr::zero(), |e, i| e + Expr::Binary(i));
+	let compiled = hmlt.compile();
+	let weights = Arc::new(Mutex::new(Vec::new()));
+	let mut solver = SimpleSolver::with_solver(&compiled, MockGenerator(weights.clone()));
+	solver.iterations = 1;
+	solver.generations = 1;
+	solver.samples = 500;
+	let (_, _, _, stats) = solver.solve_with_stats().unwrap();
+	assert_eq!(stats.samples, 500);
+	assert_eq!(weights.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn generate_once_per_generation_test() {
+	let compiled = one_hot(2).compile();
+	let weights = Arc::new(Mutex::new(Vec::new()));
+	let mut solver = SimpleSolver::with_solver(&compiled, MockGenerator(weights.clone()));
+	solver.iterations = 2;
+	solver.generations = 3;
+	solver.samples = 8;
+	// The all-false assignment never satisfies the constraint, so all the
+	// generations are run.
+	let (_, _, _, stats) = solver.solve_with_stats().unwrap();
+	assert_eq!(stats.samples, 2 * 3 * 8);
+	assert_eq!(weights.lock().unwrap().len(), 2 * 3);
+	weights.lock().unwrap().clear();
+	block_on(solver.solve_async()).unwrap();
+	assert_eq!(weights.lock().unwrap().len(), 2 * 3);
+}
+
+#[test]
+fn total_samples_test() {
+	let compiled = one_hot(2).compile();
+	let mut solver = SimpleSolver::with_solver(&compiled, MockGenerator(Default::default()))
+		.with_total_samples(5);
+	solver.iterations = 1;
+	solver.generations = 10;
+	solver.samples = 2;
+	let (_, _, unsatisfied, stats) = solver.solve_with_stats().unwrap();
+	assert_eq!(unsatisfied, vec![&"one-hot"]);
+	assert_eq!(stats.samples, 5);
+	assert_eq!(stats.best_energies.len(), 3);
+}
+
+fn coloring(
+	edges: &[(usize, usize)],
+	nodes: usize,
+	colors: usize,
+) -> Expr<(), (usize, usize), String, f64> {
+	let x = |n: usize, c: usize| Expr::Binary((n, c));
+	let mut hmlt = Expr::zero();
+	for n in 0..nodes {
+		let sum = (0..colors).fold(-Expr::one(), |e, c| e + x(n, c));
+		hmlt += Expr::Constraint {
+			label: format!("one-hot {}", n),
+			expr: Box::new(sum ^ 2),
+		};
+	}
+	for &(u, v) in edges {
+		for c in 0..colors {
+			hmlt += Expr::Constraint {
+				label: format!("edge {} {}", u, v),
+				expr: Box::new(x(u, c) * x(v, c)),
+			};
+		}
+	}
+	hmlt
+}
+
+#[test]
+fn solve_feasible_test() {
+	let edges = [(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 0)];
+	let compiled = coloring(&edges, 5, 3).compile();
+	let calls = Arc::new(AtomicUsize::new(0));
+	let calls_cb = calls.clone();
+	let mut solver = SimpleSolver::new(&compiled).with_progress_callback(move |_, _, _, _| {
+		calls_cb.fetch_add(1, Ordering::SeqCst);
+		ControlFlow::Continue(())
+	});
+	solver.samples = 8;
+	let (sol, unsatisfied) = solver.solve_feasible(None).unwrap();
+	assert!(unsatisfied.is_empty());
+	let color = |n: usize| {
+		let v = (0..3).filter(|c| sol[&(n, *c)]).collect::<Vec<_>>();
+		assert_eq!(v.len(), 1);
+		v[0]
+	};
+	for &(u, v) in edges.iter() {
+		assert_ne!(color(u), color(v));
+	}
+	assert!(calls.load(Ordering::SeqCst) < solver.generations - 1);
+
+	// The samples before the first feasible one are always taken, so the
+	// seeded result does not depend on the scheduling of the threads.
+	let solve = || {
+		let mut solver = SimpleSolver::new(&compiled).with_seed(0);
+		solver.samples = 8;
+		let (sol, unsatisfied) = solver.solve_feasible(None).unwrap();
+		assert!(unsatisfied.is_empty());
+		sol.to_map()
+	};
+	assert_eq!(solve(), solve());
+
+	// A triangle cannot be colored with 2 colors.
+	let compiled = coloring(&[(0, 1), (1, 2), (2, 0)], 3, 2).compile();
+	let calls = Arc::new(AtomicUsize::new(0));
+	let calls_cb = calls.clone();
+	let solver = SimpleSolver::new(&compiled).with_progress_callback(move |_, _, _, _| {
+		calls_cb.fetch_add(1, Ordering::SeqCst);
+		ControlFlow::Continue(())
+	});
+	let (_, unsatisfied) = solver.solve_feasible(Some(3)).unwrap();
+	assert!(!unsatisfied.is_empty());
+	assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn penalty_update_test() {
+	let x = |i: usize| Expr::Binary(i);
+	// The objective prefers two of the three qubits in "a", so "a" is violated
+	// until its penalty is strong enough.
+	let hmlt: Expr<(), usize, &str, f64> = Expr::Constraint {
+		label: "a",
+		expr: Box::new((x(0) + x(1) + x(2) - 1.0) ^ 2usize),
+	} + Expr::Constraint {
+		label: "b",
+		expr: Box::new((x(3) + x(4) - 1.0) ^ 2usize),
+	} - 7.5 * (x(0) + x(1) + x(2));
+	let compiled = hmlt.compile();
+	// The annealing is cold and seeded, so the number of generations is
+	// reproducible.
+	let generations = |update| {
+		let mut solver = SimpleSolver::new(&compiled)
+			.beta(BetaType::Schedule(vec![100.0]))
+			.with_coeff_schedule((0..60).map(|g| 10.0 * 1.05f64.powi(g)).collect())
+			.with_penalty_update(update)
+			.with_seed(0);
+		solver.samples = 8;
+		solver.iterations = 1;
+		solver.generations = 60;
+		let (_, _, unsatisfied, stats) = solver.solve_with_stats().unwrap();
+		assert!(unsatisfied.is_empty());
+		stats.best_energies.len()
+	};
+	let additive = generations(PenaltyUpdate::Additive { step: 1.0 });
+	let multiplicative = generations(PenaltyUpdate::Multiplicative { factor: 2.0 });
+	let proportional = generations(PenaltyUpdate::ViolationProportional { gain: 10.0 });
+	// The penalty of "a" grows faster with the other strategies.
+	assert!(multiplicative < additive);
+	assert!(proportional < additive);
+}
+
+#[test]
+fn weighted_constraint_test() {
+	let solve = |wa: f64, wb: f64| {
+		// "a" requires both b0 and b1 to be 1 and "b" forbids it.
+		let hmlt: Expr<(), usize, &str, f64> = Expr::WeightedConstraint {
+			label: "a",
+			expr: Box::new(2.0 - Expr::Binary(0usize) - Expr::Binary(1usize)),
+			weight: wa,
+		} + Expr::WeightedConstraint {
+			label: "b",
+			expr: Box::new(Expr::Binary(0usize) * Expr::Binary(1usize)),
+			weight: wb,
+		};
+		let compiled = hmlt.compile();
+		let mut solver = SimpleSolver::new(&compiled).beta(BetaType::Schedule(vec![100.0]));
+		solver.samples = 8;
+		solver.iterations = 1;
+		let (_, sol, unsatisfied) = solver.solve_with_constraints().unwrap();
+		(
+			sol[&0] && sol[&1],
+			unsatisfied.into_iter().copied().collect::<Vec<_>>(),
+		)
+	};
+	assert_eq!(solve(10.0, 1.0), (true, vec!["b"]));
+	assert_eq!(solve(1.0, 10.0), (false, vec!["a"]));
+}
+
+#[test]
+fn soft_constraint_test() {
+	let solve = |penalty: f64| {
+		let b = |i: usize| Expr::Binary(i);
+		let hmlt: Expr<(), usize, &str, f64> =
+			Expr::soft_constraint("pair", b(0) + b(1) - 1.0, penalty) + 5.0 * b(0) + 5.0 * b(1);
+		let compiled = hmlt.clone().compile();
+		let mut solver = SimpleSolver::new(&compiled).beta(BetaType::Schedule(vec![100.0]));
+		solver.samples = 8;
+		solver.iterations = 1;
+		let (energy, sol, unsatisfied) = solver.solve_with_constraints().unwrap();
+		assert_eq!(hmlt.evaluate(&sol.to_map()), Some(energy));
+		(energy, unsatisfied.into_iter().copied().collect::<Vec<_>>())
+	};
+	// The penalty is not tuned, so a weak one is cheaper than the objective.
+	assert_eq!(solve(3.0), (3.0, vec!["pair"]));
+	assert_eq!(solve(10.0), (5.0, vec![]));
+}
+
+#[test]
+fn integer_strength_test() {
+	fn b(i: usize) -> Expr<(), usize, &'static str, i64> {
+		Expr::Binary(i)
+	}
+	// With two constraints, each placeholder gets half of `coeff_strength`,
+	// which must not be truncated to zero for integer types.
+	let hmlt = -3 * b(0) - 3 * b(1)
+		+ Expr::Constraint {
+			label: "a",
+			expr: Box::new(b(0)),
+		} + Expr::Constraint {
+		label: "b",
+		expr: Box::new(b(1)),
+	};
+	let compiled = hmlt.compile();
+	let mut solver = SimpleSolver::new(&compiled).beta(BetaType::Schedule(vec![100]));
+	solver.samples = 8;
+	solver.iterations = 1;
+	solver.generations = 1;
+	let (energy, sol, unsatisfied, stats) = solver.solve_with_stats().unwrap();
+	assert!(unsatisfied.is_empty());
+	assert_eq!(energy, 0);
+	assert!(!sol[&0] && !sol[&1]);
+	assert!(stats.strengths.values().all(|s| *s > 0));
+}
+
+#[test]
+fn debug_qubo_test() {
+	let hmlt = Expr::Constraint {
+		label: "constraint1",
+		expr: Box::new((Expr::Binary(0) + Expr::Binary(1) - Expr::Number(1)) ^ 2usize),
+	} + Expr::Binary(0) * Expr::Number(30);
+	let compiled = hmlt.compile();
+	let calls = Arc::new(AtomicUsize::new(0));
+	let mut solver = SimpleSolver::new(&compiled).with_debug_hook({
+		let calls = calls.clone();
+		move |_, _, _, _| {
+			calls.fetch_add(1, Ordering::SeqCst);
+		}
+	});
+	// The only constraint gets the whole `coeff_strength` of 50.
+	let (c, model, qubits) = solver.debug_qubo();
+	assert_eq!(qubits, vec![&Qubit::Qubit(0), &Qubit::Qubit(1)]);
+	assert_eq!(c, 50);
+	assert_eq!(model.get_weight(&[0, 0]), -20);
+	assert_eq!(model.get_weight(&[1, 1]), -50);
+	assert_eq!(model.get_weight(&[0, 1]), 100);
+	assert_eq!(calls.load(Ordering::SeqCst), 0);
+	solver.iterations = 1;
+	solver.generations = 1;
+	solver.solve_with_constraints().unwrap();
+	assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+/// Structured solver of a complete graph with the given number of nodes.
+struct CompleteGraph(usize);
+
+impl<'a> SolverGenerator<'a, FixedSingleQuadricModel<Binary<f64>>> for CompleteGraph {
+	type SolverType = <SimulatedAnnealerGenerator<'static, FixedSingleQuadricModel<Binary<f64>>> as SolverGenerator<'a, FixedSingleQuadricModel<Binary<f64>>>>::SolverType;
+	type ErrorType = classical_solver::SimulatedAnnealerError;
+
+	fn generate(
+		&self,
+		model: &'a FixedSingleQuadricModel<Binary<f64>>,
+	) -> Result<Self::SolverType, Self::ErrorType> {
+		SimulatedAnnealerGenerator::new().generate(model)
+	}
+}
+
+impl<'a> StructuredSolverGenerator<'a, FixedSingleQuadricModel<Binary<f64>>> for CompleteGraph {
+	fn nodes(&self) -> Box<dyn Iterator<Item = usize>> {
+		Box::new(0..self.0)
+	}
+
+	fn prods(&self) -> Box<dyn Iterator<Item = BTreeSet<usize>>> {
+		let n = self.0;
+		Box::new(
+			(0..n).flat_map(move |i| (i + 1..n).map(move |j| vec![i, j].into_iter().collect())),
+		)
+	}
+}
+
+#[test]
+fn structured_solver_test() {
+	let hmlt = Expr::Constraint {
+		label: "constraint1",
+		expr: Box::new((Expr::Binary(0) + Expr::Binary(1) - Expr::Number(1.0)) ^ 2usize),
+	} + Expr::Binary(0) * Expr::Number(30.0);
+	let compiled = hmlt.compile();
+	let mut solver = SimpleSolver::with_structured_solver(
+		&compiled,
+		CompleteGraph(4),
+		Box::new(IdentityEmbedder),
+	);
+	solver.samples = 8;
+	let (c, sol, unsatisfied) = solver.solve_with_constraints().unwrap();
+	assert_eq!(c, 0.0);
+	assert!(!sol[&0] && sol[&1]);
+	assert!(unsatisfied.is_empty());
+
+	let solver = SimpleSolver::with_structured_solver(
+		&compiled,
+		CompleteGraph(1),
+		Box::new(IdentityEmbedder),
+	);
+	assert!(matches!(
+		solver.solve_with_constraints(),
+		Err(SolveError::Solver(EmbeddingError::NotFound))
+	));
+}
+
+#[test]
+fn constraint_violations_test() {
+	let violation = |n: usize| {
+		let hmlt: Expr<(), usize, &str, f64> = Expr::Constraint {
+			label: "one-hot",
+			expr: Box::new((0..n).fold(-Expr::one(), |e, i| e + Expr::Binary(i)) ^ 2),
+		} - (0..n)
+			.fold(Expr::zero(), |e, i| e + Expr::Binary(i));
+		let compiled = hmlt.compile();
+		let mut solver = SimpleSolver::new(&compiled).beta(BetaType::Schedule(vec![100.0]));
+		solver.samples = 8;
+		let penalties = vec![("one-hot", 0.0)].into_iter().collect();
+		let (_, sol, unsatisfied) = solver.solve_fixed(penalties).unwrap();
+		assert_eq!(unsatisfied, vec![&"one-hot"]);
+		let violations = solver.constraint_violations(&sol);
+		assert_eq!(violations.len(), 1);
+		assert_eq!(violations[0].0, &"one-hot");
+		violations[0].1
+	};
+	// All the bits are set, and the violation is the penalty energy of the
+	// extra bits.
+	assert_eq!(violation(3), 4.0);
+	assert_eq!(violation(5), 16.0);
+}
+
+/// Generator of solvers which return the given solutions in order, one for
+/// each generated solver.
+struct ScriptedGenerator(Mutex<Vec<Vec<bool>>>);
+
+struct ScriptedSolver(Vec<bool>);
+
+impl<'a> SolverGenerator<'a, FixedSingleQuadricModel<Binary<f64>>> for ScriptedGenerator {
+	type SolverType = ScriptedSolver;
+	type ErrorType = std::fmt::Error;
+
+	fn generate(
+		&self,
+		_model: &'a FixedSingleQuadricModel<Binary<f64>>,
+	) -> Result<ScriptedSolver, std::fmt::Error> {
+		Ok(ScriptedSolver(self.0.lock().unwrap().remove(0)))
+	}
+}
+
+impl<'a> UnstructuredSolverGenerator<'a, FixedSingleQuadricModel<Binary<f64>>>
+	for ScriptedGenerator
+{
+	type Order = Quadric;
+
+	fn order(&self) -> Quadric {
+		Quadric
+	}
+}
+
+impl Solver for ScriptedSolver {
+	type ErrorType = std::fmt::Error;
+	type SolutionType = SingleSolution<Binary<f64>>;
+}
+
+impl ClassicalSolver for ScriptedSolver {
+	fn solve_with_rng<T: rand::Rng>(
+		&self,
+		_r: &mut T,
+	) -> Result<Vec<SingleSolution<Binary<f64>>>, std::fmt::Error> {
+		Ok(vec![SingleSolution::from_vec(&self.0)])
+	}
+}
+
+#[test]
+fn best_across_iterations_test() {
+	let hmlt: Expr<(), usize, &str, f64> = Expr::Constraint {
+		label: "a",
+		expr: Box::new(Expr::Binary(0)),
+	} + Expr::Constraint {
+		label: "b",
+		expr: Box::new(Expr::Binary(1)),
+	};
+	let compiled = hmlt.compile();
+	// The second iteration finds a solution violating more constraints.
+	let script = vec![vec![true, false], vec![true, true]];
+	let mut solver =
+		SimpleSolver::with_solver(&compiled, ScriptedGenerator(Mutex::new(script))).with_seed(0);
+	solver.iterations = 2;
+	solver.generations = 1;
+	solver.samples = 1;
+	let (_, sol, unsatisfied) = solver.solve_with_constraints().unwrap();
+	assert_eq!(sol.get(&0), Some(true));
+	assert_eq!(sol.get(&1), Some(false));
+	assert_eq!(unsatisfied, vec![&"a"]);
+}
+
+#[test]
+fn constraint_tolerance_test() {
+	let unsatisfied = |tol: f64| {
+		let hmlt: Expr<(), usize, &str, f64> = Expr::Constraint {
+			label: "exact",
+			expr: Box::new(Expr::Binary(0usize) - 1.0),
+		} + Expr::Constraint {
+			label: "noisy",
+			expr: Box::new(Expr::Binary(1usize) - 1.0 + 5.0e-5),
+		} - Expr::Binary(0)
+			- Expr::Binary(1);
+		let compiled = hmlt.compile();
+		let mut solver = SimpleSolver::new(&compiled).beta(BetaType::Schedule(vec![100.0]));
+		solver.samples = 8;
+		solver.constraint_tolerance = tol;
+		let penalties = vec![("exact", 0.0), ("noisy", 0.0)].into_iter().collect();
+		let (_, sol, unsatisfied) = solver.solve_fixed(penalties).unwrap();
+		assert_eq!(sol.get(&0), Some(true));
+		assert_eq!(sol.get(&1), Some(true));
+		unsatisfied.into_iter().cloned().collect::<Vec<_>>()
+	};
+	assert_eq!(unsatisfied(1.0e-4), Vec::<&str>::new());
+	assert_eq!(unsatisfied(1.0e-5), vec!["noisy"]);
+	// The exact constraint is satisfied without tolerance.
+	assert_eq!(unsatisfied(0.0), vec!["noisy"]);
+}
+
+#[test]
+fn constraint_energies_test() {
+	let objective: Expr<(), usize, &str, f64> =
+		(0..3usize).fold(Expr::zero(), |e, i| e - Expr::Binary(i) * 3.0);
+	let pair = |label, i, j| Expr::Constraint {
+		label,
+		expr: Box::new(Expr::Binary(i) * Expr::Binary(j)),
+	};
+	let hmlt = objective.clone() + pair("a", 0, 1) + pair("b", 1, 2);
+	let compiled = hmlt.compile();
+	let mut solver = SimpleSolver::new(&compiled).beta(BetaType::Schedule(vec![100.0]));
+	solver.samples = 8;
+	// Each of the two constraints gets the strength `coeff_strength / 2` in
+	// the first generation, too weak to be satisfied.
+	solver.iterations = 1;
+	solver.generations = 1;
+	solver.coeff_strength = 2.0;
+	let (energy, sol, _, stats) = solver.solve_with_stats().unwrap();
+	let energies = stats.constraint_energies;
+	assert_eq!(energies.len(), 2);
+	assert_eq!(energies[&"a"], 1.0);
+	assert_eq!(energies[&"b"], 1.0);
+	let value = objective.evaluate(&sol.to_map()).unwrap();
+	assert!((energies.values().sum::<f64>() + value - energy).abs() < 1.0e-9);
+
+	// The strengths of the generation where the solution was found are used.
+	let mut solver = SimpleSolver::new(&compiled).beta(BetaType::Schedule(vec![100.0]));
+	solver.samples = 8;
+	let (energy, sol, _, stats) = solver.solve_with_stats().unwrap();
+	let objective = objective.evaluate(&sol.to_map()).unwrap();
+	let penalty = stats.constraint_energies.values().sum::<f64>();
+	assert!((penalty + objective - energy).abs() < 1.0e-9);
+}
+
+#[test]
+fn soft_constraint_energies_test() {
+	let b = |i: usize| Expr::Binary(i);
+	let objective: Expr<(), usize, &str, f64> = -b(0) - b(1) - 4.0 * b(2);
+	let hmlt = objective.clone()
+		+ Expr::Constraint {
+			label: "hard",
+			expr: Box::new(b(0) * b(1)),
+		} + Expr::soft_constraint("prefer", b(0) - 1.0, 3.0)
+		+ Expr::soft_constraint("avoid", b(0), 1.0);
+	let compiled = hmlt.compile();
+	let mut solver = SimpleSolver::new(&compiled).beta(BetaType::Schedule(vec![100.0]));
+	solver.samples = 8;
+	// The objective is ignored, but the soft penalties are kept while looking
+	// for a feasible solution.
+	let (sol, unsatisfied) = solver.solve_feasible(None).unwrap();
+	assert_eq!(unsatisfied, vec![&"avoid"]);
+	assert!(sol[&0] && !sol[&1]);
+
+	let (energy, sol, unsatisfied, stats) = solver.solve_with_stats().unwrap();
+	assert_eq!(unsatisfied, vec![&"avoid"]);
+	assert!(sol[&0] && !sol[&1] && sol[&2]);
+	let energies = stats.constraint_energies;
+	assert_eq!(energies.len(), 3);
+	assert_eq!(energies[&"hard"], 0.0);
+	assert_eq!(energies[&"prefer"], 0.0);
+	assert_eq!(energies[&"avoid"], 1.0);
+	let objective = objective.evaluate(&sol.to_map()).unwrap();
+	assert!((energies.values().sum::<f64>() + objective - energy).abs() < 1.0e-9);
+}
+
+#[test]
+fn exact_solver_test() {
+	// The examples in the documentation of the crate
+	let hmlt =
+		-Expr::Spin("a") * Expr::Spin("b") * Expr::Number(2) + Expr::Spin("a") * Expr::Number(3);
+	let compiled = hmlt.compile();
+	let (c, qubits) = SimpleSolver::new_exact(&compiled).solve().unwrap();
+	assert_eq!(c, -5);
+	assert_eq!(qubits.get(&"a"), Some(false));
+	assert_eq!(qubits.get(&"b"), Some(false));
+	let (sa, _) = SimpleSolver::new(&compiled).solve().unwrap();
+	assert!(c <= sa);
+
+	let hmlt = Expr::Constraint {
+		label: "constraint1",
+		expr: Box::new((Expr::Binary(0) + Expr::Binary(1) - Expr::Number(1)) ^ 2usize),
+	} + Expr::Binary(0) * Expr::Number(30);
+	let compiled = hmlt.compile();
+	let exact = SimpleSolver::new_exact(&compiled);
+	let (c, qubits, unsatisfied) = exact.solve_with_constraints().unwrap();
+	assert_eq!(c, 0);
+	assert_eq!(qubits.get(&0), Some(false));
+	assert_eq!(qubits.get(&1), Some(true));
+	assert!(unsatisfied.is_empty());
+	let solver = SimpleSolver::new(&compiled);
+	let (sa, _, unsatisfied) = solver.solve_with_constraints().unwrap();
+	assert!(unsatisfied.is_empty());
+	assert!(c <= sa);
+
+	let hmlt: Expr<(), usize, (), f64> =
+		(0..30usize).fold(Expr::zero(), |e, i| e + Expr::Binary(i));
+	let compiled = hmlt.compile();
+	assert!(matches!(
+		SimpleSolver::new_exact(&compiled).solve(),
+		Err(SolveError::Solver(ExactSolverError::TooLarge {
+			size: 30,
+			max_size: 25
+		}))
+	));
+}
+
+#[test]
+fn solution_view_display_test() {
+	let b = |i: usize| Expr::Binary(i);
+	let hmlt: Expr<(), usize, (), f64> = -b(10) + b(2) - b(1);
+	let compiled = hmlt.compile();
+	let mut solver = SimpleSolver::new(&compiled).beta(BetaType::Schedule(vec![100.0]));
+	solver.samples = 8;
+	let (_, sol) = solver.solve().unwrap();
+	assert_eq!(format!("{}", sol), "1  = 1\n2  = 0\n10 = 1");
+	assert_eq!(
+		format!("{:#}", sol),
+		"energy = -2\n1  = 1  local_field = 1\n2  = 0  local_field = 1\n10 = 1  local_field = 1"
+	);
+	assert_eq!(
+		sol.to_csv(),
+		"label,value,local_field\n1,1,1\n2,0,1\n10,1,1\n"
+	);
+	// The order does not depend on the internal map.
+	for _ in 0..5 {
+		let (_, other) = solver.solve().unwrap();
+		assert_eq!(format!("{:#}", other), format!("{:#}", sol));
+	}
+	let hmlt: Expr<(), (usize, &str), (), f64> = -Expr::Binary((0, "a\"b"));
+	let compiled = hmlt.compile();
+	let solver = SimpleSolver::new(&compiled).beta(BetaType::Schedule(vec![100.0]));
+	let (_, sol) = solver.solve().unwrap();
+	assert_eq!(
+		sol.to_csv(),
+		"label,value,local_field\n\"(0, \"\"a\\\"\"b\"\")\",1,1\n"
+	);
+}
+
+#[test]
+fn sweep_weights_test() {
+	let b = |i: usize| Expr::Binary(i);
+	// x0 is set iff a > b, and x1 is set iff a > 2b.
+	let hmlt: Expr<&str, usize, (), f64> =
+		Expr::Placeholder("a") * (2.0 - b(0) - b(1)) + Expr::Placeholder("b") * (b(0) + 2.0 * b(1));
+	let compiled = hmlt.compile();
+	let fed = compiled
+		.clone()
+		.feed_dict(vec![("a", 1.0), ("b", 1.0)].into_iter().collect());
+	let generations = Arc::new(AtomicUsize::new(0));
+	let generations_hook = generations.clone();
+	let mut solver = SimpleSolver::new(&fed)
+		.beta(BetaType::Schedule(vec![100.0]))
+		.with_debug_hook(move |_, _, _, _| {
+			generations_hook.fetch_add(1, Ordering::SeqCst);
+		});
+	solver.samples = 8;
+	// Without constraints, the first generation stops the solve.
+	solver.iterations = 1;
+	let weights = [(1.0, 2.0), (3.0, 2.0), (5.0, 2.0)];
+	let sweep = solver
+		.sweep_weights(&compiled, &"a", &"b", &weights)
+		.unwrap();
+	let results = sweep
+		.iter()
+		.map(|(w, e, sol)| (*w, *e, sol[&0], sol[&1]))
+		.collect::<Vec<_>>();
+	assert_eq!(
+		results,
+		vec![
+			((1.0, 2.0), 2.0, false, false),
+			((3.0, 2.0), 5.0, true, false),
+			((5.0, 2.0), 6.0, true, true),
+		]
+	);
+	// The hook of the solver is called for every pair.
+	assert_eq!(generations.load(Ordering::SeqCst), 3);
+	let hmlt: Expr<&str, usize, (), f64> =
+		Expr::Placeholder("a") * b(0) + Expr::Placeholder("c") * b(1);
+	assert!(solver
+		.sweep_weights(&hmlt.compile(), &"a", &"b", &weights)
+		.is_err());
+	let hmlt: Expr<&str, usize, (), f64> =
+		Expr::Placeholder("a") * b(0) + Expr::Placeholder("b") * b(2);
+	assert!(solver
+		.sweep_weights(&hmlt.compile(), &"a", &"b", &weights)
+		.is_err());
+}
+
+#[test]
+fn patience_test() {
+	let compiled = one_hot(4).compile();
+	let solve = |iterations: usize, stops_solve: bool| {
+		// The population is collected after the feasible optimum is found, so
+		// only the patience stops the iterations.
+		let generations = Arc::new(AtomicUsize::new(0));
+		let generations_hook = generations.clone();
+		let mut solver =
+			SimpleSolver::new(&compiled)
+				.with_seed(0)
+				.with_debug_hook(move |_, _, _, _| {
+					generations_hook.fetch_add(1, Ordering::SeqCst);
+				});
+		solver.samples = 8;
+		solver.iterations = iterations;
+		solver.patience = Some(2);
+		solver.patience_stops_solve = stops_solve;
+		let population = solver.solve_population().unwrap();
+		assert_eq!(population[0].0, 0.0);
+		assert_eq!(population[0].1.get(&0), Some(true));
+		generations.load(Ordering::SeqCst)
+	};
+	// An iteration stops two generations after the last improvement, before
+	// the 30 generations by default.
+	let single = solve(1, false);
+	assert!((3..30).contains(&single));
+	// The first iteration is the same with the seed.
+	assert_eq!(solve(3, true), single);
+	let all = solve(3, false);
+	assert!(all >= single + 6 && all < 90);
+}
+
+#[test]
+fn propagate_units_test() {
+	let b = |i: usize| Expr::Binary(i);
+	let hmlt: Expr<(), usize, (), f64> =
+		((b(0) - b(1)) ^ 2usize) - 2.0 * b(2) + 3.0 * b(3) + b(0) * b(4) * b(5);
+	let (compiled, fixed) = hmlt.clone().compile().propagate_units();
+	assert_eq!(fixed.len(), 2);
+	assert!(fixed[&2]);
+	assert!(!fixed[&3]);
+	assert_eq!(compiled.qubits_indexed().len(), 5);
+	let (c, sol) = SimpleSolver::new_exact(&compiled).solve().unwrap();
+	assert_eq!(c, -2.0);
+	assert_eq!(sol.len(), 6);
+	assert_eq!(sol.get(&2), Some(true));
+	assert_eq!(sol.get(&3), Some(false));
+	assert_eq!(sol.local_field(&2), None);
+	assert_eq!(hmlt.evaluate(&sol.to_map()), Some(c));
+	assert!(sol.to_csv().contains("2,1"));
+}
+
+#[test]
+fn ranked_by_field_test() {
+	let b = |s: &'static str| Expr::Binary(s);
+	let hmlt: Expr<(), &str, (), f64> = -10.0 * b("a") + b("b") - 2.0 * b("c");
+	let compiled = hmlt.compile();
+	let (_, sol) = SimpleSolver::new_exact(&compiled).solve().unwrap();
+	assert_eq!(
+		sol.ranked_by_field(),
+		vec![(&"a", 10.0), (&"c", 2.0), (&"b", 1.0)]
+	);
+	assert_eq!(sol.frozen_variables(1.5), vec![&"a", &"c"]);
+	assert_eq!(sol.frozen_variables(10.0), Vec::<&&str>::new());
+}
@@ -1,30 +1,20 @@
 extern crate rustqubo;
 use rustqubo::solve::SimpleSolver;
 use rustqubo::Expr;
+use std::collections::HashMap;
 
-#[allow(unused)]
-fn run_tsp() {
-	#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash, PartialOrd, Ord)]
-	struct TspQubit(usize, usize);
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash, PartialOrd, Ord)]
+struct TspQubit(usize, usize);
 
+fn tsp_hamiltonian() -> Expr<(), TspQubit, String, f64> {
 	let cities = 5;
 	let hmlt_city = (0..cities).into_iter().fold(Expr::zero(), |exp, c| {
-		let inner = (0..cities)
-			.into_iter()
-			.fold(-Expr::one(), |e, o| e + Expr::Binary(TspQubit(c, o)));
-		exp + Expr::Constraint {
-			label: format!("city {:}", c),
-			expr: Box::new(inner ^ 2),
-		}
+		let qubits = (0..cities).map(|o| TspQubit(c, o)).collect();
+		exp + Expr::sum_equals(format!("city {:}", c), qubits, 1)
 	});
 	let hmlt_order = (0..cities).into_iter().fold(Expr::zero(), |exp, o| {
-		let inner = (0..cities)
-			.into_iter()
-			.fold(-Expr::one(), |e, c| e + Expr::Binary(TspQubit(c, o)));
-		exp + Expr::Constraint {
-			label: format!("order {:}", o),
-			expr: Box::new(inner ^ 2),
-		}
+		let qubits = (0..cities).map(|c| TspQubit(c, o)).collect();
+		exp + Expr::sum_equals(format!("order {:}", o), qubits, 1)
 	});
 	let table = [
 		[0.0, 5.0, 5.0, 3.0, 4.5],
@@ -44,8 +34,12 @@ fn run_tsp() {
 			}
 		}
 	}
-	let hmlt = 10.0_f64 * (hmlt_city + hmlt_order) + hmlt_distance;
-	let compiled = hmlt.compile();
+	10.0_f64 * (hmlt_city + hmlt_order) + hmlt_distance
+}
+
+#[allow(unused)]
+fn run_tsp() {
+	let compiled = tsp_hamiltonian().compile();
 	let mut solver = SimpleSolver::new(&compiled);
 	solver.generations = 10;
 	solver.iterations = 1;
@@ -60,13 +54,74 @@ fn tsp_test() {
 	run_tsp();
 }
 
+#[test]
+fn solve_fixed_test() {
+	let compiled = tsp_hamiltonian().compile();
+	let mut solver = SimpleSolver::new(&compiled);
+	solver.samples = 8;
+	let (_, _, constraints) = solver.solve_with_constraints().unwrap();
+	assert!(constraints.is_empty());
+	let penalties = (0..5)
+		.flat_map(|i| vec![format!("city {:}", i), format!("order {:}", i)])
+		.map(|label| (label, 10.0))
+		.collect::<HashMap<_, _>>();
+	let (_, _, constraints) = solver.solve_fixed(penalties).unwrap();
+	assert!(constraints.is_empty());
+	assert!(solver.solve_fixed(HashMap::new()).is_err());
+}
+
 #[test]
 fn test() {
 	let exp = -10_i32 * Expr::Binary(1) + 5_i32 * Expr::Binary(2) + 12_i32;
 	let compiled = exp.compile();
-	let solver = SimpleSolver::new(&compiled);
+	let solver = SimpleSolver::new(&compiled).with_seed(0);
 	let (c, sol) = solver.solve().unwrap();
 	assert_eq!(sol.get(&1).unwrap(), true);
 	assert_eq!(sol.get(&2).unwrap(), false);
 	assert_eq!(c, 2);
 }
+
+#[test]
+fn parallel_iterations_test() {
+	let hmlt = tsp_hamiltonian();
+	let compiled = hmlt.clone().compile();
+	let mut solver = SimpleSolver::new(&compiled).with_seed(3);
+	solver.samples = 8;
+	solver.iterations = 4;
+	solver.generations = 10;
+	let (sequential_energy, _, sequential) = solver.solve_with_constraints().unwrap();
+	let sequential = sequential.len();
+	solver.parallel_iterations = true;
+	let (energy, sol, parallel) = solver.solve_with_constraints().unwrap();
+	assert!(parallel.len() <= sequential);
+	assert!(parallel.is_empty());
+	assert!(energy <= sequential_energy);
+	// Each city is visited exactly once.
+	let map = sol.to_map();
+	for i in 0..5 {
+		assert_eq!((0..5).filter(|o| map[&TspQubit(i, *o)]).count(), 1);
+		assert_eq!((0..5).filter(|c| map[&TspQubit(*c, i)]).count(), 1);
+	}
+	assert_eq!(hmlt.evaluate(&map), Some(energy));
+}
+
+#[test]
+fn variables_test() {
+	let hmlt = tsp_hamiltonian();
+	let variables = hmlt.variables();
+	assert_eq!(variables.len(), 25);
+	for c in 0..5 {
+		for o in 0..5 {
+			assert!(variables.contains(&TspQubit(c, o)));
+		}
+	}
+	assert!(hmlt.placeholders().is_empty());
+	let with_placeholder = Expr::Placeholder(()) * hmlt;
+	assert_eq!(
+		with_placeholder
+			.placeholders()
+			.into_iter()
+			.collect::<Vec<_>>(),
+		vec![&()]
+	);
+}
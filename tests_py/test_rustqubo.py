@@ -0,0 +1,100 @@
+import numpy as np
+import pytest
+
+from rustqubo import CompiledModel, Expr, SimpleSolver
+
+
+def one_hot() -> Expr:
+    a, b = Expr.binary("a"), Expr.binary("b")
+    return Expr.constraint("one", (a + b - 1) ** 2) + 3 * a
+
+
+def test_expr_operators() -> None:
+    a, b = Expr.binary("a"), Expr.binary("b")
+    for expr in [a + b, a + 1, 1 + a, a - b, a - 1, 1 - a, a * b, 2 * a, a * 2, -a]:
+        assert isinstance(expr, Expr)
+    assert isinstance(a**2, Expr)
+    assert isinstance(Expr.spin("s") * Expr.number(0.5), Expr)
+    assert "a" in repr(a)
+
+
+def test_to_numpy_qubo() -> None:
+    a, b = Expr.binary("a"), Expr.binary("b")
+    compiled = (2 * a * b - a).compile()
+    assert isinstance(compiled, CompiledModel)
+    matrix, labels = compiled.to_numpy_qubo({})
+    assert isinstance(matrix, np.ndarray)
+    assert matrix.shape == (2, 2)
+    assert sorted(labels) == ["a", "b"]
+    i, j = labels.index("a"), labels.index("b")
+    assert matrix[i, i] == -1.0
+    assert matrix[j, j] == 0.0
+    assert matrix[min(i, j), max(i, j)] == 2.0
+    assert matrix[max(i, j), min(i, j)] == 0.0
+
+
+def test_to_numpy_qubo_placeholder() -> None:
+    compiled = (Expr.placeholder("w") * Expr.binary("a")).compile()
+    matrix, labels = compiled.to_numpy_qubo({"w": 3.0})
+    assert labels == ["a"]
+    assert matrix[0, 0] == 3.0
+
+
+def test_to_numpy_qubo_ancilla() -> None:
+    a, b, c = Expr.binary("a"), Expr.binary("b"), Expr.binary("c")
+    matrix, labels = (a * b * c).compile().to_numpy_qubo({})
+    assert matrix.shape == (4, 4)
+    assert sorted(labels[:3]) == ["a", "b", "c"]
+    assert labels[3].startswith("_ancilla")
+
+
+def test_compiled_solve() -> None:
+    compiled = (-2 * Expr.binary("a")).compile()
+    energy, qubits, unsatisfied = compiled.solve()
+    assert energy == (-2.0 if qubits["a"] else 0.0)
+    assert unsatisfied == []
+
+
+def test_feed_dict() -> None:
+    compiled = (Expr.placeholder("w") * Expr.binary("a")).compile()
+    fed = compiled.feed_dict({"w": -2.0})
+    assert isinstance(fed, CompiledModel)
+    energy, qubits, _ = fed.solve()
+    assert energy == (-2.0 if qubits["a"] else 0.0)
+    # The values given to solve() take precedence.
+    energy, qubits, _ = fed.solve({"w": 2.0})
+    assert energy == (2.0 if qubits["a"] else 0.0)
+    assert fed.to_numpy_qubo({})[0][0, 0] == -2.0
+
+
+def test_simple_solver() -> None:
+    solver = SimpleSolver(one_hot().compile())
+    assert solver.iterations > 0
+    assert solver.samples > 0
+    assert solver.generations > 0
+    assert solver.coeff_strength > 0.0
+    solver.samples = 16
+    solver.iterations = 2
+    solver.generations = 10
+    assert solver.samples == 16
+    energy, qubits, unsatisfied = solver.solve()
+    assert energy == 0.0
+    assert qubits == {"a": False, "b": True}
+    assert unsatisfied == []
+
+
+def test_simple_solver_feed_dict() -> None:
+    hmlt = Expr.placeholder("w") * Expr.binary("a") + Expr.binary("b")
+    solver = SimpleSolver(hmlt.compile(), {"w": -2.0})
+    solver.samples = 16
+    energy, qubits, unsatisfied = solver.solve()
+    assert energy == -2.0
+    assert qubits == {"a": True, "b": False}
+    assert unsatisfied == []
+
+
+def test_simple_solver_invalid_parameter() -> None:
+    solver = SimpleSolver(one_hot().compile())
+    solver.generations = 0
+    with pytest.raises(RuntimeError):
+        solver.solve()